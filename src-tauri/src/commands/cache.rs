@@ -1,3 +1,4 @@
+use crate::error::LexiError;
 use crate::services::CacheDb;
 use tauri::State;
 
@@ -6,7 +7,7 @@ pub fn get_cached(
     cid: String,
     data_type: String,
     cache_db: State<'_, CacheDb>,
-) -> Result<Option<String>, String> {
+) -> Result<Option<String>, LexiError> {
     cache_db.get_cached(&cid, &data_type)
 }
 
@@ -16,6 +17,6 @@ pub fn set_cached(
     data_type: String,
     data: String,
     cache_db: State<'_, CacheDb>,
-) -> Result<(), String> {
+) -> Result<(), LexiError> {
     cache_db.set_cached(&cid, &data_type, &data)
 }
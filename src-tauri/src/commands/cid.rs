@@ -1,9 +1,250 @@
-use crate::services::compute_file_hash;
+use crate::error::LexiError;
+use crate::media_protocol::register_source;
+use crate::services::{
+    compute_file_hash, compute_sampled_cid, is_sampled_cid, ArtifactStore, CacheDb, JobHandle,
+    JobKind, JobManager,
+};
+use serde::{Deserialize, Serialize};
 use std::path::Path;
+use tauri::{AppHandle, Manager, State};
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CidMode {
+    /// SHA-256 of the whole file
+    #[default]
+    Full,
+    /// Size plus sampled blocks, in milliseconds; follow up with `verify_cid`
+    Sampled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CidStatus {
+    /// The full CID matched; nothing changed
+    Verified,
+    /// A sampled CID was replaced by the full one and its cache moved over
+    Upgraded,
+    /// The file is not what the old CID described; its cache was left behind
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CidVerification {
+    /// Full CID of the file as it is now
+    pub cid: String,
+    #[serde(rename = "previousCid")]
+    pub previous_cid: String,
+    pub status: CidStatus,
+}
+
+#[tauri::command]
+pub async fn generate_cid(
+    app: AppHandle,
+    path: String,
+    mode: Option<CidMode>,
+    jobs: State<'_, JobManager>,
+) -> Result<String, LexiError> {
+    let cid = match mode.unwrap_or_default() {
+        CidMode::Sampled => {
+            let source = path.clone();
+            tokio::task::spawn_blocking(move || compute_sampled_cid(Path::new(&source))).await??
+        }
+        CidMode::Full => {
+            let label = path.clone();
+            let source = path.clone();
+            jobs.run_blocking(JobKind::Hash, label, move |job| full_hash(job, &source))
+                .await?
+        }
+    };
+
+    register_source(&app.state::<CacheDb>(), &cid, &path)?;
+    Ok(cid)
+}
+
+/// Hash the whole file as a background job and check it against `cid`. A
+/// sampled CID of an unchanged file is upgraded to the full CID, moving its
+/// cache entries and derived files along.
 #[tauri::command]
-pub async fn generate_cid(path: String) -> Result<String, String> {
-    tokio::task::spawn_blocking(move || compute_file_hash(Path::new(&path)))
-        .await
-        .map_err(|e| format!("Task failed: {}", e))?
+pub async fn verify_cid(
+    app: AppHandle,
+    path: String,
+    cid: String,
+    jobs: State<'_, JobManager>,
+) -> Result<CidVerification, LexiError> {
+    let label = path.clone();
+    jobs.run_blocking(JobKind::Hash, label, move |job| {
+        let db = app.state::<CacheDb>();
+        let artifacts = app.state::<ArtifactStore>();
+        verify_and_migrate(job, &path, &cid, &db, &artifacts)
+    })
+    .await
+}
+
+/// Move cache entries and derived files written under a sampled CID after
+/// `verify_cid` upgraded it, e.g. by work that was still running at the time.
+/// Safe to repeat; returns the number of files moved.
+#[tauri::command]
+pub fn migrate_cid(
+    from: String,
+    to: String,
+    cache_db: State<'_, CacheDb>,
+    artifacts: State<'_, ArtifactStore>,
+) -> Result<usize, LexiError> {
+    if !is_sampled_cid(&from) || is_sampled_cid(&to) {
+        return Err(LexiError::invalid_input("Only a sampled CID can move to a full one"));
+    }
+    migrate(&cache_db, &artifacts, &from, &to)
+}
+
+/// Whether a CID is sampled and should be verified with `verify_cid`
+#[tauri::command]
+pub fn cid_is_sampled(cid: String) -> bool {
+    is_sampled_cid(&cid)
+}
+
+/// Make a source whose CID is already known (e.g. from a saved project)
+/// available as `lexi://media/<cid>`
+#[tauri::command]
+pub fn register_media_source(
+    path: String,
+    cid: String,
+    cache_db: State<'_, CacheDb>,
+) -> Result<(), LexiError> {
+    register_source(&cache_db, &cid, &path)
+}
+
+fn full_hash(job: &JobHandle, path: &str) -> Result<String, LexiError> {
+    compute_file_hash(Path::new(path), |read, total| {
+        job.check_cancelled()?;
+        if total > 0 {
+            job.progress(read as f64 / total as f64 * 100.0, "Hashing");
+        }
+        Ok(())
+    })
+}
+
+fn verify_and_migrate(
+    job: &JobHandle,
+    path: &str,
+    cid: &str,
+    db: &CacheDb,
+    artifacts: &ArtifactStore,
+) -> Result<CidVerification, LexiError> {
+    let full = full_hash(job, path)?;
+    let status = if is_sampled_cid(cid) {
+        // Re-sample so an edit made since import is not mistaken for an upgrade
+        if compute_sampled_cid(Path::new(path))? == cid {
+            CidStatus::Upgraded
+        } else {
+            CidStatus::Changed
+        }
+    } else if full == cid {
+        CidStatus::Verified
+    } else {
+        CidStatus::Changed
+    };
+
+    if status == CidStatus::Upgraded {
+        migrate(db, artifacts, cid, &full)?;
+    }
+
+    register_source(db, &full, path)?;
+    Ok(CidVerification {
+        cid: full,
+        previous_cid: cid.to_string(),
+        status,
+    })
+}
+
+fn migrate(
+    db: &CacheDb,
+    artifacts: &ArtifactStore,
+    from: &str,
+    to: &str,
+) -> Result<usize, LexiError> {
+    db.migrate_cid(from, to)?;
+    artifacts.rename_cid(from, to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_jobs, FakeRunner};
+    use std::fs;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    async fn verify(
+        path: &Path,
+        cid: &str,
+        db: &Arc<CacheDb>,
+        artifacts: &Arc<ArtifactStore>,
+    ) -> CidVerification {
+        let (path, cid) = (path.to_string_lossy().to_string(), cid.to_string());
+        let (db, artifacts) = (db.clone(), artifacts.clone());
+        test_jobs(FakeRunner::new())
+            .run_blocking(JobKind::Hash, "test", move |job| {
+                verify_and_migrate(job, &path, &cid, &db, &artifacts)
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn upgrades_sampled_cid_and_moves_its_cache() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+        let video = dir.path().join("shoot.mov");
+        fs::write(&video, vec![7u8; 64 * 1024]).unwrap();
+
+        let sampled = compute_sampled_cid(&video).unwrap();
+        let sprite = artifacts.path("filmstrips", &sampled, "a.jpg").unwrap();
+        fs::write(&sprite, b"jpg").unwrap();
+        let json = format!(r#"{{"imagePath":"{}"}}"#, sprite.display());
+        db.set_cached(&sampled, "filmstrip_a", &json).unwrap();
+
+        let result = verify(&video, &sampled, &db, &artifacts).await;
+        assert_eq!(result.status, CidStatus::Upgraded);
+        assert!(!is_sampled_cid(&result.cid));
+        assert_eq!(db.get_cached(&sampled, "filmstrip_a").unwrap(), None);
+
+        let moved = artifacts.existing("filmstrips", &result.cid, "a.jpg");
+        assert!(moved.exists() && !sprite.exists());
+        let cached = db.get_cached(&result.cid, "filmstrip_a").unwrap().unwrap();
+        assert!(cached.contains(&moved.display().to_string()));
+
+        // Verifying the full CID again changes nothing
+        let again = verify(&video, &result.cid, &db, &artifacts).await;
+        assert_eq!(again.status, CidStatus::Verified);
+        assert_eq!(again.cid, result.cid);
+
+        // Work that was still running wrote under the sampled CID; migrating again moves it
+        let late = artifacts.path("thumbnails", &sampled, "medium.jpg").unwrap();
+        fs::write(&late, b"jpg").unwrap();
+        db.set_cached(&sampled, "transcript", "{}").unwrap();
+        assert_eq!(migrate(&db, &artifacts, &sampled, &result.cid).unwrap(), 1);
+        assert!(artifacts.existing("thumbnails", &result.cid, "medium.jpg").exists());
+        assert_eq!(db.get_cached(&result.cid, "transcript").unwrap().as_deref(), Some("{}"));
+        assert_eq!(db.get_cached(&sampled, "transcript").unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn leaves_cache_behind_when_the_file_changed() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+        let video = dir.path().join("shoot.mov");
+        fs::write(&video, b"first take").unwrap();
+
+        let sampled = compute_sampled_cid(&video).unwrap();
+        db.set_cached(&sampled, "transcript", "{}").unwrap();
+        fs::write(&video, b"second take").unwrap();
+
+        let result = verify(&video, &sampled, &db, &artifacts).await;
+        assert_eq!(result.status, CidStatus::Changed);
+        assert_eq!(db.get_cached(&sampled, "transcript").unwrap().as_deref(), Some("{}"));
+        assert_eq!(db.get_cached(&result.cid, "transcript").unwrap(), None);
+    }
 }
@@ -0,0 +1,448 @@
+use super::projects::{Sentence, TimelineEntry, Word};
+use crate::error::LexiError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Candidates scoring below this are not returned unless overridden
+const DEFAULT_MIN_SCORE: f64 = 0.5;
+
+/// A gap this long next to an ambiguous filler suggests it is not part of the phrase
+const PAUSE_CUE_SECONDS: f64 = 0.25;
+
+/// Repeats closer together than this are almost always stutters
+const QUICK_REPEAT_SECONDS: f64 = 0.3;
+
+/// Transcription confidence below which a repeated word is more likely noise
+const LOW_WORD_CONFIDENCE: f64 = 0.5;
+
+/// Built-in lexicon for one language
+struct Lexicon {
+    /// Hesitation sounds that are never meaningful ("um")
+    hesitations: &'static [&'static str],
+    /// Words that are fillers only in some positions ("like")
+    ambiguous: &'static [&'static str],
+    /// Multi-word fillers ("you know")
+    phrases: &'static [&'static str],
+}
+
+fn builtin_lexicon(language: &str) -> Option<Lexicon> {
+    let lexicon = match language {
+        "en" => Lexicon {
+            hesitations: &["um", "umm", "uh", "uhh", "uhm", "erm", "er", "ah", "hmm", "mm"],
+            ambiguous: &["like", "so", "basically", "actually", "literally", "right", "okay"],
+            phrases: &["you know", "i mean", "sort of", "kind of", "you see"],
+        },
+        "es" => Lexicon {
+            hesitations: &["eh", "em", "mmm", "este"],
+            ambiguous: &["pues", "bueno", "vale", "tipo"],
+            phrases: &["o sea", "es decir", "a ver"],
+        },
+        "fr" => Lexicon {
+            hesitations: &["euh", "heu", "hum", "bah", "ben"],
+            ambiguous: &["genre", "voilà", "bon", "quoi"],
+            phrases: &["tu vois", "en fait", "du coup"],
+        },
+        "de" => Lexicon {
+            hesitations: &["äh", "ähm", "öh", "hm", "hmm"],
+            ambiguous: &["also", "halt", "eben", "quasi", "sozusagen"],
+            phrases: &["weißt du", "ich meine"],
+        },
+        "pt" => Lexicon {
+            hesitations: &["hum", "ahn", "éh"],
+            ambiguous: &["tipo", "então", "né", "sabe"],
+            phrases: &["quer dizer", "tipo assim"],
+        },
+        _ => return None,
+    };
+    Some(lexicon)
+}
+
+/// Lexicon and scoring settings; everything is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DisfluencyOptions {
+    /// Language of the built-in lexicon, as an ISO 639-1 code (default "en")
+    pub language: Option<String>,
+    /// Extra hesitation words, always flagged
+    #[serde(rename = "extraFillers")]
+    pub extra_fillers: Option<Vec<String>>,
+    /// Extra multi-word fillers
+    #[serde(rename = "extraPhrases")]
+    pub extra_phrases: Option<Vec<String>>,
+    /// Words or phrases never flagged as fillers
+    pub ignore: Option<Vec<String>>,
+    /// Drop candidates scoring below this, 0-1 (default 0.5)
+    #[serde(rename = "minScore")]
+    pub min_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DisfluencyReason {
+    /// Hesitation sound or filler word
+    Filler,
+    /// Part of a multi-word filler
+    FillerPhrase,
+    /// Same word said twice in a row ("I I think")
+    Repetition,
+    /// Cut-off word restarted in full ("th- the")
+    FalseStart,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DisfluencyCandidate {
+    #[serde(rename = "wordId")]
+    pub word_id: String,
+    pub word: String,
+    pub reason: DisfluencyReason,
+    /// How sure we are this word can go, 0-1
+    pub score: f64,
+}
+
+/// Removal candidates for one timeline entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntryDisfluencies {
+    #[serde(rename = "sentenceId")]
+    pub sentence_id: String,
+    pub candidates: Vec<DisfluencyCandidate>,
+}
+
+/// Find filler words, filler phrases and stutters in the kept words of each
+/// timeline entry, so the UI can offer removing them in one click.
+/// Entries without candidates are left out.
+#[tauri::command]
+pub fn detect_disfluencies(
+    entries: Vec<TimelineEntry>,
+    sentences: Vec<Sentence>,
+    words: Vec<Word>,
+    options: Option<DisfluencyOptions>,
+) -> Result<Vec<EntryDisfluencies>, LexiError> {
+    let options = options.unwrap_or_default();
+    let lexicon = FillerLexicon::new(&options);
+    let min_score = options.min_score.unwrap_or(DEFAULT_MIN_SCORE);
+
+    let sentence_map: HashMap<&str, &Sentence> =
+        sentences.iter().map(|s| (s.sentence_id.as_str(), s)).collect();
+    let word_map: HashMap<&str, &Word> = words.iter().map(|w| (w.id.as_str(), w)).collect();
+
+    let mut results = Vec::new();
+    for entry in entries.iter().filter(|e| !e.excluded) {
+        let Some(sentence) = sentence_map.get(entry.sentence_id.as_str()) else {
+            continue;
+        };
+        let kept: Vec<&Word> = sentence
+            .word_ids
+            .iter()
+            .filter(|id| !entry.excluded_word_ids.contains(id))
+            .filter_map(|id| word_map.get(id.as_str()).copied())
+            .collect();
+
+        let candidates: Vec<DisfluencyCandidate> = find_disfluencies(&kept, &lexicon)
+            .into_iter()
+            .filter(|c| c.score >= min_score)
+            .collect();
+        if !candidates.is_empty() {
+            results.push(EntryDisfluencies {
+                sentence_id: entry.sentence_id.clone(),
+                candidates,
+            });
+        }
+    }
+
+    println!(
+        "[disfluency] {} of {} entries have removal candidates",
+        results.len(),
+        entries.len()
+    );
+    Ok(results)
+}
+
+/// Built-in lexicon for the requested language merged with the caller's words
+struct FillerLexicon {
+    hesitations: HashSet<String>,
+    ambiguous: HashSet<String>,
+    phrases: Vec<Vec<String>>,
+}
+
+impl FillerLexicon {
+    fn new(options: &DisfluencyOptions) -> Self {
+        let language = options.language.as_deref().unwrap_or("en");
+        let builtin = builtin_lexicon(&language.to_lowercase());
+        if builtin.is_none() {
+            println!(
+                "[disfluency] No built-in lexicon for '{}', using custom words only",
+                language
+            );
+        }
+
+        let ignore: HashSet<String> = options
+            .ignore
+            .iter()
+            .flatten()
+            .map(|w| normalize_phrase(w))
+            .collect();
+        let keep = |w: &String| !ignore.contains(w);
+
+        let hesitations = builtin
+            .iter()
+            .flat_map(|l| l.hesitations.iter().map(|w| w.to_string()))
+            .chain(options.extra_fillers.iter().flatten().map(|w| normalize_phrase(w)))
+            .filter(keep)
+            .collect();
+        let ambiguous = builtin
+            .iter()
+            .flat_map(|l| l.ambiguous.iter().map(|w| w.to_string()))
+            .filter(keep)
+            .collect();
+        let phrases = builtin
+            .iter()
+            .flat_map(|l| l.phrases.iter().map(|p| p.to_string()))
+            .chain(options.extra_phrases.iter().flatten().map(|p| normalize_phrase(p)))
+            .filter(keep)
+            .map(|p| p.split_whitespace().map(|t| t.to_string()).collect::<Vec<_>>())
+            .filter(|tokens: &Vec<String>| !tokens.is_empty())
+            .collect();
+
+        Self {
+            hesitations,
+            ambiguous,
+            phrases,
+        }
+    }
+}
+
+/// Lowercase and strip surrounding punctuation, keeping inner apostrophes/hyphens
+fn normalize_token(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+fn normalize_phrase(phrase: &str) -> String {
+    phrase
+        .split_whitespace()
+        .map(normalize_token)
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Candidates in one run of consecutive kept words, at most one per word
+fn find_disfluencies(words: &[&Word], lexicon: &FillerLexicon) -> Vec<DisfluencyCandidate> {
+    let tokens: Vec<String> = words.iter().map(|w| normalize_token(&w.word)).collect();
+    let mut found: Vec<Option<(DisfluencyReason, f64)>> = vec![None; words.len()];
+
+    // A pause or comma next to a word suggests it stands apart from the sentence
+    let set_apart = |i: usize| {
+        let gap_before = i
+            .checked_sub(1)
+            .map(|j| words[i].start - words[j].end)
+            .unwrap_or(0.0);
+        let gap_after = words
+            .get(i + 1)
+            .map(|next| next.start - words[i].end)
+            .unwrap_or(0.0);
+        let comma_before = i
+            .checked_sub(1)
+            .is_some_and(|j| words[j].word.trim_end().ends_with(','));
+        gap_before >= PAUSE_CUE_SECONDS
+            || gap_after >= PAUSE_CUE_SECONDS
+            || words[i].word.trim_end().ends_with(',')
+            || comma_before
+    };
+
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_empty() {
+            continue;
+        }
+
+        if lexicon.hesitations.contains(token) {
+            found[i] = Some((DisfluencyReason::Filler, 0.95));
+            continue;
+        }
+
+        // "I I think": flag the first of two identical words, keeping the last
+        if let Some(next) = tokens.get(i + 1) {
+            if next == token {
+                let mut score: f64 = 0.8;
+                if words[i + 1].start - words[i].end < QUICK_REPEAT_SECONDS {
+                    score += 0.1;
+                }
+                if words[i].confidence < LOW_WORD_CONFIDENCE {
+                    score += 0.1;
+                }
+                found[i] = Some((DisfluencyReason::Repetition, score.min(1.0)));
+                continue;
+            }
+
+            // "th- the": a cut-off fragment of the next word
+            let raw = words[i].word.trim_end();
+            if raw.ends_with('-') && next.starts_with(token.as_str()) && next != token {
+                found[i] = Some((DisfluencyReason::FalseStart, 0.85));
+                continue;
+            }
+        }
+
+        if lexicon.ambiguous.contains(token) {
+            let score = if set_apart(i) { 0.7 } else { 0.35 };
+            found[i] = Some((DisfluencyReason::Filler, score));
+        }
+    }
+
+    for phrase in &lexicon.phrases {
+        if phrase.len() > tokens.len() {
+            continue;
+        }
+        for start in 0..=tokens.len() - phrase.len() {
+            let range = start..start + phrase.len();
+            if tokens[range.clone()] != phrase[..] {
+                continue;
+            }
+            // Run into the sentence they are usually real speech ("do you know him"),
+            // so they stay below the default threshold like ambiguous single words
+            let score = if set_apart(start) || set_apart(range.end - 1) {
+                0.75
+            } else {
+                0.35
+            };
+            for i in range {
+                if found[i].is_none() {
+                    found[i] = Some((DisfluencyReason::FillerPhrase, score));
+                }
+            }
+        }
+    }
+
+    found
+        .into_iter()
+        .zip(words)
+        .filter_map(|(hit, word)| {
+            hit.map(|(reason, score)| DisfluencyCandidate {
+                word_id: word.id.clone(),
+                word: word.word.clone(),
+                reason,
+                score,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Words spoken back to back from 0s, 0.2s each; `(gap)` before a word adds a pause
+    fn words(text: &str) -> Vec<Word> {
+        let mut t = 0.0;
+        text.split_whitespace()
+            .enumerate()
+            .filter_map(|(n, token)| {
+                if token == "(gap)" {
+                    t += 0.5;
+                    return None;
+                }
+                let word = Word {
+                    id: format!("w{}", n),
+                    word: token.to_string(),
+                    start: t,
+                    end: t + 0.2,
+                    confidence: 0.9,
+                    source_id: "src1".to_string(),
+                    speaker_id: None,
+                };
+                t += 0.2;
+                Some(word)
+            })
+            .collect()
+    }
+
+    fn detect(text: &str, options: DisfluencyOptions) -> Vec<(String, DisfluencyReason)> {
+        let words = words(text);
+        let sentence = Sentence {
+            sentence_id: "s1".to_string(),
+            source_id: "src1".to_string(),
+            word_ids: words.iter().map(|w| w.id.clone()).collect(),
+            text: text.to_string(),
+            start_time: 0.0,
+            end_time: 10.0,
+            original_group_id: None,
+            speaker_id: None,
+        };
+        let entry = TimelineEntry {
+            sentence_id: "s1".to_string(),
+            text: text.to_string(),
+            source_id: "src1".to_string(),
+            excluded: false,
+            excluded_word_ids: Vec::new(),
+            video_override: None,
+            angle_source_id: None,
+        };
+
+        detect_disfluencies(vec![entry], vec![sentence], words, Some(options))
+            .unwrap()
+            .into_iter()
+            .flat_map(|e| e.candidates)
+            .map(|c| (c.word, c.reason))
+            .collect()
+    }
+
+    #[test]
+    fn finds_fillers_phrases_and_stutters() {
+        let found = detect(
+            "Um, I I think th- the plan is, you know, like (gap) fine and I like it",
+            DisfluencyOptions::default(),
+        );
+        assert_eq!(
+            found,
+            vec![
+                ("Um,".to_string(), DisfluencyReason::Filler),
+                ("I".to_string(), DisfluencyReason::Repetition),
+                ("th-".to_string(), DisfluencyReason::FalseStart),
+                ("you".to_string(), DisfluencyReason::FillerPhrase),
+                ("know,".to_string(), DisfluencyReason::FillerPhrase),
+                ("like".to_string(), DisfluencyReason::Filler),
+            ]
+        );
+    }
+
+    #[test]
+    fn lexicon_follows_language_and_overrides() {
+        let options = DisfluencyOptions {
+            language: Some("fr".to_string()),
+            extra_fillers: Some(vec!["Bref".to_string()]),
+            ignore: Some(vec!["ben".to_string()]),
+            ..DisfluencyOptions::default()
+        };
+        let found = detect("euh ben bref um c'est ça", options);
+        let words: Vec<&str> = found.iter().map(|(w, _)| w.as_str()).collect();
+        assert_eq!(words, vec!["euh", "bref"]);
+
+        // Unknown languages still catch repeats
+        let options = DisfluencyOptions {
+            language: Some("xx".to_string()),
+            ..DisfluencyOptions::default()
+        };
+        assert_eq!(
+            detect("ja ja um", options),
+            vec![("ja".to_string(), DisfluencyReason::Repetition)]
+        );
+    }
+
+    #[test]
+    fn phrases_inside_a_sentence_are_not_flagged_by_default() {
+        assert!(detect("Do you know him", DisfluencyOptions::default()).is_empty());
+        assert!(detect("it was a kind of magic", DisfluencyOptions::default()).is_empty());
+
+        // Still offered when asked for low-confidence candidates
+        let options = DisfluencyOptions {
+            min_score: Some(0.3),
+            ..DisfluencyOptions::default()
+        };
+        let found = detect("Do you know him", options);
+        assert_eq!(
+            found,
+            vec![
+                ("you".to_string(), DisfluencyReason::FillerPhrase),
+                ("know".to_string(), DisfluencyReason::FillerPhrase),
+            ]
+        );
+    }
+}
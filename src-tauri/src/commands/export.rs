@@ -1,8 +1,14 @@
+use crate::error::LexiError;
+use crate::services::{
+    probe_media, video_dimensions, JobHandle, JobKind, JobManager, ProcessRunner,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
 use std::process::{Command, Stdio};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, State};
 use tempfile::TempDir;
 
 #[derive(Debug, Deserialize)]
@@ -13,6 +19,51 @@ pub struct ExportSegment {
     start_time: f64,
     #[serde(rename = "endTime")]
     end_time: f64,
+    /// Audio from another source (B-roll, or a multicam angle keeping the primary
+    /// audio); it then sets the segment's duration
+    #[serde(rename = "audioPath", default)]
+    audio_path: Option<String>,
+    #[serde(rename = "audioStart", default)]
+    audio_start: Option<f64>,
+    #[serde(rename = "audioEnd", default)]
+    audio_end: Option<f64>,
+}
+
+impl ExportSegment {
+    /// The separate audio range, when there is one
+    fn audio(&self) -> Option<(&str, f64, f64)> {
+        match (&self.audio_path, self.audio_start, self.audio_end) {
+            (Some(path), Some(start), Some(end)) => Some((path.as_str(), start, end)),
+            _ => None,
+        }
+    }
+
+    fn duration(&self) -> f64 {
+        match self.audio() {
+            Some((_, start, end)) => end - start,
+            None => self.end_time - self.start_time,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExportOptions {
+    /// Output canvas in pixels; every segment is scaled and padded onto it. When
+    /// unset the canvas is the upright display size of the first segment's source.
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+impl ExportOptions {
+    fn canvas(&self) -> Result<Option<(u32, u32)>, LexiError> {
+        match (self.width, self.height) {
+            (None, None) => Ok(None),
+            (Some(width), Some(height)) if width >= 2 && height >= 2 => Ok(Some((width, height))),
+            _ => Err(LexiError::invalid_input(
+                "Export width and height must be set together and be at least 2",
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -30,7 +81,7 @@ pub struct ExportProgressEvent {
     pub percent: Option<f64>,
 }
 
-fn parse_ffmpeg_progress(line: &str) -> Option<(f64, Option<f64>)> {
+pub(super) fn parse_ffmpeg_progress(line: &str) -> Option<(f64, Option<f64>)> {
     // FFmpeg progress output looks like:
     // frame=  120 fps=60 q=28.0 size=1024kB time=00:00:04.00 bitrate=2097.2kbits/s speed=2.0x
     if !line.contains("time=") {
@@ -67,6 +118,58 @@ fn parse_ffmpeg_progress(line: &str) -> Option<(f64, Option<f64>)> {
     time_secs.map(|t| (t, fps))
 }
 
+/// Whether a stderr line is progress output rather than a log message: either the
+/// periodic stats line or a bare `key=value` pair written by `-progress pipe:2`.
+fn is_progress_line(line: &str) -> bool {
+    if line.starts_with("frame=") && line.contains("time=") {
+        return true;
+    }
+    match line.split_once('=') {
+        Some((key, value)) => {
+            !key.is_empty()
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                && !value.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// Upright display size of `source_path`, the default export canvas
+fn source_canvas(runner: &dyn ProcessRunner, source_path: &str) -> Option<(u32, u32)> {
+    let info = probe_media(runner, Path::new(source_path)).ok()?;
+    video_dimensions(&info).ok()
+}
+
+/// Stands in for a missing audio track; segments are concatenated without
+/// re-encoding, so every one needs the same audio stream
+const SILENT_AUDIO: &str = "anullsrc=channel_layout=stereo:sample_rate=48000";
+
+/// Whether `path` has an audio stream, probing each file once per export
+fn has_audio(
+    runner: &dyn ProcessRunner,
+    path: &str,
+    probed: &mut HashMap<String, bool>,
+) -> Result<bool, LexiError> {
+    if let Some(&audio) = probed.get(path) {
+        return Ok(audio);
+    }
+    let audio = probe_media(runner, Path::new(path))?.audio.is_some();
+    probed.insert(path.to_string(), audio);
+    Ok(audio)
+}
+
+/// Scale-and-pad filter that fits a segment onto a `width`x`height` canvas
+fn canvas_filter((width, height): (u32, u32)) -> String {
+    // yuv420p needs even dimensions
+    let (width, height) = (width & !1, height & !1);
+
+    format!(
+        "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,setsar=1",
+        w = width,
+        h = height
+    )
+}
+
 /// Export video by concatenating segments using ffmpeg
 ///
 /// This command:
@@ -79,19 +182,57 @@ pub async fn export_video(
     app: AppHandle,
     segments: Vec<ExportSegment>,
     output_path: String,
-) -> Result<String, String> {
+    options: Option<ExportOptions>,
+    jobs: State<'_, JobManager>,
+) -> Result<String, LexiError> {
     if segments.is_empty() {
-        return Err("No segments to export".to_string());
+        return Err(LexiError::invalid_input("No segments to export"));
+    }
+    let canvas = options.unwrap_or_default().canvas()?;
+
+    let label = output_path.clone();
+    jobs.run_blocking(JobKind::Export, label, move |job| {
+        let emit = |event: &ExportProgressEvent| {
+            let _ = app.emit("export-progress", event);
+        };
+        render_export(job, &segments, canvas, output_path, &emit)
+    })
+    .await
+}
+
+/// Send an export progress event, mirroring it onto the export job
+fn emit_progress(
+    emit: &dyn Fn(&ExportProgressEvent),
+    job: &JobHandle,
+    event: ExportProgressEvent,
+) {
+    if let Some(percent) = event.percent {
+        job.progress(percent, event.phase.clone());
     }
+    emit(&event);
+}
 
+fn render_export(
+    job: &JobHandle,
+    segments: &[ExportSegment],
+    canvas: Option<(u32, u32)>,
+    output_path: String,
+    emit: &dyn Fn(&ExportProgressEvent),
+) -> Result<String, LexiError> {
     let total_segments = segments.len();
 
+    // Every segment is rendered upright onto one canvas, so portrait phone footage
+    // stays portrait and mixed orientations line up
+    let canvas = canvas.or_else(|| source_canvas(job.runner(), &segments[0].source_path));
+    let canvas_filter = canvas.map(canvas_filter);
+
     // Calculate total duration for progress
-    let total_duration: f64 = segments.iter().map(|s| s.end_time - s.start_time).sum();
+    let total_duration: f64 = segments.iter().map(ExportSegment::duration).sum();
 
     // Emit preparing phase
-    let _ = app.emit(
-        "export-progress",
+    emit_progress(
+        emit,
+        job,
         ExportProgressEvent {
             phase: "preparing".to_string(),
             current_segment: 0,
@@ -104,25 +245,28 @@ pub async fn export_video(
     );
 
     // Create temp directory for intermediate files
-    let temp_dir = TempDir::new().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let temp_dir = TempDir::new()
+        .map_err(|e| LexiError::io("Failed to create temp dir", std::env::temp_dir(), e))?;
     let temp_path = temp_dir.path();
 
     let mut segment_files: Vec<String> = Vec::new();
     let mut accumulated_duration: f64 = 0.0;
+    let mut probed_audio = HashMap::new();
 
     // Extract each segment
     for (i, segment) in segments.iter().enumerate() {
         let output_file = temp_path.join(format!("segment_{:04}.mp4", i));
         let output_file_str = output_file
             .to_str()
-            .ok_or("Invalid temp path")?
+            .ok_or_else(|| LexiError::internal("Invalid temp path"))?
             .to_string();
 
-        let duration = segment.end_time - segment.start_time;
+        let duration = segment.duration();
 
         // Emit segment start
-        let _ = app.emit(
-            "export-progress",
+        emit_progress(
+            emit,
+            job,
             ExportProgressEvent {
                 phase: "rendering".to_string(),
                 current_segment: i + 1,
@@ -137,62 +281,97 @@ pub async fn export_video(
         // Use ffmpeg to extract and re-encode segment with progress output
         // Re-encoding ensures each segment starts with a keyframe, eliminating stuttering at cuts
         // Timestamp flags ensure clean concatenation without freezes
-        let mut child = Command::new("ffmpeg")
-            .args([
-                "-y",
-                "-progress", "pipe:2",
-                "-ss",
-                &format!("{:.3}", segment.start_time),
-                "-i",
-                &segment.source_path,
-                "-t",
-                &format!("{:.3}", duration),
-                "-c:v", "libx264",
-                "-preset", "fast",
-                "-crf", "18",
-                "-c:a", "aac",
-                "-b:a", "192k",
-                "-force_key_frames", "expr:eq(n,0)",
-                "-pix_fmt", "yuv420p",
-                "-video_track_timescale", "90000",
-                "-avoid_negative_ts", "make_zero",
-                "-fflags", "+genpts",
-                "-movflags", "+faststart",
-                &output_file_str,
-            ])
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
-
-        // Read stderr for progress
-        if let Some(stderr) = child.stderr.take() {
+        // ffmpeg applies the source rotation while decoding; the rotate tag is
+        // cleared on output so players don't rotate a second time
+        let start_str = format!("{:.3}", segment.start_time);
+        let duration_str = format!("{:.3}", duration);
+        let mut args: Vec<&str> = vec![
+            "-y",
+            "-progress", "pipe:2",
+            "-ss", &start_str,
+            "-i", &segment.source_path,
+        ];
+
+        // With separate audio, the picture holds its last frame if it runs short
+        let audio_start_str;
+        let mut filters: Vec<&str> = canvas_filter.iter().map(String::as_str).collect();
+        let (audio_input, audio_path) = match segment.audio() {
+            Some((audio_path, audio_start, _)) => {
+                audio_start_str = format!("{:.3}", audio_start);
+                args.extend(["-ss", &audio_start_str, "-i", audio_path]);
+                filters.push("tpad=stop_mode=clone:stop=-1");
+                (1, audio_path)
+            }
+            None => (0, segment.source_path.as_str()),
+        };
+        let audio_map = if has_audio(job.runner(), audio_path, &mut probed_audio)? {
+            format!("{}:a:0", audio_input)
+        } else {
+            args.extend(["-f", "lavfi", "-i", SILENT_AUDIO]);
+            format!("{}:a:0", audio_input + 1)
+        };
+        args.extend(["-map", "0:v:0", "-map", &audio_map]);
+        args.extend(["-t", &duration_str]);
+
+        let filter = filters.join(",");
+        if !filter.is_empty() {
+            args.extend(["-vf", &filter]);
+        }
+
+        args.extend([
+            "-c:v", "libx264",
+            "-preset", "fast",
+            "-crf", "18",
+            "-c:a", "aac",
+            "-b:a", "192k",
+            "-ar", "48000",
+            "-ac", "2",
+            "-force_key_frames", "expr:eq(n,0)",
+            "-pix_fmt", "yuv420p",
+            "-video_track_timescale", "90000",
+            "-avoid_negative_ts", "make_zero",
+            "-fflags", "+genpts",
+            "-metadata:s:v:0", "rotate=0",
+            "-movflags", "+faststart",
+            &output_file_str,
+        ]);
+
+        let child = job.spawn(Command::new("ffmpeg").args(&args).stderr(Stdio::piped()))?;
+
+        // Read stderr for progress, keeping the non-progress lines for error reporting
+        let mut stderr_log = String::new();
+        if let Some(stderr) = child.take_stderr() {
             let reader = BufReader::new(stderr);
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    if let Some((time, fps)) = parse_ffmpeg_progress(&line) {
-                        let current_total = accumulated_duration + time;
-                        let _ = app.emit(
-                            "export-progress",
-                            ExportProgressEvent {
-                                phase: "rendering".to_string(),
-                                current_segment: i + 1,
-                                total_segments,
-                                current_time: Some(current_total),
-                                total_time: Some(total_duration),
-                                fps,
-                                percent: Some((current_total / total_duration) * 100.0),
-                            },
-                        );
-                    }
+            for line in reader.lines().map_while(Result::ok) {
+                if !is_progress_line(&line) {
+                    stderr_log.push_str(&line);
+                    stderr_log.push('\n');
+                }
+                if let Some((time, fps)) = parse_ffmpeg_progress(&line) {
+                    let current_total = accumulated_duration + time;
+                    emit_progress(
+                        emit,
+                        job,
+                        ExportProgressEvent {
+                            phase: "rendering".to_string(),
+                            current_segment: i + 1,
+                            total_segments,
+                            current_time: Some(current_total),
+                            total_time: Some(total_duration),
+                            fps,
+                            percent: Some((current_total / total_duration) * 100.0),
+                        },
+                    );
                 }
             }
         }
 
-        let status = child.wait().map_err(|e| format!("FFmpeg wait failed: {}", e))?;
+        let status = child.wait()?;
 
         if !status.success() {
-            let _ = app.emit(
-                "export-progress",
+            emit_progress(
+                emit,
+                job,
                 ExportProgressEvent {
                     phase: "error".to_string(),
                     current_segment: i + 1,
@@ -203,7 +382,10 @@ pub async fn export_video(
                     percent: None,
                 },
             );
-            return Err(format!("FFmpeg segment extraction failed for segment {}", i));
+            return Err(LexiError::ffmpeg_failed(
+                &format!("FFmpeg segment extraction failed for segment {}", i),
+                stderr_log.as_bytes(),
+            ));
         }
 
         accumulated_duration += duration;
@@ -211,8 +393,9 @@ pub async fn export_video(
     }
 
     // Emit finalizing phase
-    let _ = app.emit(
-        "export-progress",
+    emit_progress(
+        emit,
+        job,
         ExportProgressEvent {
             phase: "finalizing".to_string(),
             current_segment: total_segments,
@@ -227,11 +410,11 @@ pub async fn export_video(
     // Create concat list file
     let concat_list_path = temp_path.join("concat_list.txt");
     let mut concat_file = File::create(&concat_list_path)
-        .map_err(|e| format!("Failed to create concat list: {}", e))?;
+        .map_err(|e| LexiError::io("Failed to create concat list", &concat_list_path, e))?;
 
     for file in &segment_files {
         writeln!(concat_file, "file '{}'", file)
-            .map_err(|e| format!("Failed to write concat list: {}", e))?;
+            .map_err(|e| LexiError::io("Failed to write concat list", &concat_list_path, e))?;
     }
 
     drop(concat_file);
@@ -239,30 +422,27 @@ pub async fn export_video(
     // Concatenate all segments
     let concat_list_str = concat_list_path
         .to_str()
-        .ok_or("Invalid concat list path")?;
+        .ok_or_else(|| LexiError::internal("Invalid concat list path"))?;
 
-    let status = Command::new("ffmpeg")
-        .args([
-            "-y",
-            "-f",
-            "concat",
-            "-safe",
-            "0",
-            "-i",
-            concat_list_str,
-            "-fflags", "+genpts+igndts",
-            "-c",
-            "copy",
-            "-movflags", "+faststart",
-            &output_path,
-        ])
-        .output()
-        .map_err(|e| format!("Failed to run ffmpeg concat: {}", e))?;
+    let status = job.output(Command::new("ffmpeg").args([
+        "-y",
+        "-f",
+        "concat",
+        "-safe",
+        "0",
+        "-i",
+        concat_list_str,
+        "-fflags", "+genpts+igndts",
+        "-c",
+        "copy",
+        "-movflags", "+faststart",
+        &output_path,
+    ]))?;
 
     if !status.status.success() {
-        let stderr = String::from_utf8_lossy(&status.stderr);
-        let _ = app.emit(
-            "export-progress",
+        emit_progress(
+            emit,
+            job,
             ExportProgressEvent {
                 phase: "error".to_string(),
                 current_segment: total_segments,
@@ -273,12 +453,13 @@ pub async fn export_video(
                 percent: None,
             },
         );
-        return Err(format!("FFmpeg concat failed: {}", stderr));
+        return Err(LexiError::ffmpeg_failed("FFmpeg concat failed", &status.stderr));
     }
 
     // Emit complete
-    let _ = app.emit(
-        "export-progress",
+    emit_progress(
+        emit,
+        job,
         ExportProgressEvent {
             phase: "complete".to_string(),
             current_segment: total_segments,
@@ -292,3 +473,256 @@ pub async fn export_video(
 
     Ok(output_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{make_test_clip, probe_json, test_jobs, Canned, FakeRunner};
+    use crate::services::{probe_media, SystemRunner};
+    use std::sync::{Arc, Mutex};
+
+    fn segment(source_path: &str, start_time: f64, end_time: f64) -> ExportSegment {
+        ExportSegment {
+            source_path: source_path.to_string(),
+            start_time,
+            end_time,
+            audio_path: None,
+            audio_start: None,
+            audio_end: None,
+        }
+    }
+
+    async fn run_export(
+        runner: Arc<dyn ProcessRunner>,
+        segments: Vec<ExportSegment>,
+        canvas: Option<(u32, u32)>,
+        output_path: String,
+    ) -> (Result<String, LexiError>, Vec<ExportProgressEvent>) {
+        let jobs = test_jobs(runner);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let result = jobs
+            .run_blocking(JobKind::Export, "test", move |job| {
+                let emit = |event: &ExportProgressEvent| sink.lock().unwrap().push(event.clone());
+                render_export(job, &segments, canvas, output_path, &emit)
+            })
+            .await;
+        let events = events.lock().unwrap().clone();
+        (result, events)
+    }
+
+    #[tokio::test]
+    async fn renders_each_segment_upright_then_concats() {
+        let runner = FakeRunner::new();
+        runner
+            .on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(20.0, 1920, 1080, -90)))
+            .on("ffmpeg", &["concat"], Canned::ok().writing_output(b"mp4"))
+            .on(
+                "ffmpeg",
+                &["-progress"],
+                Canned::ok().with_stderr(
+                    "frame=30 fps=30.0 q=28.0 size=1kB time=00:00:01.00 bitrate=1.0kbits/s speed=1x\n",
+                ),
+            );
+
+        let segments = vec![
+            segment("/media/a.mp4", 1.0, 3.0),
+            segment("/media/b.mp4", 10.0, 12.5),
+        ];
+        let dir = TempDir::new().unwrap();
+        let output = dir.path().join("final.mp4").to_str().unwrap().to_string();
+        let (result, events) = run_export(runner.clone(), segments, None, output.clone()).await;
+        assert_eq!(result.unwrap(), output);
+        assert!(Path::new(&output).exists());
+
+        let calls = runner.calls_to("ffmpeg");
+        assert_eq!(calls.len(), 3);
+
+        let first = &calls[0];
+        assert_eq!(first.arg_after("-ss"), Some("1.000"));
+        assert_eq!(first.arg_after("-i"), Some("/media/a.mp4"));
+        assert_eq!(first.arg_after("-t"), Some("2.000"));
+        assert_eq!(first.arg_after("-metadata:s:v:0"), Some("rotate=0"));
+        assert!(first.arg_after("-vf").unwrap().starts_with("scale=1080:1920:"));
+
+        let second = &calls[1];
+        assert_eq!(second.arg_after("-ss"), Some("10.000"));
+        assert_eq!(second.arg_after("-t"), Some("2.500"));
+
+        let concat = &calls[2];
+        assert_eq!(concat.arg_after("-f"), Some("concat"));
+        assert_eq!(concat.last_arg(), Some(output.as_str()));
+
+        // Progress from the first segment's stderr: 1s into 4.5s total
+        assert!(events
+            .iter()
+            .any(|e| e.phase == "rendering" && e.current_time == Some(1.0)));
+        let last = events.last().unwrap();
+        assert_eq!(last.phase, "complete");
+        assert_eq!(last.percent, Some(100.0));
+    }
+
+    #[tokio::test]
+    async fn keeps_separate_audio_source_for_angle_segments() {
+        let runner = FakeRunner::new();
+        runner
+            .on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(20.0, 1920, 1080, 0)))
+            .on("ffmpeg", &["concat"], Canned::ok().writing_output(b"mp4"));
+
+        let segments = vec![ExportSegment {
+            audio_path: Some("/media/recorder.wav".to_string()),
+            audio_start: Some(15.0),
+            audio_end: Some(18.0),
+            ..segment("/media/cam-b.mp4", 2.5, 5.5)
+        }];
+        let dir = TempDir::new().unwrap();
+        let output = dir.path().join("final.mp4").to_str().unwrap().to_string();
+        let (result, _) = run_export(runner.clone(), segments, None, output).await;
+        result.unwrap();
+
+        let render = &runner.calls_to("ffmpeg")[0];
+        let inputs: Vec<(&str, &str)> = render
+            .args
+            .windows(4)
+            .filter(|w| w[0] == "-ss" && w[2] == "-i")
+            .map(|w| (w[1].as_str(), w[3].as_str()))
+            .collect();
+        assert_eq!(inputs, [("2.500", "/media/cam-b.mp4"), ("15.000", "/media/recorder.wav")]);
+        assert!(render.has_arg("1:a:0"));
+        assert!(!render.has_arg("lavfi"));
+        assert_eq!(render.arg_after("-t"), Some("3.000"));
+        assert!(render.arg_after("-vf").unwrap().ends_with(",tpad=stop_mode=clone:stop=-1"));
+    }
+
+    #[tokio::test]
+    async fn renders_onto_the_requested_canvas() {
+        let runner = FakeRunner::new();
+        runner
+            .on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(20.0, 1920, 1080, 0)))
+            .on("ffmpeg", &["concat"], Canned::ok().writing_output(b"mp4"));
+
+        let segments = vec![segment("/media/a.mp4", 0.0, 1.0), segment("/media/b.mp4", 0.0, 1.0)];
+        let dir = TempDir::new().unwrap();
+        let output = dir.path().join("final.mp4").to_str().unwrap().to_string();
+        let (result, _) = run_export(runner.clone(), segments, Some((1280, 720)), output).await;
+        result.unwrap();
+
+        // Only probed for audio, once per source
+        assert_eq!(runner.calls_to("ffprobe").len(), 2);
+        for render in &runner.calls_to("ffmpeg")[..2] {
+            assert!(render.arg_after("-vf").unwrap().starts_with("scale=1280:720:"));
+        }
+
+        let options = ExportOptions { width: Some(1280), height: None };
+        assert!(options.canvas().is_err());
+    }
+
+    #[test]
+    fn canvas_is_the_upright_display_size() {
+        let portrait = probe_json(5.0, 1920, 1080, -90);
+        let flipped = probe_json(5.0, 1920, 1080, 180);
+        let runner = FakeRunner::new();
+        runner
+            .on("ffprobe", &["/media/portrait.mov"], Canned::stdout(&portrait))
+            .on("ffprobe", &["/media/flipped.mov"], Canned::stdout(&flipped));
+
+        assert_eq!(source_canvas(runner.as_ref(), "/media/portrait.mov"), Some((1080, 1920)));
+        assert_eq!(source_canvas(runner.as_ref(), "/media/flipped.mov"), Some((1920, 1080)));
+
+        let portrait = ExportOptions { width: Some(1080), height: Some(1920) };
+        let canvas = portrait.canvas().unwrap().unwrap();
+        assert_eq!(canvas, (1080, 1920));
+        assert!(canvas_filter(canvas).starts_with("scale=1080:1920:"));
+        assert!(canvas_filter((1081, 1921)).contains("pad=1080:1920:"));
+    }
+
+    #[tokio::test]
+    async fn fills_missing_audio_with_silence() {
+        let mut silent: serde_json::Value =
+            serde_json::from_str(&probe_json(20.0, 1920, 1080, 0)).unwrap();
+        silent["streams"].as_array_mut().unwrap().truncate(1);
+        let runner = FakeRunner::new();
+        runner
+            .on("ffprobe", &["/media/broll.mp4"], Canned::stdout(&silent.to_string()))
+            .on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(20.0, 1920, 1080, 0)))
+            .on("ffmpeg", &["concat"], Canned::ok().writing_output(b"mp4"));
+
+        let segments = vec![
+            segment("/media/a.mp4", 0.0, 1.0),
+            segment("/media/broll.mp4", 0.0, 1.0),
+        ];
+        let dir = TempDir::new().unwrap();
+        let output = dir.path().join("final.mp4").to_str().unwrap().to_string();
+        let (result, _) = run_export(runner.clone(), segments, None, output).await;
+        result.unwrap();
+
+        let calls = runner.calls_to("ffmpeg");
+        assert_eq!(calls[0].args.iter().filter(|a| *a == "-map").count(), 2);
+        assert!(calls[0].has_arg("0:a:0") && !calls[0].has_arg("lavfi"));
+        assert_eq!(calls[1].arg_after("-f"), Some("lavfi"));
+        assert!(calls[1].has_arg("1:a:0"));
+        for render in &calls[..2] {
+            assert_eq!(render.arg_after("-ar"), Some("48000"));
+            assert_eq!(render.arg_after("-ac"), Some("2"));
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_segment_reports_stderr_tail() {
+        let runner = FakeRunner::new();
+        runner
+            .on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(20.0, 1280, 720, 0)))
+            .on(
+                "ffmpeg",
+                &["-progress"],
+                Canned::failure(
+                    1,
+                    "progress=continue\n/media/a.mp4: Invalid data found when processing input\n",
+                ),
+            );
+
+        let segments = vec![segment("/media/a.mp4", 0.0, 2.0)];
+        let output = "/out/final.mp4".to_string();
+        let (result, events) = run_export(runner.clone(), segments, None, output).await;
+
+        match result {
+            Err(LexiError::FfmpegFailed { stderr, .. }) => {
+                assert_eq!(stderr, "/media/a.mp4: Invalid data found when processing input");
+            }
+            other => panic!("expected FfmpegFailed, got {:?}", other),
+        }
+        assert_eq!(events.last().unwrap().phase, "error");
+        assert_eq!(runner.calls_to("ffmpeg").len(), 1);
+    }
+
+    #[test]
+    fn keeps_error_lines_that_contain_equals_signs() {
+        assert!(is_progress_line("progress=continue"));
+        assert!(is_progress_line("out_time=00:00:04.000000"));
+        assert!(is_progress_line(
+            "frame=  120 fps=60 q=28.0 size=1024kB time=00:00:04.00 bitrate=2097.2kbits/s"
+        ));
+        assert!(!is_progress_line("Invalid value 'w=abc' for option 'scale'"));
+        assert!(!is_progress_line("[Parsed_pad_1 @ 0x1] Padded dimensions cannot be smaller"));
+        assert!(!is_progress_line("Error parsing option crf=x: Invalid argument"));
+    }
+
+    #[tokio::test]
+    async fn exports_real_clip() {
+        let dir = TempDir::new().unwrap();
+        let Some(clip) = make_test_clip(dir.path(), "clip.mp4", 3.0) else {
+            return;
+        };
+        let clip = clip.to_str().unwrap().to_string();
+        let output = dir.path().join("out.mp4").to_str().unwrap().to_string();
+
+        let segments = vec![segment(&clip, 0.0, 1.0), segment(&clip, 2.0, 3.0)];
+        let (result, _) = run_export(Arc::new(SystemRunner), segments, None, output.clone()).await;
+        result.unwrap();
+
+        let info = probe_media(&SystemRunner, Path::new(&output)).unwrap();
+        assert!((info.duration - 2.0).abs() < 0.3);
+        let video = info.video.unwrap();
+        assert_eq!((video.width, video.height), (320, 240));
+    }
+}
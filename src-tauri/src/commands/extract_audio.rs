@@ -0,0 +1,315 @@
+use crate::error::LexiError;
+use crate::services::{probe_media_cached, ArtifactStore, CacheDb, JobHandle, JobKind, JobManager};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tauri::{AppHandle, Manager, State};
+
+/// Speech models resample to 16 kHz mono anyway, so nothing useful is lost
+const AUDIO_SAMPLE_RATE: &str = "16000";
+
+const AUDIO_KIND: &str = "audio";
+
+/// Chunks shorter than this are folded into the previous one
+const MIN_CHUNK_SECONDS: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    /// Lossless, roughly 2 MB per minute
+    #[default]
+    Flac,
+    /// Ogg/Opus at 24 kbit/s, roughly 0.2 MB per minute
+    Opus,
+    /// MP3 at 48 kbit/s, for services that accept nothing else
+    Mp3,
+}
+
+impl AudioFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Flac => "flac",
+            AudioFormat::Opus => "ogg",
+            AudioFormat::Mp3 => "mp3",
+        }
+    }
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Opus => "audio/ogg",
+            AudioFormat::Mp3 => "audio/mpeg",
+        }
+    }
+
+    /// Muxer and encoder arguments; the muxer is explicit because chunks are
+    /// written to a `.part` file first
+    fn codec_args(self) -> &'static [&'static str] {
+        match self {
+            AudioFormat::Flac => &["-c:a", "flac", "-f", "flac"],
+            AudioFormat::Opus => &["-c:a", "libopus", "-b:a", "24k", "-f", "ogg"],
+            AudioFormat::Mp3 => &["-c:a", "libmp3lame", "-b:a", "48k", "-f", "mp3"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AudioOptions {
+    #[serde(default)]
+    pub format: AudioFormat,
+    /// Split sources longer than this into chunks of at most this many seconds,
+    /// for services with an upload size limit. Unset means one file.
+    #[serde(rename = "chunkSeconds")]
+    pub chunk_seconds: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AudioChunk {
+    /// File under the app data dir
+    pub path: String,
+    /// Position of the chunk in the source, in seconds
+    pub start: f64,
+    pub end: f64,
+    #[serde(rename = "byteSize")]
+    pub byte_size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExtractedAudio {
+    pub format: AudioFormat,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    pub duration: f64,
+    pub chunks: Vec<AudioChunk>,
+}
+
+/// Write the source's audio as compact mono 16 kHz files for transcription,
+/// optionally split into chunks. Files are keyed by CID, format and chunk length
+/// and reused on later calls.
+#[tauri::command]
+pub async fn extract_audio(
+    app: AppHandle,
+    path: String,
+    cid: String,
+    options: Option<AudioOptions>,
+    jobs: State<'_, JobManager>,
+) -> Result<ExtractedAudio, LexiError> {
+    let options = options.unwrap_or_default();
+    let label = path.clone();
+    jobs.run_blocking(JobKind::ExtractAudio, label, move |job| {
+        let db = app.state::<CacheDb>();
+        let artifacts = app.state::<ArtifactStore>();
+        extract_audio_chunks(job, &path, &cid, &options, &db, &artifacts)
+    })
+    .await
+}
+
+pub fn extract_audio_chunks(
+    job: &JobHandle,
+    path: &str,
+    cid: &str,
+    options: &AudioOptions,
+    db: &CacheDb,
+    artifacts: &ArtifactStore,
+) -> Result<ExtractedAudio, LexiError> {
+    if options.chunk_seconds.is_some_and(|s| s.is_nan() || s < MIN_CHUNK_SECONDS) {
+        return Err(LexiError::invalid_input(format!(
+            "Audio chunks must be at least {}s long",
+            MIN_CHUNK_SECONDS
+        )));
+    }
+
+    let duration = probe_media_cached(job.runner(), Path::new(path), Some(cid), db)?.duration;
+    let format = options.format;
+    let ranges = chunk_ranges(duration, options.chunk_seconds);
+    let key = match options.chunk_seconds {
+        Some(seconds) => format!("c{}", (seconds * 1000.0).round() as u64),
+        None => "full".to_string(),
+    };
+
+    let mut chunks = Vec::with_capacity(ranges.len());
+    for (i, &(start, end)) in ranges.iter().enumerate() {
+        job.check_cancelled()?;
+        let name = format!("{}_{:03}.{}", key, i, format.extension());
+        let file = artifacts.path(AUDIO_KIND, cid, &name)?;
+
+        if !file.exists() {
+            job.progress(
+                i as f64 / ranges.len() as f64 * 100.0,
+                format!("Extracting audio chunk {}/{}", i + 1, ranges.len()),
+            );
+            encode_chunk(job, path, &file, format, start, end - start)?;
+        }
+
+        let byte_size = fs::metadata(&file)
+            .map_err(|e| LexiError::io("Failed to stat audio chunk", &file, e))?
+            .len();
+        chunks.push(AudioChunk {
+            path: file.to_string_lossy().to_string(),
+            start,
+            end,
+            byte_size,
+        });
+    }
+
+    let total: u64 = chunks.iter().map(|c| c.byte_size).sum();
+    println!(
+        "[extract_audio] {} chunk(s), {} bytes of {} for {}",
+        chunks.len(),
+        total,
+        format.extension(),
+        path
+    );
+
+    Ok(ExtractedAudio {
+        format,
+        mime_type: format.mime_type().to_string(),
+        duration,
+        chunks,
+    })
+}
+
+/// Encode one stretch of audio, renaming into place only once ffmpeg succeeded
+/// so an interrupted run never leaves a truncated chunk behind
+fn encode_chunk(
+    job: &JobHandle,
+    path: &str,
+    file: &Path,
+    format: AudioFormat,
+    start: f64,
+    length: f64,
+) -> Result<(), LexiError> {
+    let partial = file.with_extension("part");
+    let partial_str = partial.to_string_lossy().to_string();
+
+    let mut cmd = Command::new("ffmpeg");
+    cmd.args([
+        "-hide_banner",
+        "-y",
+        "-ss", &format!("{:.3}", start),
+        "-t", &format!("{:.3}", length),
+        "-i", path,
+        "-vn",
+        "-ac", "1",
+        "-ar", AUDIO_SAMPLE_RATE,
+    ]);
+    cmd.args(format.codec_args()).arg(&partial_str);
+    let output = job.output(&mut cmd)?;
+
+    if !output.status.success() || !partial.exists() {
+        let _ = fs::remove_file(&partial);
+        return Err(LexiError::ffmpeg_failed("FFmpeg audio extraction failed", &output.stderr));
+    }
+
+    fs::rename(&partial, file).map_err(|e| LexiError::io("Failed to store audio chunk", file, e))
+}
+
+/// Split `0..duration` into chunks of at most `chunk_seconds`, folding a short
+/// tail into the previous chunk
+fn chunk_ranges(duration: f64, chunk_seconds: Option<f64>) -> Vec<(f64, f64)> {
+    let Some(chunk) = chunk_seconds.filter(|&c| c < duration) else {
+        return vec![(0.0, duration)];
+    };
+
+    let mut ranges = Vec::new();
+    let mut start = 0.0;
+    while start < duration {
+        let end = (start + chunk).min(duration);
+        if duration - end < MIN_CHUNK_SECONDS {
+            ranges.push((start, duration));
+            break;
+        }
+        ranges.push((start, end));
+        start = end;
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{probe_json, test_jobs, Canned, FakeRunner};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn splits_long_sources_and_folds_short_tails() {
+        assert_eq!(chunk_ranges(90.0, None), vec![(0.0, 90.0)]);
+        assert_eq!(chunk_ranges(90.0, Some(600.0)), vec![(0.0, 90.0)]);
+        assert_eq!(chunk_ranges(25.0, Some(10.0)), vec![(0.0, 10.0), (10.0, 20.0), (20.0, 25.0)]);
+        assert_eq!(chunk_ranges(20.5, Some(10.0)), vec![(0.0, 10.0), (10.0, 20.5)]);
+    }
+
+    #[tokio::test]
+    async fn encodes_chunks_once_and_reuses_them() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+        let options = AudioOptions {
+            format: AudioFormat::Opus,
+            chunk_seconds: Some(60.0),
+        };
+
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let runner = FakeRunner::new();
+            runner
+                .on("ffprobe", &[], Canned::stdout(&probe_json(150.0, 1920, 1080, 0)))
+                .on("ffmpeg", &["libopus"], Canned::ok().writing_output(b"opus"));
+
+            let (db, artifacts, options) = (db.clone(), artifacts.clone(), options.clone());
+            let audio = test_jobs(runner.clone())
+                .run_blocking(JobKind::ExtractAudio, "test", move |job| {
+                    extract_audio_chunks(job, "/media/clip.mp4", "cid1", &options, &db, &artifacts)
+                })
+                .await
+                .unwrap();
+            runs.push((audio, runner.calls()));
+        }
+
+        let (audio, calls) = &runs[0];
+        let ffmpeg: Vec<_> = calls.iter().filter(|c| c.program == "ffmpeg").collect();
+        assert_eq!(ffmpeg.len(), 3);
+        assert_eq!(ffmpeg[2].arg_after("-ss"), Some("120.000"));
+        assert_eq!(ffmpeg[2].arg_after("-t"), Some("30.000"));
+        assert_eq!(ffmpeg[0].arg_after("-ar"), Some("16000"));
+        assert_eq!(audio.mime_type, "audio/ogg");
+        assert!(audio.chunks[1].path.ends_with("cid1_c60000_001.ogg"));
+        assert_eq!(audio.chunks[1].byte_size, 4);
+        assert!(!Path::new(&audio.chunks[0].path).with_extension("part").exists());
+
+        let (cached, calls) = &runs[1];
+        assert!(calls.is_empty());
+        assert_eq!(cached, audio);
+    }
+
+    #[tokio::test]
+    async fn failed_encode_leaves_no_chunk_behind() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+
+        let runner = FakeRunner::new();
+        runner
+            .on("ffprobe", &[], Canned::stdout(&probe_json(30.0, 1920, 1080, 0)))
+            .on("ffmpeg", &[], Canned::failure(1, "Stream map matches no streams"));
+
+        let store = artifacts.clone();
+        let result = test_jobs(runner)
+            .run_blocking(JobKind::ExtractAudio, "test", move |job| {
+                extract_audio_chunks(
+                    job,
+                    "/media/silent.mp4",
+                    "cid2",
+                    &AudioOptions::default(),
+                    &db,
+                    &store,
+                )
+            })
+            .await;
+
+        assert!(matches!(result, Err(LexiError::FfmpegFailed { .. })));
+        assert!(!artifacts.path(AUDIO_KIND, "cid2", "full_000.flac").unwrap().exists());
+    }
+}
@@ -1,6 +1,9 @@
+use crate::error::LexiError;
+use crate::services::{JobHandle, JobKind, JobManager};
 use base64::{engine::general_purpose::STANDARD, Engine};
 use std::fs;
 use std::process::Command;
+use tauri::State;
 use tempfile::NamedTempFile;
 
 /// Extract the first N seconds of a video and return as base64-encoded MP4
@@ -11,7 +14,16 @@ use tempfile::NamedTempFile;
 pub async fn extract_clip_base64(
     path: String,
     duration_seconds: f64,
-) -> Result<String, String> {
+    jobs: State<'_, JobManager>,
+) -> Result<String, LexiError> {
+    let label = path.clone();
+    jobs.run_blocking(JobKind::ExtractClip, label, move |job| {
+        extract_clip(job, &path, duration_seconds)
+    })
+    .await
+}
+
+fn extract_clip(job: &JobHandle, path: &str, duration_seconds: f64) -> Result<String, LexiError> {
     let duration = if duration_seconds <= 0.0 {
         5.0
     } else {
@@ -20,7 +32,7 @@ pub async fn extract_clip_base64(
 
     // Create a temp file for the output MP4
     let temp_file = NamedTempFile::with_suffix(".mp4")
-        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+        .map_err(|e| LexiError::io("Failed to create temp file", std::env::temp_dir(), e))?;
     let temp_path = temp_file.path().to_string_lossy().to_string();
 
     println!(
@@ -30,31 +42,30 @@ pub async fn extract_clip_base64(
 
     // Use ffmpeg to extract the first N seconds and convert to MP4 (H.264)
     // Re-encoding ensures compatibility with Gemini
-    let output = Command::new("ffmpeg")
-        .args([
-            "-y",
-            "-ss", "0",
-            "-i", &path,
-            "-t", &format!("{:.3}", duration),
-            "-c:v", "libx264",      // H.264 video codec
-            "-preset", "ultrafast", // Fast encoding
-            "-crf", "23",           // Reasonable quality
-            "-c:a", "aac",          // AAC audio codec
-            "-b:a", "128k",
-            "-movflags", "+faststart", // Enable streaming
-            &temp_path,
-        ])
-        .output()
-        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    let output = job.output(Command::new("ffmpeg").args([
+        "-y",
+        "-ss", "0",
+        "-i", path,
+        "-t", &format!("{:.3}", duration),
+        "-c:v", "libx264",      // H.264 video codec
+        "-preset", "ultrafast", // Fast encoding
+        "-crf", "23",           // Reasonable quality
+        "-c:a", "aac",          // AAC audio codec
+        "-b:a", "128k",
+        "-movflags", "+faststart", // Enable streaming
+        &temp_path,
+    ]))?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("FFmpeg clip extraction failed: {}", stderr));
+        return Err(LexiError::ffmpeg_failed(
+            "FFmpeg clip extraction failed",
+            &output.stderr,
+        ));
     }
 
     // Read the output file and encode as base64
     let bytes = fs::read(&temp_path)
-        .map_err(|e| format!("Failed to read temp file: {}", e))?;
+        .map_err(|e| LexiError::io("Failed to read temp file", &temp_path, e))?;
 
     let size_mb = bytes.len() as f64 / 1024.0 / 1024.0;
     println!(
@@ -1,15 +1,274 @@
+use crate::error::LexiError;
+use crate::services::{
+    best_in_window, dedupe_by_hash, dhash, format_hash, probe_media, probe_media_cached,
+    CacheDb, FrameCache, FrameKey, JobHandle, JobKind, JobManager, QualityThresholds,
+    DHASH_FILTER, DHASH_FRAME_BYTES, QUALITY_FILTER, QUALITY_FRAME_BYTES,
+};
 use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::process::Command;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tauri::{AppHandle, Manager, State};
 use tempfile::TempDir;
 
+/// How frame timestamps are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum SamplingStrategy {
+    /// I-frame positions plus gap fills
+    #[default]
+    Keyframes,
+    /// Only detected scene changes (ffmpeg `select='gt(scene,x)'`)
+    SceneDetect {
+        #[serde(default = "default_scene_threshold")]
+        threshold: f64,
+    },
+    /// One frame every `interval` seconds
+    Uniform {
+        #[serde(default = "default_uniform_interval")]
+        interval: f64,
+    },
+    /// Detected scene changes plus gap fills
+    Hybrid {
+        #[serde(default = "default_scene_threshold")]
+        threshold: f64,
+    },
+}
+
+fn default_scene_threshold() -> f64 {
+    DEFAULT_SCENE_THRESHOLD
+}
+
+fn default_uniform_interval() -> f64 {
+    DEFAULT_UNIFORM_INTERVAL
+}
+
+/// A time range to sample, e.g. a segment group or sentence
+#[derive(Debug, Clone, Deserialize)]
+pub struct FrameRange {
+    /// Caller's id (group or sentence id), echoed back on each frame
+    pub id: String,
+    /// Start in seconds
+    pub start: f64,
+    /// End in seconds
+    pub end: f64,
+    /// Frame budget for this range; defaults to an even share of `max_frames`
+    #[serde(rename = "maxFrames")]
+    pub max_frames: Option<u32>,
+}
+
+/// Skip black, blurry and transitional frames, nudging each timestamp to the best
+/// usable frame within `window` seconds. Unset limits use the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct FrameQualityOptions {
+    /// Seconds to search either side of each timestamp (default 0.5)
+    pub window: Option<f64>,
+    /// Mean luma (0-255) below which a frame counts as black
+    #[serde(rename = "minBrightness")]
+    pub min_brightness: Option<f64>,
+    /// Laplacian variance below which a frame counts as blurry
+    #[serde(rename = "minSharpness")]
+    pub min_sharpness: Option<f64>,
+    /// Mean luma change from both neighbours above which a frame counts as transitional
+    #[serde(rename = "maxMotion")]
+    pub max_motion: Option<f64>,
+}
+
+impl FrameQualityOptions {
+    fn window(&self) -> f64 {
+        self.window.unwrap_or(DEFAULT_QUALITY_WINDOW).max(0.0)
+    }
+
+    fn thresholds(&self) -> QualityThresholds {
+        let defaults = QualityThresholds::default();
+        QualityThresholds {
+            min_brightness: self.min_brightness.unwrap_or(defaults.min_brightness),
+            min_sharpness: self.min_sharpness.unwrap_or(defaults.min_sharpness),
+            max_motion: self.max_motion.unwrap_or(defaults.max_motion),
+            ..defaults
+        }
+    }
+
+    /// Identifies these settings in the cache of nudged timestamps
+    fn cache_key(&self) -> String {
+        let thresholds = self.thresholds();
+        format!(
+            "w{}-b{}-s{}-m{}",
+            self.window(),
+            thresholds.min_brightness,
+            thresholds.min_sharpness,
+            thresholds.max_motion
+        )
+    }
+}
+
+/// Image format for extracted frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameFormat {
+    #[default]
+    Jpeg,
+    Webp,
+    Png,
+}
+
+impl FrameFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            FrameFormat::Jpeg => "jpg",
+            FrameFormat::Webp => "webp",
+            FrameFormat::Png => "png",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            FrameFormat::Jpeg => "image/jpeg",
+            FrameFormat::Webp => "image/webp",
+            FrameFormat::Png => "image/png",
+        }
+    }
+}
+
+/// Size and encoding of extracted frames. The default is full-resolution JPEG at
+/// ffmpeg's `-q:v 2`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct FrameEncoding {
+    /// Downscale so the longer side is at most this many pixels
+    #[serde(rename = "maxLongEdge")]
+    pub max_long_edge: Option<u32>,
+    #[serde(default)]
+    pub format: FrameFormat,
+    /// 1-100, higher is better; ignored for PNG
+    pub quality: Option<u8>,
+    /// Lower quality, then size, until all frames together fit in this many bytes
+    #[serde(rename = "maxTotalBytes")]
+    pub max_total_bytes: Option<u64>,
+}
+
+impl FrameEncoding {
+    /// Encoder arguments for the image output
+    fn codec_args(&self) -> Vec<String> {
+        match self.format {
+            FrameFormat::Jpeg => {
+                // -q:v runs from 2 (best) to 31 (worst)
+                let qscale = self
+                    .quality
+                    .map_or(2, |q| 2 + (100 - q.clamp(1, 100) as u32) * 29 / 100);
+                vec!["-q:v".to_string(), qscale.to_string()]
+            }
+            FrameFormat::Webp => vec![
+                "-c:v".to_string(),
+                "libwebp".to_string(),
+                "-quality".to_string(),
+                self.quality.unwrap_or(DEFAULT_WEBP_QUALITY).min(100).to_string(),
+            ],
+            FrameFormat::Png => Vec::new(),
+        }
+    }
+
+    /// Identifies these settings in the frame cache; the byte budget is left out since
+    /// every attempt at meeting it is cached under the settings it actually used
+    fn cache_key(&self) -> String {
+        format!(
+            "{}-q{}-e{}",
+            self.format.extension(),
+            self.quality.map_or("d".to_string(), |q| q.to_string()),
+            self.max_long_edge.map_or("full".to_string(), |e| e.to_string())
+        )
+    }
+
+    /// Filter that caps the longer side, keeping aspect ratio and even dimensions
+    fn scale_filter(&self) -> Option<String> {
+        self.max_long_edge.map(|edge| {
+            format!(
+                "scale='if(gte(iw,ih),min({e},iw),-2)':'if(gte(iw,ih),-2,min({e},ih))'",
+                e = edge
+            )
+        })
+    }
+
+    /// Settings for the next attempt at fitting `total` bytes into `budget`: lower
+    /// quality first, then shrink. `None` once neither can go any lower.
+    fn shrink_for_budget(&self, total: u64, budget: u64, long_edge: u32) -> Option<FrameEncoding> {
+        // Size scales roughly with pixel count, so shrink each side by the square root
+        let ratio = (budget as f64 / total as f64).sqrt();
+
+        if self.format != FrameFormat::Png {
+            let quality = self.quality.unwrap_or(match self.format {
+                FrameFormat::Webp => DEFAULT_WEBP_QUALITY,
+                _ => DEFAULT_JPEG_QUALITY,
+            });
+            if quality > MIN_FRAME_QUALITY {
+                let next = ((quality as f64 * ratio) as u8)
+                    .min(quality.saturating_sub(QUALITY_STEP))
+                    .max(MIN_FRAME_QUALITY);
+                return Some(FrameEncoding {
+                    quality: Some(next),
+                    ..self.clone()
+                });
+            }
+        }
+
+        if long_edge > MIN_LONG_EDGE {
+            let next = ((long_edge as f64 * ratio) as u32)
+                .min(long_edge - 2)
+                .max(MIN_LONG_EDGE);
+            return Some(FrameEncoding {
+                max_long_edge: Some(next & !1),
+                ..self.clone()
+            });
+        }
+
+        None
+    }
+}
+
 /// A single extracted frame with its timestamp
-#[derive(serde::Serialize)]
+#[derive(Serialize)]
 pub struct ExtractedFrame {
     /// Timestamp in seconds
     pub timestamp: f64,
-    /// Base64-encoded JPEG image data
+    /// Base64-encoded image data
     pub data: String,
+    /// MIME type of `data`
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    /// Size in pixels after scaling
+    pub width: u32,
+    pub height: u32,
+    /// Encoded size in bytes (before base64)
+    #[serde(rename = "byteSize")]
+    pub byte_size: u64,
+    /// Strategy used to pick the frame
+    pub strategy: SamplingStrategy,
+    /// Scene change score (0-1) when the frame sits on a detected scene change
+    #[serde(rename = "sceneScore")]
+    pub scene_score: Option<f64>,
+    /// 64-bit perceptual difference hash (16 hex chars); similar shots differ in few bits
+    pub hash: Option<String>,
+    /// Id of the requested range the frame belongs to
+    #[serde(rename = "rangeId")]
+    pub range_id: Option<String>,
+}
+
+/// A frame image before it is tagged and base64-encoded for the frontend
+struct RawFrame {
+    timestamp: f64,
+    bytes: Vec<u8>,
+    width: u32,
+    height: u32,
+    hash: Option<String>,
+}
+
+/// A scene change found by ffmpeg's scene detection
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct SceneChange {
+    timestamp: f64,
+    score: f64,
 }
 
 /// Gap fill settings
@@ -17,117 +276,636 @@ const MAX_GAP_SECONDS: f64 = 5.0; // Fill gaps larger than 5 seconds
 const MIN_FRAME_DISTANCE: f64 = 1.0; // Don't place frames closer than 1 second
 const DEFAULT_MAX_FRAMES: u32 = 60; // Reasonable limit for Gemini
 
-/// Extract frames using hybrid approach:
-/// 1. Extract all I-frames (keyframes) - natural scene boundaries
-/// 2. Fill gaps > 5 seconds with additional frames
-/// 3. Dedupe frames that are too close together
+/// Sampling defaults
+const DEFAULT_SCENE_THRESHOLD: f64 = 0.3; // ffmpeg scene score is 0-1
+const DEFAULT_UNIFORM_INTERVAL: f64 = 1.0;
+const SCENE_DETECT_WIDTH: u32 = 320; // Scene scores are computed on a downscaled copy
+
+/// Frame encoding limits
+const DEFAULT_JPEG_QUALITY: u8 = 95; // Roughly what -q:v 2 gives
+const DEFAULT_WEBP_QUALITY: u8 = 80;
+const MIN_FRAME_QUALITY: u8 = 30;
+const QUALITY_STEP: u8 = 5;
+const MIN_LONG_EDGE: u32 = 256;
+const MAX_BUDGET_ATTEMPTS: usize = 6;
+
+/// Cache data types for per-source sampling data
+/// (timestamps relative to the container start time; the unversioned keys held absolute ones)
+const DATA_TYPE_KEYFRAMES: &str = "keyframes_v2";
+const DATA_TYPE_SCENE_CHANGES: &str = "scene_changes_v2";
+const DATA_TYPE_FRAME_QUALITY: &str = "frame_quality";
+
+/// Quality scoring looks this far either side of each timestamp
+const DEFAULT_QUALITY_WINDOW: f64 = 0.5;
+
+/// Candidates scored per side of a timestamp within the window
+const QUALITY_STEPS: i32 = 2;
+
+/// Candidates hashed per output slot when deduplicating, so dropped look-alikes can
+/// be replaced by visually distinct moments
+const DEDUP_OVERSAMPLE: usize = 3;
+
+/// Share of the job's 0-100 progress each stage starts at; skipped stages are jumped over
+const PROGRESS_DEDUP: f64 = 15.0;
+const PROGRESS_QUALITY: f64 = 30.0;
+const PROGRESS_EXTRACT: f64 = 45.0;
+
+/// Everything `extract_frames_base64` accepts besides the path
+#[derive(Debug, Clone, Default)]
+struct FrameRequest {
+    max_frames: Option<u32>,
+    strategy: SamplingStrategy,
+    dedup_threshold: Option<u32>,
+    ranges: Option<Vec<FrameRange>>,
+    encoding: FrameEncoding,
+    quality: Option<FrameQualityOptions>,
+}
+
+/// The file frames are read from. Timestamps passed around are relative to `start`,
+/// the container start time, while ffmpeg's `select` and showinfo use absolute times.
+#[derive(Debug, Clone, Copy)]
+struct FrameSource<'a> {
+    path: &'a str,
+    start: f64,
+}
+
+/// A source's CID and the stores its frames and sampling data are cached in
+struct FrameStore<'a> {
+    cid: &'a str,
+    db: &'a CacheDb,
+    frames: &'a FrameCache,
+}
+
+/// Extract frames sampled by `strategy` (keyframes by default):
+/// 1. Find candidate timestamps (I-frames, scene changes or a fixed interval)
+/// 2. Fill gaps > 5 seconds with additional frames (keyframes and hybrid)
+/// 3. Dedupe frames that are too close together, and optionally frames that look
+///    alike (perceptual hash within `dedup_threshold` bits)
 ///
-/// Returns an array of base64-encoded JPEG frames with timestamps.
+/// When `ranges` are given only those parts of the file are sampled, each with its
+/// own budget, and every frame carries the id of its range.
+///
+/// With `quality`, black, blurry and transitional frames are replaced by the best
+/// frame nearby, or dropped when there is none.
+///
+/// With a `cid`, frames, keyframes, scene changes and hashes are cached on disk, so
+/// sampling the same source again does not run ffmpeg.
+///
+/// Returns an array of base64-encoded frames (JPEG unless `encoding` says otherwise)
+/// with timestamps, pixel size and byte size.
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn extract_frames_base64(
+    app: AppHandle,
     path: String,
+    cid: Option<String>,
     max_frames: Option<u32>,
-) -> Result<Vec<ExtractedFrame>, String> {
+    strategy: Option<SamplingStrategy>,
+    dedup_threshold: Option<u32>,
+    ranges: Option<Vec<FrameRange>>,
+    encoding: Option<FrameEncoding>,
+    quality: Option<FrameQualityOptions>,
+    jobs: State<'_, JobManager>,
+) -> Result<Vec<ExtractedFrame>, LexiError> {
+    let request = FrameRequest {
+        max_frames,
+        strategy: strategy.unwrap_or_default(),
+        dedup_threshold,
+        ranges,
+        encoding: encoding.unwrap_or_default(),
+        quality,
+    };
+
+    let label = path.clone();
+    jobs.run_blocking(JobKind::ExtractFrames, label, move |job| {
+        let db = app.state::<CacheDb>();
+        let frames = app.state::<FrameCache>();
+        let store = cid.as_deref().map(|cid| FrameStore {
+            cid,
+            db: &db,
+            frames: &frames,
+        });
+        extract_frames(job, &path, request, store.as_ref())
+    })
+    .await
+}
+
+fn extract_frames(
+    job: &JobHandle,
+    path: &str,
+    request: FrameRequest,
+    store: Option<&FrameStore>,
+) -> Result<Vec<ExtractedFrame>, LexiError> {
+    let FrameRequest {
+        max_frames,
+        strategy,
+        dedup_threshold,
+        ranges,
+        encoding,
+        quality,
+    } = request;
     let max = max_frames.unwrap_or(DEFAULT_MAX_FRAMES);
 
-    // First, get video duration
-    let duration = get_video_duration(&path)?;
+    // First, get video duration and the start time timestamps are relative to
+    let info = match store {
+        Some(store) => {
+            probe_media_cached(job.runner(), Path::new(path), Some(store.cid), store.db)?
+        }
+        None => probe_media(job.runner(), Path::new(path))?,
+    };
+    let (duration, start) = (info.duration, info.start_time);
     println!(
-        "[extract_frames] Video duration: {:.1}s, max frames: {}",
-        duration, max
+        "[extract_frames] Video duration: {:.1}s, max frames: {}, strategy: {:?}",
+        duration, max, strategy
     );
+    let source = FrameSource { path, start };
+
+    let spans = frame_spans(ranges, duration, max as usize)?;
 
     // Create temp directory for frames
-    let temp_dir =
-        TempDir::new().map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let temp_dir = TempDir::new()
+        .map_err(|e| LexiError::io("Failed to create temp directory", std::env::temp_dir(), e))?;
     let temp_path = temp_dir.path();
 
-    // Step 1: Extract I-frames with timestamps
-    println!("[extract_frames] Step 1: Extracting I-frames (keyframes)...");
-    let keyframe_timestamps = extract_keyframe_timestamps(&path)?;
-    println!(
-        "[extract_frames] Found {} I-frames at: {:?}",
-        keyframe_timestamps.len(),
-        keyframe_timestamps
-            .iter()
-            .map(|t| format!("{:.1}s", t))
-            .collect::<Vec<_>>()
-    );
+    // Step 1: Find candidate boundaries across the whole file
+    let mut scene_changes: Vec<SceneChange> = Vec::new();
+    let boundaries = match strategy {
+        SamplingStrategy::Keyframes => {
+            println!("[extract_frames] Step 1: Extracting I-frames (keyframes)...");
+            job.progress(0.0, "Finding keyframes");
+            let keyframe_timestamps = cached_or_compute(store, DATA_TYPE_KEYFRAMES, || {
+                extract_keyframe_timestamps(job, source)
+            })?;
+            println!(
+                "[extract_frames] Found {} I-frames at: {:?}",
+                keyframe_timestamps.len(),
+                keyframe_timestamps
+                    .iter()
+                    .map(|t| format!("{:.1}s", t))
+                    .collect::<Vec<_>>()
+            );
+            keyframe_timestamps
+        }
+        SamplingStrategy::SceneDetect { threshold } | SamplingStrategy::Hybrid { threshold } => {
+            println!(
+                "[extract_frames] Step 1: Detecting scene changes (threshold {})...",
+                threshold
+            );
+            job.progress(0.0, "Detecting scene changes");
+            let data_type = format!("{}_{}", DATA_TYPE_SCENE_CHANGES, threshold);
+            scene_changes = cached_or_compute(store, &data_type, || {
+                detect_scene_changes(job, source, threshold)
+            })?;
+            println!(
+                "[extract_frames] Found {} scene changes",
+                scene_changes.len()
+            );
+            scene_changes.iter().map(|s| s.timestamp).collect()
+        }
+        SamplingStrategy::Uniform { .. } => Vec::new(),
+    };
 
-    // Step 2: Calculate which timestamps we need (I-frames + gap fills)
-    let mut target_timestamps = calculate_target_timestamps(&keyframe_timestamps, duration);
+    // Step 2: Calculate which timestamps each span needs
+    let mut span_targets: Vec<Vec<f64>> = spans
+        .iter()
+        .map(|span| span_target_timestamps(strategy, &boundaries, span))
+        .collect();
     println!(
-        "[extract_frames] Step 2: Target timestamps (with gap fills): {} frames",
-        target_timestamps.len()
+        "[extract_frames] Step 2: Target timestamps: {} frames in {} span(s)",
+        span_targets.iter().map(|t| t.len()).sum::<usize>(),
+        spans.len()
     );
 
-    // Step 3: Limit to max frames (prioritize even distribution)
-    if target_timestamps.len() > max as usize {
-        target_timestamps = subsample_timestamps(&target_timestamps, max as usize);
-        println!(
-            "[extract_frames] Step 3: Subsampled to {} frames",
-            target_timestamps.len()
-        );
+    // Step 3a: Drop look-alike frames, hashing a few candidates per slot in one pass
+    if let Some(threshold) = dedup_threshold {
+        for (targets, span) in span_targets.iter_mut().zip(&spans) {
+            *targets = subsample_timestamps(targets, span.budget * DEDUP_OVERSAMPLE);
+        }
+        job.progress(PROGRESS_DEDUP, "Comparing frames");
+        let hashed = frame_hashes(job, source, &merge_timestamps(&span_targets), store)?;
+
+        for targets in span_targets.iter_mut() {
+            let candidates: Vec<(f64, u64)> = hashed
+                .iter()
+                .filter(|(ts, _)| targets.contains(ts))
+                .copied()
+                .collect();
+            let distinct = dedupe_by_hash(candidates, |(_, hash)| *hash, threshold);
+            *targets = distinct.into_iter().map(|(ts, _)| ts).collect();
+        }
+    }
+
+    // Step 3: Limit each span to its budget (prioritize even distribution)
+    for (targets, span) in span_targets.iter_mut().zip(&spans) {
+        if targets.len() > span.budget {
+            *targets = subsample_timestamps(targets, span.budget);
+            println!(
+                "[extract_frames] Step 3: Subsampled to {} frames",
+                targets.len()
+            );
+        }
+    }
+
+    // Step 3b: Move each frame to the best usable one nearby, dropping those with none
+    if let Some(options) = &quality {
+        job.progress(PROGRESS_QUALITY, "Scoring frames");
+        nudge_to_usable_frames(job, source, &mut span_targets, &spans, options, store)?;
     }
 
-    // Step 4: Extract frames at specific timestamps
+    // Step 4: Extract frames at specific timestamps, all spans in a single pass
+    let target_timestamps = merge_timestamps(&span_targets);
     println!(
         "[extract_frames] Step 4: Extracting {} frames at specific timestamps...",
         target_timestamps.len()
     );
-    let frames = extract_frames_at_timestamps(&path, &target_timestamps, temp_path)?;
+    let mime_type = encoding.format.mime_type();
+    let extracted =
+        extract_frames_within_budget(job, source, &target_timestamps, temp_path, encoding, store)?;
+
+    let mut frames: Vec<ExtractedFrame> = Vec::new();
+    for (targets, span) in span_targets.iter().zip(&spans) {
+        for frame in extracted.iter().filter(|f| targets.contains(&f.timestamp)) {
+            frames.push(ExtractedFrame {
+                timestamp: frame.timestamp,
+                data: STANDARD.encode(&frame.bytes),
+                mime_type: mime_type.to_string(),
+                width: frame.width,
+                height: frame.height,
+                byte_size: frame.bytes.len() as u64,
+                strategy,
+                scene_score: scene_score_at(&scene_changes, frame.timestamp),
+                hash: frame.hash.clone(),
+                range_id: span.id.clone(),
+            });
+        }
+    }
+
+    if let Some(store) = store {
+        store.frames.evict(store.db)?;
+    }
 
     println!("[extract_frames] Done! Extracted {} frames", frames.len());
     Ok(frames)
 }
 
-/// Get video duration in seconds using ffprobe
-fn get_video_duration(path: &str) -> Result<f64, String> {
-    let output = Command::new("ffprobe")
-        .args([
-            "-v",
-            "error",
-            "-show_entries",
-            "format=duration",
-            "-of",
-            "default=noprint_wrappers=1:nokey=1",
-            path,
-        ])
-        .output()
-        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+/// Load per-source data from the cache, computing and storing it on a miss.
+/// Without a store this just computes.
+fn cached_or_compute<T: Serialize + DeserializeOwned>(
+    store: Option<&FrameStore>,
+    data_type: &str,
+    compute: impl FnOnce() -> Result<T, LexiError>,
+) -> Result<T, LexiError> {
+    let Some(store) = store else {
+        return compute();
+    };
+
+    if let Some(json) = store.db.get_cached(store.cid, data_type)? {
+        if let Ok(value) = serde_json::from_str(&json) {
+            return Ok(value);
+        }
+    }
+
+    let value = compute()?;
+    let json = serde_json::to_string(&value)
+        .map_err(|e| LexiError::serialization("Failed to serialize frame sampling data", e))?;
+    store.db.set_cached(store.cid, data_type, &json)?;
+    Ok(value)
+}
+
+/// Replace each target with the best usable frame within the quality window (never
+/// leaving its span), or drop it. Results are cached per target timestamp and span.
+fn nudge_to_usable_frames(
+    job: &JobHandle,
+    source: FrameSource,
+    span_targets: &mut [Vec<f64>],
+    spans: &[FrameSpan],
+    options: &FrameQualityOptions,
+    store: Option<&FrameStore>,
+) -> Result<(), LexiError> {
+    let data_type = format!("{}_{}", DATA_TYPE_FRAME_QUALITY, options.cache_key());
+    let to_ms = |ts: f64| (ts * 1000.0).round() as i64;
+    // The span clips the search window, so the same target can nudge differently per span
+    let key = |ts: f64, span: &FrameSpan| (to_ms(ts), to_ms(span.start), to_ms(span.end));
+
+    // (target, span start, span end) in ms -> chosen timestamp, None when nothing
+    // nearby is usable
+    let mut nudged: Vec<((i64, i64, i64), Option<f64>)> = match store {
+        Some(store) => store
+            .db
+            .get_cached(store.cid, &data_type)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    let mut windows: Vec<(f64, &FrameSpan, Vec<f64>)> = Vec::new();
+    for (targets, span) in span_targets.iter().zip(spans) {
+        for &target in targets.iter() {
+            if !nudged.iter().any(|(k, _)| *k == key(target, span)) {
+                windows.push((target, span, quality_window(target, options.window(), span)));
+            }
+        }
+    }
+
+    if !windows.is_empty() {
+        let candidates: Vec<Vec<f64>> = windows.iter().map(|(_, _, w)| w.clone()).collect();
+        let frames = raw_frames_at_timestamps(
+            job,
+            source,
+            &merge_timestamps(&candidates),
+            QUALITY_FILTER,
+            QUALITY_FRAME_BYTES,
+        )?;
+
+        let thresholds = options.thresholds();
+        for (target, span, window) in &windows {
+            let scored: Vec<(f64, &[u8])> = window
+                .iter()
+                .filter_map(|ts| frames.iter().find(|(t, _)| t == ts))
+                .map(|(ts, pixels)| (*ts, pixels.as_slice()))
+                .collect();
+            nudged.push((key(*target, span), best_in_window(&scored, *target, &thresholds)));
+        }
+
+        if let Some(store) = store {
+            let json = serde_json::to_string(&nudged)
+                .map_err(|e| LexiError::serialization("Failed to serialize frame quality", e))?;
+            store.db.set_cached(store.cid, &data_type, &json)?;
+        }
+    }
+
+    for (targets, span) in span_targets.iter_mut().zip(spans) {
+        let mut usable: Vec<f64> = targets
+            .iter()
+            .filter_map(|&t| nudged.iter().find(|(k, _)| *k == key(t, span)).and_then(|n| n.1))
+            .collect();
+        usable.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        usable.dedup();
+        *targets = usable;
+    }
+
+    Ok(())
+}
+
+/// `target` plus evenly spaced neighbours within `window` seconds, inside the span
+fn quality_window(target: f64, window: f64, span: &FrameSpan) -> Vec<f64> {
+    let step = window / QUALITY_STEPS as f64;
+    (-QUALITY_STEPS..=QUALITY_STEPS)
+        .map(|k| ((target + k as f64 * step) * 1000.0).round() / 1000.0)
+        .filter(|&t| t == target || (t >= span.start && t < span.end))
+        .fold(Vec::new(), |mut window, t| {
+            if window.last() != Some(&t) {
+                window.push(t);
+            }
+            window
+        })
+}
+
+/// Perceptual hashes for dedup, hashing only timestamps not already in the cache
+fn frame_hashes(
+    job: &JobHandle,
+    source: FrameSource,
+    timestamps: &[f64],
+    store: Option<&FrameStore>,
+) -> Result<Vec<(f64, u64)>, LexiError> {
+    let Some(store) = store else {
+        return hash_frames_at_timestamps(job, source, timestamps);
+    };
+
+    let known = store.db.get_frame_hashes(store.cid)?;
+    let lookup = |ts: f64| {
+        let ms = (ts * 1000.0).round() as i64;
+        known.iter().find(|(k, _)| *k == ms).map(|(_, hash)| *hash)
+    };
+
+    let missing: Vec<f64> = timestamps
+        .iter()
+        .copied()
+        .filter(|&ts| lookup(ts).is_none())
+        .collect();
+    let computed = hash_frames_at_timestamps(job, source, &missing)?;
+    store.db.set_frame_hashes(
+        store.cid,
+        &computed
+            .iter()
+            .map(|(ts, hash)| ((ts * 1000.0).round() as i64, *hash))
+            .collect::<Vec<_>>(),
+    )?;
+
+    Ok(timestamps
+        .iter()
+        .filter_map(|&ts| {
+            lookup(ts)
+                .or_else(|| computed.iter().find(|(t, _)| *t == ts).map(|(_, h)| *h))
+                .map(|hash| (ts, hash))
+        })
+        .collect())
+}
+
+/// A part of the video sampled with its own frame budget
+struct FrameSpan {
+    id: Option<String>,
+    start: f64,
+    end: f64,
+    budget: usize,
+}
+
+/// Validate requested ranges, or cover the whole file when none are given.
+/// Ranges without their own budget share `max` evenly.
+fn frame_spans(
+    ranges: Option<Vec<FrameRange>>,
+    duration: f64,
+    max: usize,
+) -> Result<Vec<FrameSpan>, LexiError> {
+    let ranges = match ranges {
+        Some(ranges) if !ranges.is_empty() => ranges,
+        _ => {
+            return Ok(vec![FrameSpan {
+                id: None,
+                start: 0.0,
+                end: duration,
+                budget: max,
+            }])
+        }
+    };
+
+    let shared_budget = (max / ranges.len()).max(1);
+    ranges
+        .into_iter()
+        .map(|range| {
+            let end = range.end.min(duration);
+            if range.start < 0.0 || range.start >= end {
+                return Err(LexiError::invalid_input(format!(
+                    "Invalid frame range {} ({:.2}-{:.2}s) for a {:.2}s video",
+                    range.id, range.start, range.end, duration
+                )));
+            }
+            Ok(FrameSpan {
+                id: Some(range.id),
+                start: range.start,
+                end,
+                budget: range.max_frames.map_or(shared_budget, |m| m as usize),
+            })
+        })
+        .collect()
+}
+
+/// Target timestamps inside one span, from boundaries found across the whole file.
+/// For the whole-file span this is the same as sampling the file directly.
+fn span_target_timestamps(
+    strategy: SamplingStrategy,
+    boundaries: &[f64],
+    span: &FrameSpan,
+) -> Vec<f64> {
+    let length = span.end - span.start;
+    let mut local: Vec<f64> = boundaries
+        .iter()
+        .filter(|&&t| t >= span.start && t < span.end)
+        .map(|t| t - span.start)
+        .collect();
+
+    let targets = match strategy {
+        SamplingStrategy::Keyframes => calculate_target_timestamps(&local, length),
+        SamplingStrategy::SceneDetect { .. } | SamplingStrategy::Hybrid { .. } => {
+            if local.first().is_none_or(|&t| t > MIN_FRAME_DISTANCE) {
+                local.insert(0, 0.0);
+            }
+            if matches!(strategy, SamplingStrategy::Hybrid { .. }) {
+                calculate_target_timestamps(&local, length)
+            } else {
+                dedupe_timestamps(&local)
+            }
+        }
+        SamplingStrategy::Uniform { interval } => uniform_timestamps(length, interval),
+    };
+
+    targets.into_iter().map(|t| t + span.start).collect()
+}
+
+/// Sorted, de-duplicated union of every span's timestamps
+fn merge_timestamps(span_targets: &[Vec<f64>]) -> Vec<f64> {
+    let mut merged: Vec<f64> = span_targets.iter().flatten().copied().collect();
+    merged.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    merged.dedup();
+    merged
+}
+
+/// Detect scene changes with ffmpeg's `select` scene score, in one decode pass
+fn detect_scene_changes(
+    job: &JobHandle,
+    source: FrameSource,
+    threshold: f64,
+) -> Result<Vec<SceneChange>, LexiError> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(LexiError::invalid_input(format!(
+            "Scene threshold must be between 0 and 1, got {}",
+            threshold
+        )));
+    }
+
+    let filter = format!(
+        "scale={}:-2,select='gt(scene,{})',metadata=print:key=lavfi.scene_score",
+        SCENE_DETECT_WIDTH, threshold
+    );
+    let output = job.output(Command::new("ffmpeg").args([
+        "-hide_banner",
+        "-i", source.path,
+        "-an",
+        "-vf", &filter,
+        "-f", "null",
+        "-",
+    ]))?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("ffprobe failed: {}", stderr));
+        return Err(LexiError::ffmpeg_failed(
+            "ffmpeg scene detection failed",
+            &output.stderr,
+        ));
+    }
+
+    let mut changes = parse_scene_metadata(&String::from_utf8_lossy(&output.stderr));
+    for change in &mut changes {
+        change.timestamp -= source.start;
+    }
+    Ok(changes)
+}
+
+/// Parse `metadata=print` output: a `pts_time:` line followed by `lavfi.scene_score=`
+fn parse_scene_metadata(log: &str) -> Vec<SceneChange> {
+    let mut changes = Vec::new();
+    let mut current_time: Option<f64> = None;
+
+    for line in log.lines() {
+        if !line.contains("Parsed_metadata") {
+            continue;
+        }
+        if let Some(idx) = line.find("pts_time:") {
+            current_time = line[idx + "pts_time:".len()..]
+                .split_whitespace()
+                .next()
+                .and_then(|t| t.parse().ok());
+        } else if let Some(idx) = line.find("lavfi.scene_score=") {
+            let score = line[idx + "lavfi.scene_score=".len()..].trim().parse().ok();
+            if let (Some(timestamp), Some(score)) = (current_time.take(), score) {
+                changes.push(SceneChange { timestamp, score });
+            }
+        }
     }
 
-    let duration_str = String::from_utf8_lossy(&output.stdout);
-    duration_str
-        .trim()
-        .parse::<f64>()
-        .map_err(|e| format!("Failed to parse duration '{}': {}", duration_str.trim(), e))
+    changes
+}
+
+/// Score of the scene change at `timestamp`, if the frame was placed on one
+fn scene_score_at(scene_changes: &[SceneChange], timestamp: f64) -> Option<f64> {
+    scene_changes
+        .iter()
+        .find(|s| (s.timestamp - timestamp).abs() < TIMESTAMP_EPSILON)
+        .map(|s| s.score)
+}
+
+/// One timestamp every `interval` seconds, starting at 0
+fn uniform_timestamps(duration: f64, interval: f64) -> Vec<f64> {
+    let interval = interval.max(0.1);
+    // Multiplying rather than accumulating keeps long sources from drifting
+    (0..)
+        .map(|i| i as f64 * interval)
+        .take_while(|&ts| ts < duration)
+        .collect()
+}
+
+/// Drop timestamps closer than `MIN_FRAME_DISTANCE` to the previous kept one
+fn dedupe_timestamps(timestamps: &[f64]) -> Vec<f64> {
+    let mut result: Vec<f64> = Vec::new();
+    for &ts in timestamps {
+        if result.last().is_none_or(|&last| ts - last >= MIN_FRAME_DISTANCE) {
+            result.push(ts);
+        }
+    }
+    result
 }
 
 /// Extract timestamps of all I-frames using ffprobe
-fn extract_keyframe_timestamps(path: &str) -> Result<Vec<f64>, String> {
-    let output = Command::new("ffprobe")
-        .args([
-            "-v",
-            "error",
-            "-select_streams",
-            "v:0",
-            "-show_entries",
-            "frame=pts_time,pict_type",
-            "-of",
-            "csv=print_section=0",
-            path,
-        ])
-        .output()
-        .map_err(|e| format!("Failed to run ffprobe for keyframes: {}", e))?;
+fn extract_keyframe_timestamps(
+    job: &JobHandle,
+    source: FrameSource,
+) -> Result<Vec<f64>, LexiError> {
+    let output = job.output(Command::new("ffprobe").args([
+        "-v",
+        "error",
+        "-select_streams",
+        "v:0",
+        "-show_entries",
+        "frame=pts_time,pict_type",
+        "-of",
+        "csv=print_section=0",
+        source.path,
+    ]))?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("ffprobe keyframe extraction failed: {}", stderr));
+        return Err(LexiError::ffmpeg_failed(
+            "ffprobe keyframe extraction failed",
+            &output.stderr,
+        ));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -139,7 +917,7 @@ fn extract_keyframe_timestamps(path: &str) -> Result<Vec<f64>, String> {
             let pict_type = parts[1].trim();
             if pict_type == "I" {
                 if let Ok(ts) = parts[0].trim().parse::<f64>() {
-                    timestamps.push(ts);
+                    timestamps.push(ts - source.start);
                 }
             }
         }
@@ -230,36 +1008,289 @@ fn subsample_timestamps(timestamps: &[f64], max: usize) -> Vec<f64> {
     result
 }
 
-/// Extract frames at specific timestamps
+/// Tolerance when comparing frame times reported by showinfo against targets
+const TIMESTAMP_EPSILON: f64 = 1e-4;
+
+/// Build a `select` expression that passes the first decoded frame at or after
+/// each target timestamp, matching what a per-frame accurate `-ss` seek returns.
+/// `select` sees absolute times, so targets are offset by the container start time.
+fn frame_select_expr(timestamps: &[f64], start: f64) -> String {
+    timestamps
+        .iter()
+        .map(|ts| {
+            let ts = ts + start;
+            format!(
+                "gte(t,{ts:.3})*(isnan(prev_selected_t)+lt(prev_selected_t,{ts:.3}))",
+                ts = ts
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// Presentation time of a frame from a showinfo log line
+fn parse_showinfo_pts_time(line: &str) -> Option<f64> {
+    if !line.contains("Parsed_showinfo") {
+        return None;
+    }
+    let rest = &line[line.find("pts_time:")? + "pts_time:".len()..];
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+/// Map each target timestamp to the index of the first selected frame at or after it.
+/// Frame times are absolute, targets relative to `start`. Targets past the end of the
+/// video have no frame and are skipped.
+fn match_frames_to_timestamps(
+    timestamps: &[f64],
+    frame_times: &[f64],
+    start: f64,
+) -> Vec<(f64, usize)> {
+    timestamps
+        .iter()
+        .filter_map(|&ts| {
+            let rounded = ((ts + start) * 1000.0).round() / 1000.0;
+            frame_times
+                .iter()
+                .position(|&t| t >= rounded - TIMESTAMP_EPSILON)
+                .map(|idx| (ts, idx))
+        })
+        .collect()
+}
+
+/// Frame size from a showinfo log line (`s:1920x1080`)
+fn parse_showinfo_size(line: &str) -> Option<(u32, u32)> {
+    let size = line
+        .split_whitespace()
+        .find_map(|token| token.strip_prefix("s:"))?;
+    let (width, height) = size.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+/// Extract frames, re-encoding smaller until the total fits `max_total_bytes`.
+/// With a budget the video is decoded only once, to lossless frames that every
+/// attempt re-encodes. Gives up after a few attempts and returns the smallest result.
+fn extract_frames_within_budget(
+    job: &JobHandle,
+    source: FrameSource,
+    timestamps: &[f64],
+    temp_path: &Path,
+    mut encoding: FrameEncoding,
+    store: Option<&FrameStore>,
+) -> Result<Vec<RawFrame>, LexiError> {
+    let Some(budget) = encoding.max_total_bytes else {
+        return extract_frames_cached(timestamps, &encoding, store, |misses| {
+            extract_frames_at_timestamps(job, source, misses, temp_path, &encoding)
+        });
+    };
+
+    // Decoded on the first cache miss, at the requested size; shrinking only goes down
+    let master_path = temp_path.join("master");
+    let master_encoding = FrameEncoding {
+        format: FrameFormat::Png,
+        quality: None,
+        max_total_bytes: None,
+        ..encoding.clone()
+    };
+    let mut masters: Option<Vec<RawFrame>> = None;
+
+    let mut attempt = 1;
+    loop {
+        // Fresh directory per attempt so no stale frames are picked up
+        let pass_path = temp_path.join(format!("pass_{}", attempt));
+        fs::create_dir_all(&pass_path)
+            .map_err(|e| LexiError::io("Failed to create temp directory", &pass_path, e))?;
+
+        let frames = extract_frames_cached(timestamps, &encoding, store, |misses| {
+            if masters.is_none() {
+                fs::create_dir_all(&master_path).map_err(|e| {
+                    LexiError::io("Failed to create temp directory", &master_path, e)
+                })?;
+                masters = Some(extract_frames_at_timestamps(
+                    job,
+                    source,
+                    timestamps,
+                    &master_path,
+                    &master_encoding,
+                )?);
+            }
+            let wanted: Vec<&RawFrame> = masters
+                .iter()
+                .flatten()
+                .filter(|m| misses.contains(&m.timestamp))
+                .collect();
+            encode_frames(job, &wanted, &pass_path, &encoding)
+        })?;
+
+        let total: u64 = frames.iter().map(|f| f.bytes.len() as u64).sum();
+        if total <= budget {
+            return Ok(frames);
+        }
+
+        let long_edge = frames.iter().map(|f| f.width.max(f.height)).max().unwrap_or(0);
+        let next = encoding
+            .shrink_for_budget(total, budget, long_edge)
+            .filter(|_| attempt < MAX_BUDGET_ATTEMPTS);
+        let Some(next) = next else {
+            println!(
+                "[extract_frames] Warning: could not fit frames in {} bytes, returning {} bytes",
+                budget, total
+            );
+            return Ok(frames);
+        };
+
+        encoding = next;
+        attempt += 1;
+    }
+}
+
+/// Serve frames from the frame cache, calling `extract` for the misses only and
+/// caching what it returns. Without a store everything is extracted.
+fn extract_frames_cached(
+    timestamps: &[f64],
+    encoding: &FrameEncoding,
+    store: Option<&FrameStore>,
+    mut extract: impl FnMut(&[f64]) -> Result<Vec<RawFrame>, LexiError>,
+) -> Result<Vec<RawFrame>, LexiError> {
+    let Some(store) = store else {
+        return extract(timestamps);
+    };
+
+    let options_key = encoding.cache_key();
+    let key = |timestamp: f64| FrameKey {
+        cid: store.cid,
+        timestamp,
+        options_key: &options_key,
+    };
+
+    let mut frames: Vec<RawFrame> = Vec::new();
+    let mut misses: Vec<f64> = Vec::new();
+    for &ts in timestamps {
+        match store.frames.get(store.db, &key(ts))? {
+            Some(hit) => frames.push(RawFrame {
+                timestamp: ts,
+                bytes: hit.bytes,
+                width: hit.width,
+                height: hit.height,
+                hash: hit.hash,
+            }),
+            None => misses.push(ts),
+        }
+    }
+
+    let extracted = if misses.is_empty() { Vec::new() } else { extract(&misses)? };
+    for frame in extracted {
+        store.frames.put(
+            store.db,
+            &key(frame.timestamp),
+            encoding.format.extension(),
+            &frame.bytes,
+            (frame.width, frame.height),
+            frame.hash.as_deref(),
+        )?;
+        frames.push(frame);
+    }
+
+    frames.sort_by(|a, b| a.timestamp.partial_cmp(&b.timestamp).unwrap());
+    Ok(frames)
+}
+
+/// Extract frames at specific timestamps in a single decode pass
 fn extract_frames_at_timestamps(
-    path: &str,
+    job: &JobHandle,
+    source: FrameSource,
     timestamps: &[f64],
-    temp_path: &std::path::Path,
-) -> Result<Vec<ExtractedFrame>, String> {
-    let mut frames: Vec<ExtractedFrame> = Vec::new();
+    temp_path: &Path,
+    encoding: &FrameEncoding,
+) -> Result<Vec<RawFrame>, LexiError> {
+    if timestamps.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Each selected frame goes to a numbered image and, shrunk to a tiny grayscale
+    // grid, to stdout for perceptual hashing
+    let extension = encoding.format.extension();
+    let output_pattern = temp_path.join(format!("frame_%04d.{}", extension));
+    let scale = encoding
+        .scale_filter()
+        .map(|filter| format!("{},", filter))
+        .unwrap_or_default();
+    let filter = format!(
+        "[0:v]select='{}',{}showinfo,split=2[full][small];[small]{}[hash]",
+        frame_select_expr(timestamps, source.start),
+        scale,
+        DHASH_FILTER
+    );
+
+    let mut args: Vec<String> = [
+        "-y",
+        "-i", source.path,
+        "-filter_complex", &filter,
+        "-fps_mode", "vfr",
+        "-map", "[hash]", "-f", "rawvideo", "pipe:1",
+        "-map", "[full]",
+    ]
+    .iter()
+    .map(|a| a.to_string())
+    .collect();
+    args.extend(encoding.codec_args());
+    args.push(output_pattern.to_string_lossy().to_string());
+
+    job.progress(PROGRESS_EXTRACT, format!("Extracting {} frames", timestamps.len()));
+    let child = job.spawn(
+        Command::new("ffmpeg")
+            .args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )?;
+
+    let stdout = child.take_stdout();
+    let hash_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut stdout) = stdout {
+            let _ = stdout.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    // showinfo logs one line per selected frame, in output order
+    let mut frame_times: Vec<f64> = Vec::new();
+    let mut frame_sizes: Vec<(u32, u32)> = Vec::new();
+    let mut stderr_log = String::new();
+    if let Some(stderr) = child.take_stderr() {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            match parse_showinfo_pts_time(&line) {
+                Some(pts_time) => {
+                    frame_times.push(pts_time);
+                    frame_sizes.push(parse_showinfo_size(&line).unwrap_or((0, 0)));
+                    let done = frame_times.len() as f64 / timestamps.len() as f64;
+                    job.progress(
+                        PROGRESS_EXTRACT + done * (100.0 - PROGRESS_EXTRACT),
+                        format!("Extracting frame {} of {}", frame_times.len(), timestamps.len()),
+                    );
+                }
+                None => {
+                    stderr_log.push_str(&line);
+                    stderr_log.push('\n');
+                }
+            }
+        }
+    }
+
+    let status = child.wait()?;
+    let hash_bytes = hash_reader.join().unwrap_or_default();
+    if !status.success() {
+        // Keep whatever was decoded before the failure (might be at very end)
+        println!(
+            "[extract_frames] Warning: ffmpeg exited with an error after {} frames: {}",
+            frame_times.len(),
+            stderr_log.lines().last().unwrap_or("")
+        );
+    }
 
-    for (idx, &ts) in timestamps.iter().enumerate() {
-        let output_path = temp_path.join(format!("frame_{:04}.jpg", idx));
-
-        // Use ffmpeg to extract a single frame at the specific timestamp
-        let output = Command::new("ffmpeg")
-            .args([
-                "-y",
-                "-ss",
-                &format!("{:.3}", ts),
-                "-i",
-                path,
-                "-vframes",
-                "1",
-                "-q:v",
-                "2",
-                output_path.to_str().unwrap(),
-            ])
-            .output()
-            .map_err(|e| format!("Failed to extract frame at {:.1}s: {}", ts, e))?;
-
-        if !output.status.success() {
-            // Skip frames that fail (might be at very end)
+    let mut frames: Vec<RawFrame> = Vec::new();
+    for (ts, idx) in match_frames_to_timestamps(timestamps, &frame_times, source.start) {
+        let frame_path = temp_path.join(format!("frame_{:04}.{}", idx + 1, extension));
+        if !frame_path.exists() {
             println!(
                 "[extract_frames] Warning: Failed to extract frame at {:.1}s, skipping",
                 ts
@@ -267,16 +1298,734 @@ fn extract_frames_at_timestamps(
             continue;
         }
 
-        if output_path.exists() {
-            let bytes = fs::read(&output_path)
-                .map_err(|e| format!("Failed to read frame at {:.1}s: {}", ts, e))?;
+        let bytes = fs::read(&frame_path)
+            .map_err(|e| LexiError::io("Failed to read frame", &frame_path, e))?;
 
-            frames.push(ExtractedFrame {
-                timestamp: ts,
-                data: STANDARD.encode(&bytes),
-            });
-        }
+        let hash = hash_bytes
+            .chunks_exact(DHASH_FRAME_BYTES)
+            .nth(idx)
+            .and_then(dhash)
+            .map(format_hash);
+
+        let (width, height) = frame_sizes[idx];
+        frames.push(RawFrame {
+            timestamp: ts,
+            bytes,
+            width,
+            height,
+            hash,
+        });
     }
 
     Ok(frames)
 }
+
+/// Re-encode already decoded frames with `encoding`, in one ffmpeg run over the
+/// images. Timestamps and hashes carry over; sizes are read back from showinfo.
+fn encode_frames(
+    job: &JobHandle,
+    frames: &[&RawFrame],
+    temp_path: &Path,
+    encoding: &FrameEncoding,
+) -> Result<Vec<RawFrame>, LexiError> {
+    if frames.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    for (n, frame) in frames.iter().enumerate() {
+        let input = temp_path.join(format!("in_{:04}.png", n + 1));
+        fs::write(&input, &frame.bytes)
+            .map_err(|e| LexiError::io("Failed to write frame", &input, e))?;
+    }
+
+    let extension = encoding.format.extension();
+    let input_pattern = temp_path.join("in_%04d.png").to_string_lossy().to_string();
+    let output_pattern = temp_path.join(format!("frame_%04d.{}", extension));
+    let filter = match encoding.scale_filter() {
+        Some(scale) => format!("{},showinfo", scale),
+        None => "showinfo".to_string(),
+    };
+
+    let mut args: Vec<String> = [
+        "-y",
+        "-f", "image2",
+        "-start_number", "1",
+        "-i", &input_pattern,
+        "-vf", &filter,
+    ]
+    .iter()
+    .map(|a| a.to_string())
+    .collect();
+    args.extend(encoding.codec_args());
+    args.push(output_pattern.to_string_lossy().to_string());
+
+    let output = job.output(Command::new("ffmpeg").args(&args))?;
+    if !output.status.success() {
+        return Err(LexiError::ffmpeg_failed(
+            "ffmpeg frame re-encoding failed",
+            &output.stderr,
+        ));
+    }
+
+    let sizes: Vec<(u32, u32)> = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|line| parse_showinfo_pts_time(line).is_some())
+        .filter_map(parse_showinfo_size)
+        .collect();
+
+    frames
+        .iter()
+        .enumerate()
+        .map(|(n, frame)| {
+            let path = temp_path.join(format!("frame_{:04}.{}", n + 1, extension));
+            let bytes =
+                fs::read(&path).map_err(|e| LexiError::io("Failed to read frame", &path, e))?;
+            let (width, height) = sizes.get(n).copied().unwrap_or((frame.width, frame.height));
+            Ok(RawFrame {
+                timestamp: frame.timestamp,
+                bytes,
+                width,
+                height,
+                hash: frame.hash.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Perceptual hashes of the frames at `timestamps`, without writing any images.
+/// Targets past the end of the video are dropped.
+fn hash_frames_at_timestamps(
+    job: &JobHandle,
+    source: FrameSource,
+    timestamps: &[f64],
+) -> Result<Vec<(f64, u64)>, LexiError> {
+    Ok(raw_frames_at_timestamps(job, source, timestamps, DHASH_FILTER, DHASH_FRAME_BYTES)?
+        .into_iter()
+        .filter_map(|(ts, pixels)| dhash(&pixels).map(|hash| (ts, hash)))
+        .collect())
+}
+
+/// Small raw frames for analysis (hashing, quality scoring), all in one ffmpeg pass.
+/// `filter` must produce frames of exactly `frame_bytes` bytes.
+fn raw_frames_at_timestamps(
+    job: &JobHandle,
+    source: FrameSource,
+    timestamps: &[f64],
+    filter: &str,
+    frame_bytes: usize,
+) -> Result<Vec<(f64, Vec<u8>)>, LexiError> {
+    if timestamps.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let filter = format!(
+        "select='{}',showinfo,{}",
+        frame_select_expr(timestamps, source.start),
+        filter
+    );
+    let output = job.output(Command::new("ffmpeg").args([
+        "-i", source.path,
+        "-an",
+        "-vf", &filter,
+        "-fps_mode", "vfr",
+        "-f", "rawvideo",
+        "pipe:1",
+    ]))?;
+
+    if !output.status.success() {
+        return Err(LexiError::ffmpeg_failed(
+            "ffmpeg frame analysis failed",
+            &output.stderr,
+        ));
+    }
+
+    let frame_times: Vec<f64> = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter_map(parse_showinfo_pts_time)
+        .collect();
+    let frames: Vec<&[u8]> = output.stdout.chunks_exact(frame_bytes).collect();
+
+    Ok(match_frames_to_timestamps(timestamps, &frame_times, source.start)
+        .into_iter()
+        .filter_map(|(ts, idx)| frames.get(idx).map(|pixels| (ts, pixels.to_vec())))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::SystemRunner;
+    use crate::test_support::{make_test_clip, probe_json, test_jobs, Canned, FakeRunner};
+    use std::sync::Arc;
+
+    async fn run_extract(
+        runner: Arc<dyn crate::services::ProcessRunner>,
+        path: String,
+        max_frames: Option<u32>,
+        strategy: SamplingStrategy,
+    ) -> Result<Vec<ExtractedFrame>, LexiError> {
+        test_jobs(runner)
+            .run_blocking(JobKind::ExtractFrames, "test", move |job| {
+                let request = FrameRequest {
+                    max_frames,
+                    strategy,
+                    ..FrameRequest::default()
+                };
+                extract_frames(job, &path, request, None)
+            })
+            .await
+    }
+
+    const CLIP: &str = "/media/clip.mp4";
+
+    /// showinfo output for frames selected at `frame_times`
+    fn showinfo_log(frame_times: &[f64]) -> String {
+        frame_times
+            .iter()
+            .enumerate()
+            .map(|(n, t)| {
+                format!(
+                    "[Parsed_showinfo_1 @ 0x600000] n:{:4} pts:{:7} pts_time:{} duration:512 \
+                     fmt:yuvj420p sar:1/1 s:640x360 i:P iskey:0 type:P\n",
+                    n,
+                    (t * 12800.0) as i64,
+                    t
+                )
+            })
+            .collect()
+    }
+
+    /// metadata=print output for scene changes at `changes` (timestamp, score)
+    fn scene_log(changes: &[(f64, f64)]) -> String {
+        changes
+            .iter()
+            .enumerate()
+            .map(|(n, (t, score))| {
+                format!(
+                    "[Parsed_metadata_2 @ 0x600000] frame:{} pts:{} pts_time:{}\n\
+                     [Parsed_metadata_2 @ 0x600000] lavfi.scene_score={:.6}\n",
+                    n,
+                    (t * 12800.0) as i64,
+                    t,
+                    score
+                )
+            })
+            .collect()
+    }
+
+    fn fake_runner(frame_times: &[f64]) -> Arc<FakeRunner> {
+        fake_runner_with_scenes(frame_times, &[])
+    }
+
+    fn fake_runner_with_scenes(frame_times: &[f64], scenes: &[(f64, f64)]) -> Arc<FakeRunner> {
+        let runner = FakeRunner::new();
+        // The scene pass goes first: it also has a -vf argument
+        runner
+            .on("ffmpeg", &["null"], Canned::ok().with_stderr(&scene_log(scenes)))
+            .on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(12.0, 640, 360, 0)))
+            .on(
+                "ffprobe",
+                &["frame=pts_time,pict_type"],
+                Canned::stdout("0.000000,I\n0.040000,P\n0.080000,B\n6.000000,I\n6.040000,P\n"),
+            )
+            .on(
+                "ffmpeg",
+                &["-filter_complex"],
+                Canned::stdout_bytes(&vec![0; DHASH_FRAME_BYTES * frame_times.len()])
+                    .with_stderr(&showinfo_log(frame_times))
+                    .writing_numbered_outputs(frame_times.len(), b"jpeg-bytes"),
+            );
+        runner
+    }
+
+    #[tokio::test]
+    async fn keyframes_plus_gap_fills_in_one_pass() {
+        let expected = vec![0.0, 1.0, 2.0, 3.0, 4.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.5];
+        let runner = fake_runner(&expected);
+        let frames = run_extract(runner.clone(), CLIP.to_string(), None, SamplingStrategy::Keyframes)
+            .await
+            .unwrap();
+
+        let timestamps: Vec<f64> = frames.iter().map(|f| f.timestamp).collect();
+        assert_eq!(timestamps, expected);
+        assert!(frames.iter().all(|f| f.data == STANDARD.encode(b"jpeg-bytes")));
+
+        assert!(frames.iter().all(|f| f.hash.as_deref() == Some("0000000000000000")));
+        assert!(frames
+            .iter()
+            .all(|f| (f.width, f.height, f.byte_size) == (640, 360, 10) && f.mime_type == "image/jpeg"));
+
+        let calls = runner.calls_to("ffmpeg");
+        assert_eq!(calls.len(), 1);
+        let filter = calls[0].arg_after("-filter_complex").unwrap();
+        assert!(filter.starts_with("[0:v]select='gte(t,0.000)*"));
+        assert!(filter.contains("lt(prev_selected_t,11.500))',showinfo,split=2"));
+        assert!(calls[0].last_arg().unwrap().ends_with("frame_%04d.jpg"));
+        assert_eq!(calls[0].arg_after("-q:v"), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn cached_frames_are_served_without_ffmpeg() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let cache = Arc::new(FrameCache::new(dir.path().join("frames"), 1024 * 1024));
+        let expected = vec![0.0, 3.0, 8.0, 11.5];
+
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let runner = fake_runner(&expected);
+            let (db, cache) = (db.clone(), cache.clone());
+            let frames = test_jobs(runner.clone())
+                .run_blocking(JobKind::ExtractFrames, "test", move |job| {
+                    let request = FrameRequest {
+                        max_frames: Some(4),
+                        ..FrameRequest::default()
+                    };
+                    let store = FrameStore {
+                        cid: "cid1",
+                        db: &db,
+                        frames: &cache,
+                    };
+                    extract_frames(job, CLIP, request, Some(&store))
+                })
+                .await
+                .unwrap();
+            runs.push((frames, runner.calls().len()));
+        }
+
+        let (first, first_calls) = &runs[0];
+        let (second, second_calls) = &runs[1];
+        assert!(*first_calls > 0);
+        assert_eq!(*second_calls, 0);
+
+        let timestamps: Vec<f64> = second.iter().map(|f| f.timestamp).collect();
+        assert_eq!(timestamps, first.iter().map(|f| f.timestamp).collect::<Vec<_>>());
+        assert!(second
+            .iter()
+            .all(|f| f.data == STANDARD.encode(b"jpeg-bytes") && (f.width, f.height) == (640, 360)));
+    }
+
+    #[tokio::test]
+    async fn lowers_quality_until_frames_fit_the_byte_budget() {
+        let runner = fake_runner(&[0.0, 3.0, 8.0, 11.5]);
+        let encoding = FrameEncoding {
+            max_long_edge: Some(512),
+            max_total_bytes: Some(25),
+            ..FrameEncoding::default()
+        };
+
+        let times = [0.0, 3.0, 8.0, 11.5];
+        runner.on(
+            "ffmpeg",
+            &["image2"],
+            Canned::ok()
+                .with_stderr(&showinfo_log(&times))
+                .writing_numbered_outputs(times.len(), b"jpeg-bytes"),
+        );
+
+        // The fake always writes 10-byte frames, so every attempt is over budget
+        let frames = test_jobs(runner.clone())
+            .run_blocking(JobKind::ExtractFrames, "test", |job| {
+                let request = FrameRequest {
+                    max_frames: Some(4),
+                    encoding,
+                    ..FrameRequest::default()
+                };
+                extract_frames(job, CLIP, request, None)
+            })
+            .await
+            .unwrap();
+        assert_eq!(frames.len(), 4);
+
+        // The video is decoded once, losslessly; every attempt re-encodes those frames
+        let calls = runner.calls_to("ffmpeg");
+        let (decodes, encodes): (Vec<_>, Vec<_>) =
+            calls.iter().partition(|c| c.has_arg("-filter_complex"));
+        assert_eq!(decodes.len(), 1);
+        assert!(!decodes[0].has_arg("-q:v"));
+        assert!(decodes[0].last_arg().unwrap().ends_with(".png"));
+        assert!(decodes[0]
+            .arg_after("-filter_complex")
+            .unwrap()
+            .contains("scale='if(gte(iw,ih),min(512,iw),-2)':'if(gte(iw,ih),-2,min(512,ih))',showinfo"));
+
+        assert_eq!(encodes.len(), MAX_BUDGET_ATTEMPTS);
+        let qscales: Vec<u32> = encodes
+            .iter()
+            .map(|c| c.arg_after("-q:v").unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(qscales[0], 2);
+        assert!(qscales.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn shrinks_quality_then_size() {
+        let jpeg = FrameEncoding {
+            quality: Some(MIN_FRAME_QUALITY),
+            ..FrameEncoding::default()
+        };
+        let next = jpeg.shrink_for_budget(400, 100, 1920).unwrap();
+        assert_eq!((next.quality, next.max_long_edge), (Some(MIN_FRAME_QUALITY), Some(960)));
+
+        let png = FrameEncoding {
+            format: FrameFormat::Png,
+            ..FrameEncoding::default()
+        };
+        assert_eq!(png.shrink_for_budget(400, 100, 1920).unwrap().max_long_edge, Some(960));
+        assert_eq!(png.shrink_for_budget(400, 100, MIN_LONG_EDGE), None);
+
+        let webp = FrameEncoding {
+            format: FrameFormat::Webp,
+            ..FrameEncoding::default()
+        };
+        assert_eq!(webp.shrink_for_budget(400, 100, 1920).unwrap().quality, Some(40));
+        assert_eq!(webp.codec_args(), vec!["-c:v", "libwebp", "-quality", "80"]);
+    }
+
+    #[test]
+    fn encoding_deserializes_from_camel_case() {
+        let encoding: FrameEncoding = serde_json::from_str(
+            r#"{"maxLongEdge":768,"format":"webp","quality":70,"maxTotalBytes":2000000}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            encoding,
+            FrameEncoding {
+                max_long_edge: Some(768),
+                format: FrameFormat::Webp,
+                quality: Some(70),
+                max_total_bytes: Some(2_000_000),
+            }
+        );
+        assert_eq!(FrameEncoding::default().codec_args(), vec!["-q:v", "2"]);
+    }
+
+    #[tokio::test]
+    async fn dedup_replaces_lookalikes_with_distinct_moments() {
+        // Keyframe targets span two shots; the first shot comes back at the end
+        let candidates = [0.0, 1.0, 2.0, 3.0, 4.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.5];
+        let shot_b: Vec<u8> = (0..DHASH_FRAME_BYTES).map(|i| 255 - (i % 9) as u8 * 20).collect();
+        let hash_pixels: Vec<u8> = candidates
+            .iter()
+            .flat_map(|&t| {
+                if (6.0..11.0).contains(&t) {
+                    shot_b.clone()
+                } else {
+                    vec![0; DHASH_FRAME_BYTES]
+                }
+            })
+            .collect();
+
+        let runner = fake_runner(&[0.0, 6.0]);
+        runner.on(
+            "ffmpeg",
+            &["-vf", "rawvideo"],
+            Canned::stdout_bytes(&hash_pixels).with_stderr(&showinfo_log(&candidates)),
+        );
+
+        let frames = test_jobs(runner.clone())
+            .run_blocking(JobKind::ExtractFrames, "test", |job| {
+                let request = FrameRequest {
+                    max_frames: Some(4),
+                    dedup_threshold: Some(5),
+                    ..FrameRequest::default()
+                };
+                extract_frames(job, CLIP, request, None)
+            })
+            .await
+            .unwrap();
+
+        let timestamps: Vec<f64> = frames.iter().map(|f| f.timestamp).collect();
+        assert_eq!(timestamps, vec![0.0, 6.0]);
+
+        // The hash pass looks at every candidate, the extraction pass only the survivors
+        let calls = runner.calls_to("ffmpeg");
+        assert_eq!(calls.len(), 2);
+        assert!(calls[0].arg_after("-vf").unwrap().contains("lt(prev_selected_t,11.500))"));
+        assert!(!calls[1].arg_after("-filter_complex").unwrap().contains("7.000"));
+    }
+
+    #[tokio::test]
+    async fn quality_check_nudges_past_black_frames_and_drops_unusable_ones() {
+        let candidates = [
+            0.0, 0.25, 0.5, 3.5, 3.75, 4.0, 4.25, 4.5, 7.5, 7.75, 8.0, 8.25, 8.5,
+        ];
+        // Vertical stripes; wider swings score sharper
+        let stripes = |swing: u8| -> Vec<u8> {
+            (0..QUALITY_FRAME_BYTES)
+                .map(|i| if i.is_multiple_of(2) { 20 } else { 20 + swing })
+                .collect()
+        };
+        let pixels: Vec<u8> = candidates
+            .iter()
+            .flat_map(|&t| {
+                if t < 1.0 || t == 4.0 {
+                    vec![0; QUALITY_FRAME_BYTES]
+                } else if t == 4.25 {
+                    stripes(160)
+                } else {
+                    stripes(120)
+                }
+            })
+            .collect();
+
+        let runner = fake_runner(&[4.25, 8.0]);
+        runner.on(
+            "ffmpeg",
+            &["-vf", "rawvideo"],
+            Canned::stdout_bytes(&pixels).with_stderr(&showinfo_log(&candidates)),
+        );
+
+        let frames = test_jobs(runner.clone())
+            .run_blocking(JobKind::ExtractFrames, "test", |job| {
+                let request = FrameRequest {
+                    strategy: SamplingStrategy::Uniform { interval: 4.0 },
+                    quality: Some(FrameQualityOptions::default()),
+                    ..FrameRequest::default()
+                };
+                extract_frames(job, CLIP, request, None)
+            })
+            .await
+            .unwrap();
+
+        // 0s has nothing usable nearby, 4s moves to the sharpest neighbour
+        let timestamps: Vec<f64> = frames.iter().map(|f| f.timestamp).collect();
+        assert_eq!(timestamps, vec![4.25, 8.0]);
+
+        let calls = runner.calls_to("ffmpeg");
+        assert_eq!(calls.len(), 2);
+        assert!(calls[0].arg_after("-vf").unwrap().contains(QUALITY_FILTER));
+    }
+
+    #[tokio::test]
+    async fn nudges_are_cached_per_span() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let cache = Arc::new(FrameCache::new(dir.path().join("frames"), 1024 * 1024));
+        let runner = FakeRunner::new();
+        let jobs = test_jobs(runner.clone());
+
+        // Same target inside two ranges that clip its search window differently
+        for (start, end) in [(3.9, 6.0), (3.0, 4.1), (3.9, 6.0)] {
+            let (db, cache) = (db.clone(), cache.clone());
+            jobs.run_blocking(JobKind::ExtractFrames, "test", move |job| {
+                let store = FrameStore {
+                    cid: "cid1",
+                    db: &db,
+                    frames: &cache,
+                };
+                let span = FrameSpan {
+                    id: None,
+                    start,
+                    end,
+                    budget: 1,
+                };
+                let source = FrameSource { path: CLIP, start: 0.0 };
+                let options = FrameQualityOptions::default();
+                let mut targets = [vec![4.0]];
+                nudge_to_usable_frames(job, source, &mut targets, &[span], &options, Some(&store))
+            })
+            .await
+            .unwrap();
+        }
+
+        // The repeated first span is served from the cache
+        assert_eq!(runner.calls_to("ffmpeg").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn subsamples_to_max_frames() {
+        let expected = vec![0.0, 3.0, 8.0, 11.5];
+        let runner = fake_runner(&expected);
+        let frames = run_extract(runner, CLIP.to_string(), Some(4), SamplingStrategy::Keyframes)
+            .await
+            .unwrap();
+
+        let timestamps: Vec<f64> = frames.iter().map(|f| f.timestamp).collect();
+        assert_eq!(timestamps, expected);
+    }
+
+    #[test]
+    fn maps_targets_to_first_frame_at_or_after() {
+        // 25fps: the frame for 1.01 is the one at 1.04; nothing exists at 5.0
+        let targets = [0.0, 1.01, 1.2, 5.0];
+        let frame_times = [0.0, 1.04, 1.2];
+        assert_eq!(
+            match_frames_to_timestamps(&targets, &frame_times, 0.0),
+            vec![(0.0, 0), (1.01, 1), (1.2, 2)]
+        );
+
+        // One sparse frame can satisfy two targets, as two seeks would
+        assert_eq!(
+            match_frames_to_timestamps(&[1.0, 1.2], &[1.5], 0.0),
+            vec![(1.0, 0), (1.2, 0)]
+        );
+    }
+
+    #[test]
+    fn offsets_targets_by_the_container_start_time() {
+        // MPEG-TS style file whose first frame is at 1.4s: target 0 is that frame
+        let expr = frame_select_expr(&[0.0, 2.5], 1.4);
+        assert!(expr.starts_with("gte(t,1.400)*"));
+        assert!(expr.contains("gte(t,3.900)*"));
+        assert_eq!(
+            match_frames_to_timestamps(&[0.0, 2.5], &[1.4, 3.9], 1.4),
+            vec![(0.0, 0), (2.5, 1)]
+        );
+    }
+
+    #[test]
+    fn parses_showinfo_frame_lines_only() {
+        assert_eq!(
+            parse_showinfo_pts_time(
+                "[Parsed_showinfo_1 @ 0x7f] n:   3 pts: 153600 pts_time:12      duration:512"
+            ),
+            Some(12.0)
+        );
+        assert_eq!(
+            parse_showinfo_pts_time("[Parsed_showinfo_1 @ 0x7f] config in time_base: 1/12800"),
+            None
+        );
+        assert_eq!(parse_showinfo_pts_time("frame=   10 fps=0.0 q=2.0"), None);
+    }
+
+    #[tokio::test]
+    async fn scene_detect_samples_only_scene_changes() {
+        let scenes = [(2.5, 0.62), (2.9, 0.4), (9.0, 0.91)];
+        let runner = fake_runner_with_scenes(&[0.0, 2.5, 9.0], &scenes);
+
+        let strategy = SamplingStrategy::SceneDetect { threshold: 0.35 };
+        let frames = run_extract(runner.clone(), CLIP.to_string(), None, strategy)
+            .await
+            .unwrap();
+
+        // 2.9 is within a second of 2.5 and is dropped
+        let timestamps: Vec<f64> = frames.iter().map(|f| f.timestamp).collect();
+        assert_eq!(timestamps, vec![0.0, 2.5, 9.0]);
+        let scores: Vec<Option<f64>> = frames.iter().map(|f| f.scene_score).collect();
+        assert_eq!(scores, vec![None, Some(0.62), Some(0.91)]);
+        assert!(frames.iter().all(|f| f.strategy == strategy));
+
+        let scene_pass = &runner.calls_to("ffmpeg")[0];
+        assert!(scene_pass.arg_after("-vf").unwrap().contains("select='gt(scene,0.35)'"));
+        assert!(runner.calls_to("ffprobe").iter().all(|c| !c.has_arg("frame=pts_time,pict_type")));
+    }
+
+    #[tokio::test]
+    async fn rejects_out_of_range_scene_threshold() {
+        let runner = fake_runner(&[]);
+        let strategy = SamplingStrategy::Hybrid { threshold: 3.0 };
+        let result = run_extract(runner, CLIP.to_string(), None, strategy).await;
+        assert!(matches!(result, Err(LexiError::InvalidInput { .. })));
+    }
+
+    #[test]
+    fn hybrid_fills_gaps_between_scene_changes() {
+        // Boundaries as built for hybrid: 0 plus a single cut at 8s in a 12s clip
+        assert_eq!(
+            calculate_target_timestamps(&[0.0, 8.0], 12.0),
+            vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 11.5]
+        );
+    }
+
+    #[test]
+    fn uniform_sampling_covers_duration() {
+        assert_eq!(uniform_timestamps(5.0, 2.0), vec![0.0, 2.0, 4.0]);
+        assert_eq!(uniform_timestamps(1.0, 0.5), vec![0.0, 0.5]);
+
+        // No accumulated float error over a long source
+        let hour = uniform_timestamps(3600.0, 0.1);
+        assert_eq!(hour.len(), 36000);
+        assert_eq!(hour[35999], 35999.0 * 0.1);
+    }
+
+    #[test]
+    fn strategy_deserializes_with_defaults() {
+        let strategy: SamplingStrategy = serde_json::from_str(r#"{"mode":"sceneDetect"}"#).unwrap();
+        assert_eq!(strategy, SamplingStrategy::SceneDetect { threshold: 0.3 });
+
+        let strategy: SamplingStrategy =
+            serde_json::from_str(r#"{"mode":"uniform","interval":2.5}"#).unwrap();
+        assert_eq!(strategy, SamplingStrategy::Uniform { interval: 2.5 });
+    }
+
+    fn range(id: &str, start: f64, end: f64, max_frames: Option<u32>) -> FrameRange {
+        FrameRange {
+            id: id.to_string(),
+            start,
+            end,
+            max_frames,
+        }
+    }
+
+    #[tokio::test]
+    async fn samples_only_requested_ranges() {
+        let runner = fake_runner(&[2.0, 4.5, 8.0, 11.5]);
+        let ranges = vec![range("g1", 2.0, 5.0, Some(2)), range("g2", 8.0, 20.0, None)];
+
+        let frames = test_jobs(runner.clone())
+            .run_blocking(JobKind::ExtractFrames, "test", |job| {
+                let request = FrameRequest {
+                    ranges: Some(ranges),
+                    ..FrameRequest::default()
+                };
+                extract_frames(job, CLIP, request, None)
+            })
+            .await
+            .unwrap();
+
+        let tagged: Vec<(f64, Option<&str>)> = frames
+            .iter()
+            .map(|f| (f.timestamp, f.range_id.as_deref()))
+            .collect();
+        assert_eq!(
+            tagged,
+            vec![(2.0, Some("g1")), (4.5, Some("g1")), (8.0, Some("g2")), (11.5, Some("g2"))]
+        );
+        assert_eq!(runner.calls_to("ffmpeg").len(), 1);
+    }
+
+    #[test]
+    fn range_budgets_and_validation() {
+        let spans = frame_spans(
+            Some(vec![range("a", 0.0, 4.0, None), range("b", 4.0, 8.0, Some(10))]),
+            6.0,
+            9,
+        )
+        .unwrap();
+        assert_eq!(spans[0].budget, 4);
+        assert_eq!((spans[1].start, spans[1].end, spans[1].budget), (4.0, 6.0, 10));
+
+        assert!(frame_spans(Some(vec![range("late", 7.0, 9.0, None)]), 6.0, 9).is_err());
+        assert_eq!(frame_spans(None, 6.0, 9).unwrap()[0].budget, 9);
+    }
+
+    #[tokio::test]
+    async fn missing_ffprobe_is_reported() {
+        let runner = FakeRunner::new();
+        runner.on("ffprobe", &[], Canned::missing());
+
+        let result = run_extract(runner, CLIP.to_string(), None, SamplingStrategy::Keyframes).await;
+        assert!(matches!(result, Err(LexiError::FfmpegMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn extracts_real_jpegs() {
+        let dir = TempDir::new().unwrap();
+        let Some(clip) = make_test_clip(dir.path(), "clip.mp4", 3.0) else {
+            return;
+        };
+
+        let clip = clip.to_str().unwrap().to_string();
+        let frames = run_extract(Arc::new(SystemRunner), clip, None, SamplingStrategy::default())
+            .await
+            .unwrap();
+
+        assert!(!frames.is_empty());
+        assert_eq!(frames[0].timestamp, 0.0);
+        for frame in &frames {
+            let bytes = STANDARD.decode(&frame.data).unwrap();
+            assert_eq!(&bytes[..2], &[0xFF, 0xD8]);
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use crate::error::LexiError;
+use crate::services::{
+    probe_media_cached, video_dimensions, ArtifactStore, CacheDb, JobHandle, JobKind, JobManager,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use tauri::{AppHandle, Manager, State};
+
+const DEFAULT_TILE_COUNT: u32 = 20;
+const MAX_TILE_COUNT: u32 = 400;
+const DEFAULT_TILE_WIDTH: u32 = 160;
+const DEFAULT_COLUMNS: u32 = 10;
+
+/// Artifact kind and cache data type prefix for filmstrips
+const FILMSTRIP_KIND: &str = "filmstrips";
+const DATA_TYPE_FILMSTRIP: &str = "filmstrip";
+
+/// What to render; everything is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FilmstripOptions {
+    /// Number of thumbnails (default 20)
+    pub count: Option<u32>,
+    /// Range start in seconds (default 0)
+    pub start: Option<f64>,
+    /// Range end in seconds (default: end of the source)
+    pub end: Option<f64>,
+    /// Width of one thumbnail in pixels (default 160); height follows the aspect ratio
+    #[serde(rename = "tileWidth")]
+    pub tile_width: Option<u32>,
+    /// Thumbnails per sprite row (default 10)
+    pub columns: Option<u32>,
+}
+
+/// One thumbnail in the sprite sheet and the stretch of time it stands for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilmstripTile {
+    /// Start of the covered time in seconds
+    pub start: f64,
+    /// End of the covered time in seconds
+    pub end: f64,
+    /// Top-left corner of the tile in the sprite sheet, in pixels
+    pub x: u32,
+    pub y: u32,
+}
+
+/// A sprite sheet of evenly spaced thumbnails plus its thumbnail index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Filmstrip {
+    /// JPEG sprite sheet under the app data dir (load through the asset protocol)
+    #[serde(rename = "imagePath")]
+    pub image_path: String,
+    #[serde(rename = "tileWidth")]
+    pub tile_width: u32,
+    #[serde(rename = "tileHeight")]
+    pub tile_height: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub tiles: Vec<FilmstripTile>,
+    /// WebVTT thumbnail track (`sprite.jpg#xywh=x,y,w,h` cues) for the same tiles
+    pub vtt: String,
+}
+
+/// Render a sprite sheet of evenly spaced thumbnails for a source or a time range
+/// of it, with an index mapping time to tile coordinates. Cached by CID.
+#[tauri::command]
+pub async fn generate_filmstrip(
+    app: AppHandle,
+    path: String,
+    cid: String,
+    options: Option<FilmstripOptions>,
+    jobs: State<'_, JobManager>,
+) -> Result<Filmstrip, LexiError> {
+    let options = options.unwrap_or_default();
+    let label = path.clone();
+    jobs.run_blocking(JobKind::Filmstrip, label, move |job| {
+        let db = app.state::<CacheDb>();
+        let artifacts = app.state::<ArtifactStore>();
+        render_filmstrip(job, &path, &cid, &options, &db, &artifacts)
+    })
+    .await
+}
+
+fn render_filmstrip(
+    job: &JobHandle,
+    path: &str,
+    cid: &str,
+    options: &FilmstripOptions,
+    db: &CacheDb,
+    artifacts: &ArtifactStore,
+) -> Result<Filmstrip, LexiError> {
+    let info = probe_media_cached(job.runner(), Path::new(path), Some(cid), db)?;
+    let (display_width, display_height) = video_dimensions(&info)?;
+    if display_width == 0 || display_height == 0 {
+        return Err(LexiError::invalid_input(format!(
+            "No video dimensions reported for {}",
+            path
+        )));
+    }
+
+    let start = options.start.unwrap_or(0.0);
+    let end = options.end.unwrap_or(info.duration).min(info.duration);
+    if start < 0.0 || start >= end {
+        return Err(LexiError::invalid_input(format!(
+            "Invalid filmstrip range {:.3}-{:.3}s",
+            start, end
+        )));
+    }
+
+    let count = options.count.unwrap_or(DEFAULT_TILE_COUNT);
+    if count == 0 || count > MAX_TILE_COUNT {
+        return Err(LexiError::invalid_input(format!(
+            "Filmstrip tile count must be between 1 and {}",
+            MAX_TILE_COUNT
+        )));
+    }
+
+    let tile_width = options.tile_width.unwrap_or(DEFAULT_TILE_WIDTH).max(2) & !1;
+    let tile_height = ((tile_width as f64 * display_height as f64 / display_width as f64)
+        .round() as u32)
+        .max(2)
+        & !1;
+    let columns = options.columns.unwrap_or(DEFAULT_COLUMNS).clamp(1, count);
+    let rows = count.div_ceil(columns);
+
+    let key = format!(
+        "{}-{}-{}-{}x{}-c{}",
+        (start * 1000.0).round() as i64,
+        (end * 1000.0).round() as i64,
+        count,
+        tile_width,
+        tile_height,
+        columns
+    );
+    let data_type = format!("{}_{}", DATA_TYPE_FILMSTRIP, key);
+
+    if let Some(json) = db.get_cached(cid, &data_type)? {
+        if let Ok(filmstrip) = serde_json::from_str::<Filmstrip>(&json) {
+            if Path::new(&filmstrip.image_path).exists() {
+                return Ok(filmstrip);
+            }
+        }
+    }
+
+    let image_path = artifacts.path(FILMSTRIP_KIND, cid, &format!("{}.jpg", key))?;
+    let image_path_str = image_path.to_string_lossy().to_string();
+
+    // Sample the middle of each tile's interval
+    let interval = (end - start) / count as f64;
+    let filter = format!(
+        "fps={:.6},scale={}:{},tile={}x{}",
+        1.0 / interval,
+        tile_width,
+        tile_height,
+        columns,
+        rows
+    );
+
+    let output = job.output(Command::new("ffmpeg").args([
+        "-hide_banner",
+        "-y",
+        "-ss", &format!("{:.3}", start + interval / 2.0),
+        "-t", &format!("{:.3}", end - start - interval / 2.0),
+        "-i", path,
+        "-an",
+        "-vf", &filter,
+        "-frames:v", "1",
+        "-q:v", "4",
+        &image_path_str,
+    ]))?;
+
+    if !output.status.success() || !image_path.exists() {
+        return Err(LexiError::ffmpeg_failed(
+            "FFmpeg filmstrip rendering failed",
+            &output.stderr,
+        ));
+    }
+
+    let tiles = tile_layout(start, end, count, columns, (tile_width, tile_height));
+    let file_name = image_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let filmstrip = Filmstrip {
+        vtt: thumbnail_vtt(&file_name, &tiles, (tile_width, tile_height)),
+        image_path: image_path_str,
+        tile_width,
+        tile_height,
+        columns,
+        rows,
+        tiles,
+    };
+
+    let json = serde_json::to_string(&filmstrip)
+        .map_err(|e| LexiError::serialization("Failed to serialize filmstrip", e))?;
+    db.set_cached(cid, &data_type, &json)?;
+
+    Ok(filmstrip)
+}
+
+/// Split `start..end` into `count` equal tiles laid out row by row
+fn tile_layout(
+    start: f64,
+    end: f64,
+    count: u32,
+    columns: u32,
+    (tile_width, tile_height): (u32, u32),
+) -> Vec<FilmstripTile> {
+    let interval = (end - start) / count as f64;
+    (0..count)
+        .map(|i| FilmstripTile {
+            start: start + i as f64 * interval,
+            end: if i + 1 == count {
+                end
+            } else {
+                start + (i + 1) as f64 * interval
+            },
+            x: (i % columns) * tile_width,
+            y: (i / columns) * tile_height,
+        })
+        .collect()
+}
+
+/// WebVTT thumbnail track pointing each cue at its tile in the sprite sheet
+fn thumbnail_vtt(image: &str, tiles: &[FilmstripTile], (width, height): (u32, u32)) -> String {
+    let mut vtt = String::from("WEBVTT\n");
+    for tile in tiles {
+        vtt.push_str(&format!(
+            "\n{} --> {}\n{}#xywh={},{},{},{}\n",
+            vtt_timestamp(tile.start),
+            vtt_timestamp(tile.end),
+            image,
+            tile.x,
+            tile.y,
+            width,
+            height
+        ));
+    }
+    vtt
+}
+
+/// `HH:MM:SS.mmm`
+fn vtt_timestamp(seconds: f64) -> String {
+    let ms = (seconds * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{probe_json, test_jobs, Canned, FakeRunner};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn index_maps_time_to_tiles() {
+        let tiles = tile_layout(10.0, 16.0, 3, 2, (160, 90));
+        assert_eq!(
+            tiles,
+            vec![
+                FilmstripTile { start: 10.0, end: 12.0, x: 0, y: 0 },
+                FilmstripTile { start: 12.0, end: 14.0, x: 160, y: 0 },
+                FilmstripTile { start: 14.0, end: 16.0, x: 0, y: 90 },
+            ]
+        );
+
+        let vtt = thumbnail_vtt("sprite.jpg", &tiles[2..], (160, 90));
+        assert_eq!(vtt, "WEBVTT\n\n00:00:14.000 --> 00:00:16.000\nsprite.jpg#xywh=0,90,160,90\n");
+        assert_eq!(vtt_timestamp(3725.5), "01:02:05.500");
+    }
+
+    #[tokio::test]
+    async fn renders_sprite_once_then_serves_from_cache() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let runner = FakeRunner::new();
+            runner
+                .on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(12.0, 640, 360, 0)))
+                .on("ffmpeg", &["-vf"], Canned::ok().writing_output(b"sprite"));
+
+            let (db, artifacts) = (db.clone(), artifacts.clone());
+            let filmstrip = test_jobs(runner.clone())
+                .run_blocking(JobKind::Filmstrip, "test", move |job| {
+                    let options = FilmstripOptions {
+                        count: Some(12),
+                        columns: Some(5),
+                        ..FilmstripOptions::default()
+                    };
+                    render_filmstrip(job, "/media/clip.mp4", "cid1", &options, &db, &artifacts)
+                })
+                .await
+                .unwrap();
+            runs.push((filmstrip, runner.calls_to("ffmpeg")));
+        }
+
+        let (filmstrip, calls) = &runs[0];
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].arg_after("-vf"), Some("fps=1.000000,scale=160:90,tile=5x3"));
+        assert_eq!(calls[0].arg_after("-ss"), Some("0.500"));
+        assert_eq!((filmstrip.columns, filmstrip.rows, filmstrip.tiles.len()), (5, 3, 12));
+        assert_eq!(std::fs::read(&filmstrip.image_path).unwrap(), b"sprite");
+
+        let (cached, calls) = &runs[1];
+        assert!(calls.is_empty());
+        assert_eq!(cached.image_path, filmstrip.image_path);
+        assert_eq!(cached.vtt, filmstrip.vtt);
+    }
+
+    #[tokio::test]
+    async fn rejects_video_without_dimensions() {
+        let dir = TempDir::new().unwrap();
+        let db = CacheDb::init(dir.path().to_path_buf()).unwrap();
+        let artifacts = ArtifactStore::new(dir.path().to_path_buf());
+        let runner = FakeRunner::new();
+        runner.on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(12.0, 0, 0, 0)));
+
+        let result = test_jobs(runner.clone())
+            .run_blocking(JobKind::Filmstrip, "test", move |job| {
+                let options = FilmstripOptions::default();
+                render_filmstrip(job, "/media/clip.mp4", "cid1", &options, &db, &artifacts)
+            })
+            .await;
+        assert!(matches!(result, Err(LexiError::InvalidInput { .. })));
+        assert!(runner.calls_to("ffmpeg").is_empty());
+    }
+}
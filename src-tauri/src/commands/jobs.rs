@@ -0,0 +1,22 @@
+use crate::error::LexiError;
+use crate::services::{JobInfo, JobManager};
+use tauri::State;
+
+/// List running jobs and recently finished ones
+#[tauri::command]
+pub fn list_jobs(jobs: State<'_, JobManager>) -> Vec<JobInfo> {
+    jobs.list()
+}
+
+/// Cancel a queued or running job, killing any ffmpeg process it started
+#[tauri::command]
+pub fn cancel_job(job_id: String, jobs: State<'_, JobManager>) -> Result<JobInfo, LexiError> {
+    jobs.cancel(&job_id)
+}
+
+/// Set how many ffmpeg/ffprobe processes may run at once across all jobs
+#[tauri::command]
+pub fn set_max_concurrent_ffmpeg(limit: usize, jobs: State<'_, JobManager>) -> usize {
+    jobs.set_max_concurrent_ffmpeg(limit);
+    jobs.max_concurrent_ffmpeg()
+}
@@ -1,6 +1,10 @@
+use crate::error::LexiError;
+use crate::services::{get_video_duration, CacheDb, JobHandle, JobKind, JobManager};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::Path;
 use std::process::Command;
+use tauri::{AppHandle, Manager, State};
 
 // Max file size before compression (500MB - Late supports up to 5GB via presigned URLs)
 const MAX_FILE_SIZE: u64 = 500 * 1024 * 1024; // 500MB
@@ -28,9 +32,14 @@ struct LateErrorResponse {
 }
 
 /// Compress video using FFmpeg if it exceeds size limit
-fn compress_video_if_needed(file_path: &str) -> Result<String, String> {
+fn compress_video_if_needed(
+    job: &JobHandle,
+    file_path: &str,
+    cid: Option<&str>,
+    cache_db: &CacheDb,
+) -> Result<String, LexiError> {
     let metadata = fs::metadata(file_path)
-        .map_err(|e| format!("Failed to get file metadata: {}", e))?;
+        .map_err(|e| LexiError::io("Failed to get file metadata", file_path, e))?;
 
     let file_size = metadata.len();
 
@@ -41,18 +50,8 @@ fn compress_video_if_needed(file_path: &str) -> Result<String, String> {
 
     // Calculate target bitrate to achieve ~35MB output (conservative to stay under 50MB limit)
     // Get video duration first
-    let duration_output = Command::new("ffprobe")
-        .args([
-            "-v", "error",
-            "-show_entries", "format=duration",
-            "-of", "default=noprint_wrappers=1:nokey=1",
-            file_path,
-        ])
-        .output()
-        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
-
-    let duration_str = String::from_utf8_lossy(&duration_output.stdout);
-    let duration: f64 = duration_str.trim().parse().unwrap_or(60.0);
+    let duration =
+        get_video_duration(job.runner(), Path::new(file_path), cid, cache_db).unwrap_or(60.0);
 
     // Target ~35MB with 0.8 safety factor (FFmpeg often overshoots)
     // 35MB * 0.8 = 28MB effective target
@@ -65,46 +64,63 @@ fn compress_video_if_needed(file_path: &str) -> Result<String, String> {
     // Create compressed output path
     let compressed_path = file_path.replace(".mp4", "_compressed.mp4");
 
-    let status = Command::new("ffmpeg")
-        .args([
-            "-y",
-            "-i", file_path,
-            "-c:v", "libx264",
-            "-preset", "fast",
-            "-b:v", &format!("{}k", video_bitrate),
-            "-maxrate", &format!("{}k", video_bitrate * 2),
-            "-bufsize", &format!("{}k", video_bitrate * 4),
-            "-c:a", "aac",
-            "-b:a", &format!("{}k", audio_bitrate),
-            "-movflags", "+faststart",
-            &compressed_path,
-        ])
-        .output()
-        .map_err(|e| format!("Failed to run ffmpeg compression: {}", e))?;
+    job.stage("Compressing");
+    let status = job.output(Command::new("ffmpeg").args([
+        "-y",
+        "-i", file_path,
+        "-c:v", "libx264",
+        "-preset", "fast",
+        "-b:v", &format!("{}k", video_bitrate),
+        "-maxrate", &format!("{}k", video_bitrate * 2),
+        "-bufsize", &format!("{}k", video_bitrate * 4),
+        "-c:a", "aac",
+        "-b:a", &format!("{}k", audio_bitrate),
+        "-movflags", "+faststart",
+        &compressed_path,
+    ]))?;
 
     if !status.status.success() {
-        let stderr = String::from_utf8_lossy(&status.stderr);
-        return Err(format!("FFmpeg compression failed: {}", stderr));
+        return Err(LexiError::ffmpeg_failed("FFmpeg compression failed", &status.stderr));
     }
 
     Ok(compressed_path)
 }
 
 /// Upload a video file to Late API using presigned URLs
-/// This avoids the serverless function payload limit by uploading directly to storage
+/// This avoids the serverless function payload limit by uploading directly to storage.
+/// A CID, when the file has one, lets its probe come from the cache.
 #[tauri::command]
-pub async fn upload_to_late(file_path: String, api_key: String) -> Result<MediaUploadResult, String> {
+pub async fn upload_to_late(
+    app: AppHandle,
+    file_path: String,
+    api_key: String,
+    cid: Option<String>,
+    jobs: State<'_, JobManager>,
+) -> Result<MediaUploadResult, LexiError> {
+    let label = file_path.clone();
+    jobs.run_async(JobKind::Upload, label, |job| upload(job, app, file_path, api_key, cid))
+        .await
+}
+
+async fn upload(
+    job: JobHandle,
+    app: AppHandle,
+    file_path: String,
+    api_key: String,
+    cid: Option<String>,
+) -> Result<MediaUploadResult, LexiError> {
     // Compress video if needed (run in blocking task since it's CPU-intensive)
     let path_for_compress = file_path.clone();
+    let compress_job = job.clone();
     let upload_path = tokio::task::spawn_blocking(move || {
-        compress_video_if_needed(&path_for_compress)
+        let cache_db = app.state::<CacheDb>();
+        compress_video_if_needed(&compress_job, &path_for_compress, cid.as_deref(), &cache_db)
     })
-    .await
-    .map_err(|e| format!("Compression task failed: {}", e))??;
+    .await??;
 
     // Read the file
     let file_bytes = fs::read(&upload_path)
-        .map_err(|e| format!("Failed to read file '{}': {}", upload_path, e))?;
+        .map_err(|e| LexiError::io("Failed to read file", &upload_path, e))?;
 
     let file_size = file_bytes.len() as u64;
 
@@ -117,6 +133,7 @@ pub async fn upload_to_late(file_path: String, api_key: String) -> Result<MediaU
     let client = reqwest::Client::new();
 
     // Step 1: Get presigned URL from Late API
+    job.stage("Uploading");
     let presign_body = serde_json::json!({
         "filename": file_name,
         "contentType": "video/mp4"
@@ -129,25 +146,33 @@ pub async fn upload_to_late(file_path: String, api_key: String) -> Result<MediaU
         .body(presign_body.to_string())
         .send()
         .await
-        .map_err(|e| format!("Failed to get presigned URL: {}", e))?;
+        .map_err(|e| LexiError::http("Failed to get presigned URL", e))?;
 
     let presign_status = presign_response.status();
     let presign_body_text = presign_response
         .text()
         .await
-        .map_err(|e| format!("Failed to read presign response: {}", e))?;
+        .map_err(|e| LexiError::http("Failed to read presign response", e))?;
 
     if !presign_status.is_success() {
-        if let Ok(parsed) = serde_json::from_str::<LateErrorResponse>(&presign_body_text) {
-            if let Some(msg) = parsed.message.or(parsed.error) {
-                return Err(msg);
-            }
-        }
-        return Err(format!("Failed to get presigned URL ({}): {}", presign_status, presign_body_text));
+        let message = serde_json::from_str::<LateErrorResponse>(&presign_body_text)
+            .ok()
+            .and_then(|parsed| parsed.message.or(parsed.error))
+            .unwrap_or(presign_body_text);
+        return Err(LexiError::http_status(
+            "Failed to get presigned URL",
+            presign_status.as_u16(),
+            message,
+        ));
     }
 
     let presign_data: PresignResponse = serde_json::from_str(&presign_body_text)
-        .map_err(|e| format!("Failed to parse presign response: {} - Body: {}", e, presign_body_text))?;
+        .map_err(|e| {
+            LexiError::serialization(
+                "Failed to parse presign response",
+                format!("{} - Body: {}", e, presign_body_text),
+            )
+        })?;
 
     // Step 2: Upload file directly to storage using presigned URL
     let upload_response = client
@@ -156,12 +181,17 @@ pub async fn upload_to_late(file_path: String, api_key: String) -> Result<MediaU
         .body(file_bytes)
         .send()
         .await
-        .map_err(|e| format!("Failed to upload to storage: {}", e))?;
+        .map_err(|e| LexiError::http("Failed to upload to storage", e))?;
 
     let upload_status = upload_response.status();
     if !upload_status.is_success() {
         let error_body = upload_response.text().await.unwrap_or_default();
-        return Err(format!("Storage upload failed ({}): {}", upload_status, error_body));
+        // A 403 here is an expired presigned URL, not a rejected API key
+        return Err(LexiError::Http {
+            context: "Storage upload failed".to_string(),
+            status: Some(upload_status.as_u16()),
+            message: error_body,
+        });
     }
 
     // Return the public URL that can be used in posts
@@ -171,3 +201,78 @@ pub async fn upload_to_late(file_path: String, api_key: String) -> Result<MediaU
         size: file_size,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{probe_json, test_jobs, Canned, FakeRunner};
+    use tempfile::TempDir;
+
+    async fn run_compress(
+        runner: std::sync::Arc<FakeRunner>,
+        path: String,
+    ) -> Result<String, LexiError> {
+        let dir = TempDir::new().unwrap();
+        let db = CacheDb::init(dir.path().to_path_buf()).unwrap();
+        test_jobs(runner)
+            .run_blocking(JobKind::Upload, "test", move |job| {
+                compress_video_if_needed(job, &path, Some("cid"), &db)
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn small_file_is_uploaded_as_is() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("small.mp4");
+        fs::write(&path, b"tiny").unwrap();
+        let path = path.to_str().unwrap().to_string();
+
+        let runner = FakeRunner::new();
+        let result = run_compress(runner.clone(), path.clone()).await.unwrap();
+
+        assert_eq!(result, path);
+        assert!(runner.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn large_file_is_compressed_to_duration_bitrate() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("big.mp4");
+        fs::File::create(&path)
+            .unwrap()
+            .set_len(MAX_FILE_SIZE + 1)
+            .unwrap();
+        let path = path.to_str().unwrap().to_string();
+
+        let runner = FakeRunner::new();
+        runner.on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(100.0, 1920, 1080, 0)));
+
+        let result = run_compress(runner.clone(), path.clone()).await.unwrap();
+        assert_eq!(result, path.replace("big.mp4", "big_compressed.mp4"));
+
+        // 28MB over 100s is 2293kbps, minus 96kbps for audio
+        let calls = runner.calls_to("ffmpeg");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].arg_after("-i"), Some(path.as_str()));
+        assert_eq!(calls[0].arg_after("-b:v"), Some("2197k"));
+        assert_eq!(calls[0].arg_after("-maxrate"), Some("4394k"));
+        assert_eq!(calls[0].last_arg(), Some(result.as_str()));
+    }
+
+    #[tokio::test]
+    async fn failed_compression_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("big.mp4");
+        fs::File::create(&path)
+            .unwrap()
+            .set_len(MAX_FILE_SIZE + 1)
+            .unwrap();
+
+        let runner = FakeRunner::new();
+        runner.on("ffmpeg", &[], Canned::failure(1, "Conversion failed!\n"));
+
+        let result = run_compress(runner, path.to_str().unwrap().to_string()).await;
+        assert!(matches!(result, Err(LexiError::FfmpegFailed { .. })));
+    }
+}
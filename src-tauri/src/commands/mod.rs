@@ -1,19 +1,41 @@
 mod cache;
 mod cid;
+mod disfluency;
 mod export;
+mod extract_audio;
 mod extract_clip;
 mod extract_frames;
+mod filmstrip;
+mod jobs;
 mod late_upload;
+mod multicam;
+mod probe;
 mod projects;
+mod proxy;
 mod read_file;
+mod silence;
+mod speakers;
 mod thumbnail;
+mod transcribe;
+mod waveform;
 
 pub use cache::{get_cached, set_cached};
-pub use cid::generate_cid;
+pub use cid::{cid_is_sampled, generate_cid, migrate_cid, register_media_source, verify_cid};
+pub use disfluency::detect_disfluencies;
 pub use export::export_video;
+pub use extract_audio::extract_audio;
 pub use extract_clip::extract_clip_base64;
 pub use extract_frames::extract_frames_base64;
+pub use filmstrip::generate_filmstrip;
+pub use jobs::{cancel_job, list_jobs, set_max_concurrent_ffmpeg};
 pub use late_upload::upload_to_late;
+pub use multicam::sync_sources;
+pub use probe::{get_dimensions, get_duration, probe_media};
 pub use projects::{load_project_data, load_projects, save_project_data, save_projects};
+pub use proxy::generate_proxy;
 pub use read_file::read_file_base64;
-pub use thumbnail::{generate_thumbnail, get_dimensions, get_duration};
+pub use silence::detect_silence;
+pub use speakers::{filter_entries_by_speaker, merge_speakers, rename_speaker};
+pub use thumbnail::generate_thumbnail;
+pub use transcribe::transcribe_source;
+pub use waveform::compute_waveform;
@@ -0,0 +1,244 @@
+use super::projects::{SourceOffset, SyncGroup};
+use crate::error::LexiError;
+use crate::services::{
+    align_envelopes, onset_envelope, probe_media_cached, ArtifactStore, CacheDb, JobHandle,
+    JobKind, JobManager, SYNC_SAMPLE_RATE,
+};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tauri::{AppHandle, Manager, State};
+
+const ENVELOPE_KIND: &str = "envelopes";
+const ENVELOPE_FILE: &str = "onsets.f32";
+
+/// A source to sync; the CID keys its cached envelope
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyncSource {
+    #[serde(rename = "sourceId")]
+    pub source_id: String,
+    pub path: String,
+    pub cid: String,
+}
+
+/// Line up sources recorded at the same time by cross-correlating their audio.
+/// Returns each source's offset from the reference (default: the first source)
+/// with a confidence score; the frontend stores the group in `ProjectData`.
+#[tauri::command]
+pub async fn sync_sources(
+    app: AppHandle,
+    sources: Vec<SyncSource>,
+    reference_source_id: Option<String>,
+    max_offset: Option<f64>,
+    jobs: State<'_, JobManager>,
+) -> Result<SyncGroup, LexiError> {
+    let label = format!("{} sources", sources.len());
+    jobs.run_blocking(JobKind::Sync, label, move |job| {
+        let db = app.state::<CacheDb>();
+        let artifacts = app.state::<ArtifactStore>();
+        sync_group(job, &sources, reference_source_id.as_deref(), max_offset, &db, &artifacts)
+    })
+    .await
+}
+
+fn sync_group(
+    job: &JobHandle,
+    sources: &[SyncSource],
+    reference_source_id: Option<&str>,
+    max_offset: Option<f64>,
+    db: &CacheDb,
+    artifacts: &ArtifactStore,
+) -> Result<SyncGroup, LexiError> {
+    if sources.len() < 2 {
+        return Err(LexiError::invalid_input("Syncing needs at least two sources"));
+    }
+    let reference = match reference_source_id {
+        Some(id) => sources.iter().position(|s| s.source_id == id).ok_or_else(|| {
+            LexiError::invalid_input(format!("Reference source {} is not being synced", id))
+        })?,
+        None => 0,
+    };
+
+    let mut envelopes = Vec::with_capacity(sources.len());
+    for (i, source) in sources.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(
+            i as f64 / sources.len() as f64 * 100.0,
+            format!("Analyzing audio {}/{}", i + 1, sources.len()),
+        );
+        envelopes.push(load_or_decode_envelope(job, source, db, artifacts)?);
+    }
+
+    let offsets = sources
+        .iter()
+        .zip(&envelopes)
+        .enumerate()
+        .map(|(i, (source, envelope))| {
+            let (offset, confidence) = if i == reference {
+                (0.0, 1.0)
+            } else {
+                // No audio to compare: leave unsynced and let the user place it
+                align_envelopes(&envelopes[reference], envelope, max_offset)
+                    .map(|m| (m.offset, m.confidence))
+                    .unwrap_or((0.0, 0.0))
+            };
+            println!(
+                "[multicam] {} offset {:.3}s (confidence {:.2})",
+                source.source_id, offset, confidence
+            );
+            SourceOffset {
+                source_id: source.source_id.clone(),
+                offset,
+                confidence,
+            }
+        })
+        .collect();
+
+    let reference_source_id = sources[reference].source_id.clone();
+    Ok(SyncGroup {
+        id: format!("sync-{}", reference_source_id),
+        reference_source_id,
+        offsets,
+    })
+}
+
+/// Onset envelope of a source's audio, cached by CID as little-endian f32. Empty
+/// when the source has no audio stream, which leaves it unsynced.
+fn load_or_decode_envelope(
+    job: &JobHandle,
+    source: &SyncSource,
+    db: &CacheDb,
+    artifacts: &ArtifactStore,
+) -> Result<Vec<f32>, LexiError> {
+    let file = artifacts.path(ENVELOPE_KIND, &source.cid, ENVELOPE_FILE)?;
+    if let Ok(bytes) = fs::read(&file) {
+        println!("[multicam] Cache hit for {}", source.cid);
+        return Ok(bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect());
+    }
+
+    let info = probe_media_cached(job.runner(), Path::new(&source.path), Some(&source.cid), db)?;
+    if info.audio.is_none() {
+        println!("[multicam] {} has no audio to sync by", source.source_id);
+        return Ok(Vec::new());
+    }
+
+    let rate = SYNC_SAMPLE_RATE.to_string();
+    let output = job.output(Command::new("ffmpeg").args([
+        "-hide_banner",
+        "-i", &source.path,
+        "-vn",
+        "-ac", "1",
+        "-ar", &rate,
+        "-f", "s16le",
+        "-acodec", "pcm_s16le",
+        "pipe:1",
+    ]))?;
+
+    if !output.status.success() {
+        return Err(LexiError::ffmpeg_failed("FFmpeg audio decoding failed", &output.stderr));
+    }
+
+    let envelope = onset_envelope(&output.stdout);
+    let bytes: Vec<u8> = envelope.iter().flat_map(|v| v.to_le_bytes()).collect();
+    fs::write(&file, bytes).map_err(|e| LexiError::io("Failed to write envelope", &file, e))?;
+    Ok(envelope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{bursty_pcm, probe_json, s16le_bytes, test_jobs, Canned, FakeRunner};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn source(id: &str) -> SyncSource {
+        SyncSource {
+            source_id: id.to_string(),
+            path: format!("/media/{}.mp4", id),
+            cid: format!("cid-{}", id),
+        }
+    }
+
+    #[tokio::test]
+    async fn offsets_angles_against_the_recorder_and_caches_envelopes() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+
+        // The recorder runs throughout; camera A starts 3s in, camera B 12.5s in
+        let rate = SYNC_SAMPLE_RATE as usize;
+        let recorder = bursty_pcm(60.0, SYNC_SAMPLE_RATE, 3);
+        let cam_a: Vec<i16> = recorder[3 * rate..].iter().map(|s| s / 2).collect();
+        let cam_b: Vec<i16> = recorder[rate * 25 / 2..rate * 50].to_vec();
+        let sources = vec![source("cam-a"), source("recorder"), source("cam-b")];
+
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let runner = FakeRunner::new();
+            runner.on("ffprobe", &[], Canned::stdout(&probe_json(60.0, 1920, 1080, 0)));
+            for (id, pcm) in [("recorder", &recorder), ("cam-a", &cam_a), ("cam-b", &cam_b)] {
+                let path = format!("/media/{}.mp4", id);
+                runner.on("ffmpeg", &[&path], Canned::stdout_bytes(&s16le_bytes(pcm)));
+            }
+
+            let (db, artifacts, sources) = (db.clone(), artifacts.clone(), sources.clone());
+            let group = test_jobs(runner.clone())
+                .run_blocking(JobKind::Sync, "test", move |job| {
+                    sync_group(job, &sources, Some("recorder"), None, &db, &artifacts)
+                })
+                .await
+                .unwrap();
+            runs.push((group, runner.calls_to("ffmpeg").len()));
+        }
+
+        let (group, calls) = &runs[0];
+        assert_eq!(*calls, 3);
+        assert_eq!(group.id, "sync-recorder");
+        let offsets: Vec<(&str, f64)> = group
+            .offsets
+            .iter()
+            .map(|o| (o.source_id.as_str(), o.offset))
+            .collect();
+        assert_eq!(offsets[1], ("recorder", 0.0));
+        assert!((offsets[0].1 - 3.0).abs() < 0.02, "{:?}", offsets);
+        assert!((offsets[2].1 - 12.5).abs() < 0.02, "{:?}", offsets);
+        assert!(group.offsets.iter().all(|o| o.confidence > 0.5));
+
+        let (cached, calls) = &runs[1];
+        assert_eq!(*calls, 0);
+        assert_eq!(cached, group);
+    }
+
+    #[tokio::test]
+    async fn leaves_a_source_without_audio_unsynced() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+
+        let mut silent: serde_json::Value =
+            serde_json::from_str(&probe_json(60.0, 1920, 1080, 0)).unwrap();
+        silent["streams"].as_array_mut().unwrap().truncate(1);
+        let recorder = s16le_bytes(&bursty_pcm(60.0, SYNC_SAMPLE_RATE, 3));
+        let runner = FakeRunner::new();
+        runner
+            .on("ffprobe", &["/media/broll.mp4"], Canned::stdout(&silent.to_string()))
+            .on("ffprobe", &[], Canned::stdout(&probe_json(60.0, 1920, 1080, 0)))
+            .on("ffmpeg", &["/media/recorder.mp4"], Canned::stdout_bytes(&recorder));
+
+        let sources = vec![source("recorder"), source("broll")];
+        let group = test_jobs(runner.clone())
+            .run_blocking(JobKind::Sync, "test", move |job| {
+                sync_group(job, &sources, None, None, &db, &artifacts)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(runner.calls_to("ffmpeg").len(), 1);
+        assert_eq!(group.offsets[1].source_id, "broll");
+        assert_eq!((group.offsets[1].offset, group.offsets[1].confidence), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,52 @@
+use crate::error::LexiError;
+use crate::services::{probe_media_cached, video_dimensions, CacheDb, MediaInfo, SystemRunner};
+use serde::Serialize;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+#[derive(Serialize)]
+pub struct VideoDimensions {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Probe a media file for container, video and audio stream metadata.
+/// Results are cached by CID when one is provided.
+#[tauri::command]
+pub async fn probe_media(
+    app: AppHandle,
+    video_path: String,
+    cid: Option<String>,
+) -> Result<MediaInfo, LexiError> {
+    tokio::task::spawn_blocking(move || {
+        let cache_db = app.state::<CacheDb>();
+        probe_media_cached(
+            &SystemRunner,
+            Path::new(&video_path),
+            cid.as_deref(),
+            &cache_db,
+        )
+    })
+    .await?
+}
+
+#[tauri::command]
+pub async fn get_duration(
+    app: AppHandle,
+    video_path: String,
+    cid: Option<String>,
+) -> Result<f64, LexiError> {
+    let info = probe_media(app, video_path, cid).await?;
+    Ok(info.duration)
+}
+
+#[tauri::command]
+pub async fn get_dimensions(
+    app: AppHandle,
+    video_path: String,
+    cid: Option<String>,
+) -> Result<VideoDimensions, LexiError> {
+    let info = probe_media(app, video_path, cid).await?;
+    let (width, height) = video_dimensions(&info)?;
+    Ok(VideoDimensions { width, height })
+}
@@ -1,4 +1,6 @@
+use crate::error::LexiError;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tauri::AppHandle;
 use tauri::Manager;
 
@@ -35,6 +37,9 @@ pub struct Source {
     pub duration: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub descriptions: Option<Vec<SourceDescription>>,
+    /// Playback proxy name from `generate_proxy`; export always uses `path`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +51,9 @@ pub struct Word {
     pub confidence: f64,
     #[serde(rename = "sourceId")]
     pub source_id: String,
+    /// Diarized speaker, an id into `ProjectData::speakers`
+    #[serde(rename = "speakerId", default, skip_serializing_if = "Option::is_none")]
+    pub speaker_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +71,9 @@ pub struct Sentence {
     pub end_time: f64,
     #[serde(rename = "originalGroupId", skip_serializing_if = "Option::is_none")]
     pub original_group_id: Option<String>,
+    /// Speaker of most of the sentence's words
+    #[serde(rename = "speakerId", default, skip_serializing_if = "Option::is_none")]
+    pub speaker_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,6 +115,10 @@ pub struct TimelineEntry {
     pub excluded_word_ids: Vec<String>,
     #[serde(rename = "videoOverride", skip_serializing_if = "Option::is_none")]
     pub video_override: Option<VideoOverride>,
+    /// Multicam angle to show while keeping the group's primary audio; wins over
+    /// auto-switching, loses to a video override
+    #[serde(rename = "angleSourceId", default, skip_serializing_if = "Option::is_none")]
+    pub angle_source_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -112,6 +127,71 @@ pub struct Timeline {
     pub entries: Vec<TimelineEntry>,
 }
 
+// --- Speaker Types ---
+
+/// A diarized voice; transcription labels become ids, the user names them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Speaker {
+    pub id: String,
+    pub name: String,
+    /// CSS color used for the speaker's label and timeline tint
+    pub color: String,
+}
+
+// --- Multicam Types ---
+
+/// Where one source sits on its sync group's reference clock
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceOffset {
+    #[serde(rename = "sourceId")]
+    pub source_id: String,
+    /// Seconds to add to this source's time to get reference time
+    pub offset: f64,
+    /// 0-1; low values mean the audio did not clearly line up
+    pub confidence: f64,
+}
+
+/// Sources recorded at the same time (cameras, audio recorder), aligned by audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncGroup {
+    pub id: String,
+    #[serde(rename = "referenceSourceId")]
+    pub reference_source_id: String,
+    /// Includes the reference itself at offset 0
+    pub offsets: Vec<SourceOffset>,
+}
+
+/// How angles are picked for entries without an explicit angle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum AutoSwitch {
+    /// Show the angle assigned to whoever is speaking
+    Speaker {
+        /// Speaker id to angle source id
+        angles: HashMap<String, String>,
+    },
+    /// Move to the next angle at the first sentence boundary after `seconds`
+    Cadence { seconds: f64 },
+}
+
+/// Synced sources edited as one: any angle's picture over the primary audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AngleGroup {
+    pub id: String,
+    pub name: String,
+    /// The sync group providing the offsets between angles
+    #[serde(rename = "syncGroupId")]
+    pub sync_group_id: String,
+    /// Source whose audio is kept whichever angle is shown
+    #[serde(rename = "audioSourceId")]
+    pub audio_source_id: String,
+    /// Sources with usable picture, in switching order
+    #[serde(rename = "angleSourceIds")]
+    pub angle_source_ids: Vec<String>,
+    #[serde(rename = "autoSwitch", default, skip_serializing_if = "Option::is_none")]
+    pub auto_switch: Option<AutoSwitch>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectData {
     pub id: String,
@@ -133,74 +213,87 @@ pub struct ProjectData {
     pub excluded_word_ids: Option<Vec<String>>,
     #[serde(rename = "transcriptlessSourceIds")]
     pub transcriptless_source_ids: Vec<String>,
+    #[serde(rename = "syncGroups", default, skip_serializing_if = "Vec::is_empty")]
+    pub sync_groups: Vec<SyncGroup>,
+    #[serde(rename = "angleGroups", default, skip_serializing_if = "Vec::is_empty")]
+    pub angle_groups: Vec<AngleGroup>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub speakers: Vec<Speaker>,
     #[serde(rename = "savedAt")]
     pub saved_at: u64,
 }
 
-fn lexi_cut_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+fn lexi_cut_dir(app: &AppHandle) -> Result<std::path::PathBuf, LexiError> {
     let documents = app
         .path()
         .document_dir()
-        .map_err(|e| format!("Failed to get documents dir: {}", e))?;
+        .map_err(|e| LexiError::internal(format!("Failed to get documents dir: {}", e)))?;
     let dir = documents.join("Lexi Cut");
-    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create Lexi Cut dir: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| LexiError::io("Failed to create Lexi Cut dir", &dir, e))?;
     Ok(dir)
 }
 
-fn projects_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+fn projects_path(app: &AppHandle) -> Result<std::path::PathBuf, LexiError> {
     let dir = lexi_cut_dir(app)?;
     Ok(dir.join("projects.json"))
 }
 
 #[tauri::command]
-pub fn load_projects(app: AppHandle) -> Result<Vec<ProjectMeta>, String> {
+pub fn load_projects(app: AppHandle) -> Result<Vec<ProjectMeta>, LexiError> {
     let path = projects_path(&app)?;
     if !path.exists() {
         return Ok(vec![]);
     }
-    let contents =
-        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read projects: {}", e))?;
-    let projects: Vec<ProjectMeta> =
-        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse projects: {}", e))?;
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| LexiError::io("Failed to read projects", &path, e))?;
+    let projects: Vec<ProjectMeta> = serde_json::from_str(&contents)
+        .map_err(|e| LexiError::serialization("Failed to parse projects", e))?;
     Ok(projects)
 }
 
 #[tauri::command]
-pub fn save_projects(app: AppHandle, projects: Vec<ProjectMeta>) -> Result<(), String> {
+pub fn save_projects(app: AppHandle, projects: Vec<ProjectMeta>) -> Result<(), LexiError> {
     let path = projects_path(&app)?;
     let json = serde_json::to_string_pretty(&projects)
-        .map_err(|e| format!("Failed to serialize projects: {}", e))?;
-    std::fs::write(&path, json).map_err(|e| format!("Failed to write projects: {}", e))?;
+        .map_err(|e| LexiError::serialization("Failed to serialize projects", e))?;
+    std::fs::write(&path, json)
+        .map_err(|e| LexiError::io("Failed to write projects", &path, e))?;
     Ok(())
 }
 
-fn project_dir(app: &AppHandle, project_id: &str) -> Result<std::path::PathBuf, String> {
+fn project_dir(app: &AppHandle, project_id: &str) -> Result<std::path::PathBuf, LexiError> {
     let base = lexi_cut_dir(app)?;
     let dir = base.join("projects").join(project_id);
-    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create project dir: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| LexiError::io("Failed to create project dir", &dir, e))?;
     Ok(dir)
 }
 
 #[tauri::command]
-pub fn save_project_data(app: AppHandle, data: ProjectData) -> Result<(), String> {
+pub fn save_project_data(app: AppHandle, data: ProjectData) -> Result<(), LexiError> {
     let dir = project_dir(&app, &data.id)?;
     let path = dir.join("project.json");
     let json = serde_json::to_string_pretty(&data)
-        .map_err(|e| format!("Failed to serialize project data: {}", e))?;
-    std::fs::write(&path, json).map_err(|e| format!("Failed to write project data: {}", e))?;
+        .map_err(|e| LexiError::serialization("Failed to serialize project data", e))?;
+    std::fs::write(&path, json)
+        .map_err(|e| LexiError::io("Failed to write project data", &path, e))?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn load_project_data(app: AppHandle, project_id: String) -> Result<Option<ProjectData>, String> {
+pub fn load_project_data(
+    app: AppHandle,
+    project_id: String,
+) -> Result<Option<ProjectData>, LexiError> {
     let base = lexi_cut_dir(&app)?;
     let path = base.join("projects").join(&project_id).join("project.json");
     if !path.exists() {
         return Ok(None);
     }
-    let contents =
-        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read project data: {}", e))?;
-    let data: ProjectData =
-        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse project data: {}", e))?;
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| LexiError::io("Failed to read project data", &path, e))?;
+    let data: ProjectData = serde_json::from_str(&contents)
+        .map_err(|e| LexiError::serialization("Failed to parse project data", e))?;
     Ok(Some(data))
 }
@@ -0,0 +1,268 @@
+use super::export::parse_ffmpeg_progress;
+use crate::error::LexiError;
+use crate::services::{
+    probe_media_cached, ArtifactStore, CacheDb, JobHandle, JobKind, JobManager,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tauri::{AppHandle, Manager, State};
+
+/// Artifact kind for proxies; also the `lexi://media/<cid>/proxies/<name>` path
+const PROXY_KIND: &str = "proxies";
+
+/// Tall enough to judge framing in the editor, small enough to decode instantly
+const DEFAULT_PROXY_HEIGHT: u32 = 540;
+
+/// Keyframe interval of short-GOP proxies; seeking never decodes more than this
+const SHORT_GOP_FRAMES: &str = "12";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProxyOptions {
+    /// Output height in pixels (default 540); sources are never upscaled
+    #[serde(rename = "maxHeight")]
+    pub max_height: Option<u32>,
+    /// Make every frame a keyframe: larger files, instant frame-accurate seeks
+    #[serde(rename = "allIntra", default)]
+    pub all_intra: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Proxy {
+    /// Name under the source's proxies, for `lexi://media/<cid>/proxies/<name>`
+    pub name: String,
+    /// File under the app data dir
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Transcode a source to a small H.264 proxy for smooth playback in the editor.
+/// Proxies are keyed by CID and options, so every project using the source
+/// shares them. Returns `None` for sources without video. Export never uses
+/// proxies; it always renders from the original `Source.path`.
+#[tauri::command]
+pub async fn generate_proxy(
+    app: AppHandle,
+    path: String,
+    cid: String,
+    options: Option<ProxyOptions>,
+    jobs: State<'_, JobManager>,
+) -> Result<Option<Proxy>, LexiError> {
+    let options = options.unwrap_or_default();
+    let label = path.clone();
+    jobs.run_blocking(JobKind::Proxy, label, move |job| {
+        let db = app.state::<CacheDb>();
+        let artifacts = app.state::<ArtifactStore>();
+        render_proxy(job, &path, &cid, &options, &db, &artifacts)
+    })
+    .await
+}
+
+fn render_proxy(
+    job: &JobHandle,
+    path: &str,
+    cid: &str,
+    options: &ProxyOptions,
+    db: &CacheDb,
+    artifacts: &ArtifactStore,
+) -> Result<Option<Proxy>, LexiError> {
+    let info = probe_media_cached(job.runner(), Path::new(path), Some(cid), db)?;
+    let Some(video) = &info.video else {
+        println!("[proxy] No video stream in {}, skipping", path);
+        return Ok(None);
+    };
+    let (width, height) = proxy_size(
+        (video.display_width, video.display_height),
+        options.max_height.unwrap_or(DEFAULT_PROXY_HEIGHT),
+    );
+
+    let gop = if options.all_intra { "intra" } else { "gop" };
+    let name = format!("{}p_{}.mp4", height, gop);
+    let file = artifacts.path(PROXY_KIND, cid, &name)?;
+
+    if file.exists() {
+        println!("[proxy] Cache hit for {}", cid);
+    } else {
+        encode_proxy(job, path, &file, (width, height), options.all_intra, info.duration)?;
+    }
+
+    Ok(Some(Proxy {
+        name,
+        path: file.to_string_lossy().to_string(),
+        width,
+        height,
+    }))
+}
+
+/// Even output dimensions with the source's display aspect, no taller than `max_height`
+fn proxy_size((width, height): (u32, u32), max_height: u32) -> (u32, u32) {
+    let out_height = height.min(max_height).max(2) & !1;
+    let out_width =
+        ((width as f64 * out_height as f64 / height.max(1) as f64).round() as u32).max(2) & !1;
+    (out_width, out_height)
+}
+
+/// Encode to a `.part` file and rename it into place once ffmpeg succeeded, so
+/// a cancelled or failed run never leaves a truncated proxy to be reused
+fn encode_proxy(
+    job: &JobHandle,
+    path: &str,
+    file: &Path,
+    (width, height): (u32, u32),
+    all_intra: bool,
+    duration: f64,
+) -> Result<(), LexiError> {
+    let partial = file.with_extension("part");
+    let partial_str = partial.to_string_lossy().to_string();
+    let scale = format!("scale={}:{}", width, height);
+    let gop = if all_intra { "1" } else { SHORT_GOP_FRAMES };
+
+    // ffmpeg applies the rotation while decoding (as in export), so the tag is cleared
+    let mut cmd = Command::new("ffmpeg");
+    cmd.args([
+        "-hide_banner",
+        "-y",
+        "-progress", "pipe:2",
+        "-i", path,
+        "-map", "0:v:0",
+        "-map", "0:a:0?",
+        "-vf", &scale,
+        "-c:v", "libx264",
+        "-preset", "veryfast",
+        "-crf", "23",
+        "-g", gop,
+        "-keyint_min", gop,
+        "-sc_threshold", "0",
+        "-pix_fmt", "yuv420p",
+        "-c:a", "aac",
+        "-b:a", "128k",
+        "-ac", "2",
+        "-metadata:s:v:0", "rotate=0",
+        "-movflags", "+faststart",
+        "-f", "mp4",
+        &partial_str,
+    ]);
+    let child = job.spawn(cmd.stderr(Stdio::piped()))?;
+
+    // Read stderr for progress, keeping the non-progress lines for error reporting
+    let mut stderr_log = String::new();
+    if let Some(stderr) = child.take_stderr() {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if !line.contains('=') {
+                stderr_log.push_str(&line);
+                stderr_log.push('\n');
+            }
+            if let Some((time, _)) = parse_ffmpeg_progress(&line) {
+                if duration > 0.0 {
+                    job.progress(time / duration * 100.0, "Creating proxy");
+                }
+            }
+        }
+    }
+    let status = child.wait()?;
+
+    if !status.success() || !partial.exists() {
+        let _ = fs::remove_file(&partial);
+        job.check_cancelled()?;
+        return Err(LexiError::ffmpeg_failed("FFmpeg proxy encoding failed", stderr_log.as_bytes()));
+    }
+
+    println!("[proxy] Wrote {}x{} proxy for {}", width, height, path);
+    fs::rename(&partial, file).map_err(|e| LexiError::io("Failed to store proxy", file, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{probe_json, test_jobs, Canned, FakeRunner};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn scales_to_max_height_keeping_display_aspect() {
+        assert_eq!(proxy_size((3840, 2160), 540), (960, 540));
+        assert_eq!(proxy_size((1080, 1920), 540), (304, 540));
+        assert_eq!(proxy_size((640, 360), 540), (640, 360));
+        assert_eq!(proxy_size((1440, 1080), 720), (960, 720));
+    }
+
+    #[tokio::test]
+    async fn encodes_once_and_reuses_the_proxy() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let runner = FakeRunner::new();
+            runner
+                .on("ffprobe", &[], Canned::stdout(&probe_json(8.0, 3840, 2160, 0)))
+                .on(
+                    "ffmpeg",
+                    &["libx264"],
+                    Canned::ok()
+                        .writing_output(b"mp4")
+                        .with_stderr("frame=96 fps=48 q=23.0 size=1kB time=00:00:04.00 bitrate=1k speed=2x\n"),
+                );
+
+            let (db, artifacts) = (db.clone(), artifacts.clone());
+            let proxy = test_jobs(runner.clone())
+                .run_blocking(JobKind::Proxy, "test", move |job| {
+                    render_proxy(
+                        job,
+                        "/media/a.mov",
+                        "cid1",
+                        &ProxyOptions::default(),
+                        &db,
+                        &artifacts,
+                    )
+                })
+                .await
+                .unwrap()
+                .unwrap();
+            runs.push((proxy, runner.calls()));
+        }
+
+        let (proxy, calls) = &runs[0];
+        let ffmpeg: Vec<_> = calls.iter().filter(|c| c.program == "ffmpeg").collect();
+        assert_eq!(ffmpeg.len(), 1);
+        assert_eq!(ffmpeg[0].arg_after("-vf"), Some("scale=960:540"));
+        assert_eq!(ffmpeg[0].arg_after("-g"), Some("12"));
+        assert_eq!((proxy.width, proxy.height), (960, 540));
+        assert_eq!(proxy.name, "540p_gop.mp4");
+        assert!(proxy.path.ends_with("cid1_540p_gop.mp4"));
+        assert!(Path::new(&proxy.path).exists());
+        assert!(!Path::new(&proxy.path).with_extension("part").exists());
+
+        let (cached, calls) = &runs[1];
+        assert!(calls.is_empty());
+        assert_eq!(cached, proxy);
+    }
+
+    #[tokio::test]
+    async fn skips_audio_only_sources() {
+        let dir = TempDir::new().unwrap();
+        let db = CacheDb::init(dir.path().to_path_buf()).unwrap();
+        let artifacts = ArtifactStore::new(dir.path().to_path_buf());
+
+        let probe = r#"{
+            "streams": [{"index": 0, "codec_type": "audio", "codec_name": "aac", "sample_rate": "48000", "channels": 2}],
+            "format": {"format_name": "mp3", "duration": "30.0"}
+        }"#;
+        let runner = FakeRunner::new();
+        runner.on("ffprobe", &[], Canned::stdout(probe));
+
+        let proxy = test_jobs(runner.clone())
+            .run_blocking(JobKind::Proxy, "test", move |job| {
+                let options = ProxyOptions::default();
+                render_proxy(job, "/media/voice.mp3", "cid2", &options, &db, &artifacts)
+            })
+            .await
+            .unwrap();
+        assert!(proxy.is_none());
+        assert!(runner.calls_to("ffmpeg").is_empty());
+    }
+}
@@ -0,0 +1,361 @@
+use super::projects::{TimelineEntry, Word};
+use crate::error::LexiError;
+use crate::services::{probe_media_cached, CacheDb, JobHandle, JobKind, JobManager};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use tauri::{AppHandle, Manager, State};
+
+const DEFAULT_NOISE_DB: f64 = -35.0;
+const DEFAULT_MIN_SILENCE: f64 = 0.5;
+const DEFAULT_MIN_PAUSE_MS: u64 = 600;
+
+/// Audio kept either side of a cut so word onsets and tails are not clipped
+const PAUSE_PADDING: f64 = 0.1;
+
+/// Share of a word inside silence above which the word is likely a transcription
+/// artifact (breath, noise) rather than speech
+const SILENT_WORD_RATIO: f64 = 0.8;
+
+const DATA_TYPE_SILENCE: &str = "silence";
+
+/// silencedetect settings
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SilenceOptions {
+    /// Anything quieter than this counts as silence, in dB (default -35)
+    #[serde(rename = "noiseDb")]
+    pub noise_db: Option<f64>,
+    /// Shortest silence reported, in seconds (default 0.5)
+    #[serde(rename = "minDuration")]
+    pub min_duration: Option<f64>,
+}
+
+/// A timeline entry and its sentence's words, for proposing pause removal
+#[derive(Debug, Clone, Deserialize)]
+pub struct PauseRequest {
+    pub entry: TimelineEntry,
+    pub words: Vec<Word>,
+    /// Pauses shorter than this are left alone (default 600)
+    #[serde(rename = "minPauseMs")]
+    pub min_pause_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SilenceRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+/// A stretch of dead air between two kept words that can be cut
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PauseCut {
+    pub start: f64,
+    pub end: f64,
+    #[serde(rename = "afterWordId")]
+    pub after_word_id: String,
+    #[serde(rename = "beforeWordId")]
+    pub before_word_id: String,
+}
+
+/// Suggested edits for one timeline entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PauseProposal {
+    #[serde(rename = "sentenceId")]
+    pub sentence_id: String,
+    /// Words that sit almost entirely in silence, to add to `excludedWordIds`
+    #[serde(rename = "excludedWordIds")]
+    pub excluded_word_ids: Vec<String>,
+    /// Source-time ranges to cut
+    pub cuts: Vec<PauseCut>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SilenceReport {
+    pub silences: Vec<SilenceRange>,
+    /// Only when a `PauseRequest` was given
+    pub proposal: Option<PauseProposal>,
+}
+
+/// Find silent ranges in a source with ffmpeg's silencedetect, cached by CID.
+/// With `pauses`, also propose words to exclude and ranges to cut in that entry
+/// wherever the gap between words is longer than `minPauseMs`.
+#[tauri::command]
+pub async fn detect_silence(
+    app: AppHandle,
+    path: String,
+    cid: String,
+    options: Option<SilenceOptions>,
+    pauses: Option<PauseRequest>,
+    jobs: State<'_, JobManager>,
+) -> Result<SilenceReport, LexiError> {
+    let options = options.unwrap_or_default();
+    let label = path.clone();
+    jobs.run_blocking(JobKind::Silence, label, move |job| {
+        let db = app.state::<CacheDb>();
+        let silences = silence_ranges(job, &path, &cid, &options, &db)?;
+        let proposal = pauses.map(|request| propose_pause_cuts(&request, &silences));
+        Ok(SilenceReport { silences, proposal })
+    })
+    .await
+}
+
+fn silence_ranges(
+    job: &JobHandle,
+    path: &str,
+    cid: &str,
+    options: &SilenceOptions,
+    db: &CacheDb,
+) -> Result<Vec<SilenceRange>, LexiError> {
+    let noise_db = options.noise_db.unwrap_or(DEFAULT_NOISE_DB);
+    let min_duration = options.min_duration.unwrap_or(DEFAULT_MIN_SILENCE);
+    if noise_db >= 0.0 || min_duration <= 0.0 {
+        return Err(LexiError::invalid_input(
+            "Silence noise floor must be below 0 dB and minimum duration above 0",
+        ));
+    }
+
+    let data_type = format!("{}_{}_{}", DATA_TYPE_SILENCE, noise_db, min_duration);
+    if let Some(json) = db.get_cached(cid, &data_type)? {
+        if let Ok(silences) = serde_json::from_str(&json) {
+            return Ok(silences);
+        }
+    }
+
+    let duration = probe_media_cached(job.runner(), Path::new(path), Some(cid), db)?.duration;
+    let filter = format!("silencedetect=noise={}dB:d={}", noise_db, min_duration);
+    let output = job.output(Command::new("ffmpeg").args([
+        "-hide_banner",
+        "-i", path,
+        "-vn",
+        "-af", &filter,
+        "-f", "null",
+        "-",
+    ]))?;
+
+    if !output.status.success() {
+        return Err(LexiError::ffmpeg_failed(
+            "FFmpeg silence detection failed",
+            &output.stderr,
+        ));
+    }
+
+    let silences = parse_silencedetect(&String::from_utf8_lossy(&output.stderr), duration);
+    println!(
+        "[silence] Found {} silent ranges in {}",
+        silences.len(),
+        path
+    );
+
+    let json = serde_json::to_string(&silences)
+        .map_err(|e| LexiError::serialization("Failed to serialize silence ranges", e))?;
+    db.set_cached(cid, &data_type, &json)?;
+    Ok(silences)
+}
+
+/// Pair `silence_start` / `silence_end` lines; a silence still open at the end of
+/// the file runs to `duration`
+fn parse_silencedetect(stderr: &str, duration: f64) -> Vec<SilenceRange> {
+    let value_after = |line: &str, key: &str| -> Option<f64> {
+        let rest = &line[line.find(key)? + key.len()..];
+        rest.split_whitespace().next()?.parse().ok()
+    };
+
+    let mut silences = Vec::new();
+    let mut open: Option<f64> = None;
+    for line in stderr.lines().filter(|l| l.contains("silencedetect")) {
+        if let Some(start) = value_after(line, "silence_start:") {
+            open = Some(start.max(0.0));
+        } else if let Some(end) = value_after(line, "silence_end:") {
+            if let Some(start) = open.take() {
+                silences.push(SilenceRange { start, end });
+            }
+        }
+    }
+    if let Some(start) = open {
+        if duration > start {
+            silences.push(SilenceRange {
+                start,
+                end: duration,
+            });
+        }
+    }
+    silences
+}
+
+/// Seconds of `start..end` covered by silence
+fn silent_overlap(silences: &[SilenceRange], start: f64, end: f64) -> f64 {
+    silences
+        .iter()
+        .map(|s| (s.end.min(end) - s.start.max(start)).max(0.0))
+        .sum()
+}
+
+fn propose_pause_cuts(request: &PauseRequest, silences: &[SilenceRange]) -> PauseProposal {
+    let entry = &request.entry;
+    let min_pause = request.min_pause_ms.unwrap_or(DEFAULT_MIN_PAUSE_MS) as f64 / 1000.0;
+
+    let mut kept: Vec<&Word> = request
+        .words
+        .iter()
+        .filter(|w| !entry.excluded_word_ids.contains(&w.id))
+        .collect();
+    kept.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
+
+    let excluded_word_ids: Vec<String> = kept
+        .iter()
+        .filter(|w| {
+            let length = w.end - w.start;
+            length > 0.0 && silent_overlap(silences, w.start, w.end) / length >= SILENT_WORD_RATIO
+        })
+        .map(|w| w.id.clone())
+        .collect();
+    let kept: Vec<&Word> = kept
+        .into_iter()
+        .filter(|w| !excluded_word_ids.contains(&w.id))
+        .collect();
+
+    let mut cuts = Vec::new();
+    for pair in kept.windows(2) {
+        let (before, after) = (pair[0], pair[1]);
+        if after.start - before.end < min_pause {
+            continue;
+        }
+
+        // Cut only what is actually silent, keeping a little air around the words
+        for silence in silences {
+            let start = silence.start.max(before.end) + PAUSE_PADDING;
+            let end = silence.end.min(after.start) - PAUSE_PADDING;
+            if end - start >= min_pause - 2.0 * PAUSE_PADDING && end > start {
+                cuts.push(PauseCut {
+                    start,
+                    end,
+                    after_word_id: before.id.clone(),
+                    before_word_id: after.id.clone(),
+                });
+            }
+        }
+    }
+
+    PauseProposal {
+        sentence_id: entry.sentence_id.clone(),
+        excluded_word_ids,
+        cuts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{probe_json, test_jobs, Canned, FakeRunner};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    const SILENCEDETECT_LOG: &str = "\
+[silencedetect @ 0x6000] silence_start: -0.002
+[silencedetect @ 0x6000] silence_end: 0.8 | silence_duration: 0.802
+size=N/A time=00:00:05.00 bitrate=N/A speed= 500x
+[silencedetect @ 0x6000] silence_start: 2.1
+[silencedetect @ 0x6000] silence_end: 3.4 | silence_duration: 1.3
+[silencedetect @ 0x6000] silence_start: 9.5
+";
+
+    fn word(id: &str, start: f64, end: f64) -> Word {
+        Word {
+            id: id.to_string(),
+            word: id.to_string(),
+            start,
+            end,
+            confidence: 0.9,
+            source_id: "src1".to_string(),
+            speaker_id: None,
+        }
+    }
+
+    #[test]
+    fn parses_silence_ranges_with_open_ending() {
+        assert_eq!(
+            parse_silencedetect(SILENCEDETECT_LOG, 10.0),
+            vec![
+                SilenceRange { start: 0.0, end: 0.8 },
+                SilenceRange { start: 2.1, end: 3.4 },
+                SilenceRange { start: 9.5, end: 10.0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn proposes_cuts_for_long_pauses_and_silent_words() {
+        let silences = vec![
+            SilenceRange { start: 2.1, end: 3.4 },
+            SilenceRange { start: 5.0, end: 5.3 },
+            SilenceRange { start: 7.0, end: 8.0 },
+        ];
+        let request = PauseRequest {
+            entry: TimelineEntry {
+                sentence_id: "s1".to_string(),
+                text: String::new(),
+                source_id: "src1".to_string(),
+                excluded: false,
+                excluded_word_ids: vec!["w5".to_string()],
+                video_override: None,
+                angle_source_id: None,
+            },
+            words: vec![
+                word("w1", 1.0, 2.0),
+                // 1.5s pause, mostly silent
+                word("w2", 3.5, 4.9),
+                // 0.4s pause: too short
+                word("w3", 5.3, 6.0),
+                // "uh" inside silence
+                word("w4", 7.1, 7.3),
+                word("w5", 8.0, 8.5),
+                word("w6", 8.6, 9.0),
+            ],
+            min_pause_ms: Some(700),
+        };
+
+        let proposal = propose_pause_cuts(&request, &silences);
+        assert_eq!(proposal.excluded_word_ids, vec!["w4"]);
+
+        // w4 dropped and w5 already excluded: w3 -> w6 spans the 7-8s silence too
+        let cuts: Vec<(f64, f64, &str, &str)> = proposal
+            .cuts
+            .iter()
+            .map(|c| (c.start, c.end, c.after_word_id.as_str(), c.before_word_id.as_str()))
+            .collect();
+        assert_eq!(cuts.len(), 2);
+        assert_eq!((cuts[0].2, cuts[0].3), ("w1", "w2"));
+        assert!((cuts[0].0 - 2.2).abs() < 1e-9 && (cuts[0].1 - 3.3).abs() < 1e-9);
+        assert_eq!((cuts[1].2, cuts[1].3), ("w3", "w6"));
+        assert!((cuts[1].0 - 7.1).abs() < 1e-9 && (cuts[1].1 - 7.9).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn detects_once_per_cid_and_options() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+
+        let mut calls = Vec::new();
+        for _ in 0..2 {
+            let runner = FakeRunner::new();
+            runner
+                .on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(10.0, 640, 360, 0)))
+                .on("ffmpeg", &["-af"], Canned::ok().with_stderr(SILENCEDETECT_LOG));
+
+            let db = db.clone();
+            let silences = test_jobs(runner.clone())
+                .run_blocking(JobKind::Silence, "test", move |job| {
+                    let options = SilenceOptions::default();
+                    silence_ranges(job, "/media/clip.mp4", "cid1", &options, &db)
+                })
+                .await
+                .unwrap();
+            assert_eq!(silences.len(), 3);
+            calls.push(runner.calls_to("ffmpeg"));
+        }
+
+        assert_eq!(calls[0].len(), 1);
+        assert_eq!(calls[0][0].arg_after("-af"), Some("silencedetect=noise=-35dB:d=0.5"));
+        assert!(calls[1].is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+use super::projects::{Sentence, Speaker, TimelineEntry, Word};
+use crate::error::LexiError;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Words and sentences after a merge, with the merged-away speakers removed
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeakerMerge {
+    pub speakers: Vec<Speaker>,
+    pub words: Vec<Word>,
+    pub sentences: Vec<Sentence>,
+}
+
+/// Give a speaker a display name and, optionally, a new color
+#[tauri::command]
+pub fn rename_speaker(
+    mut speakers: Vec<Speaker>,
+    speaker_id: String,
+    name: String,
+    color: Option<String>,
+) -> Result<Vec<Speaker>, LexiError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(LexiError::invalid_input("Speaker name cannot be empty"));
+    }
+    let speaker = speakers
+        .iter_mut()
+        .find(|s| s.id == speaker_id)
+        .ok_or_else(|| LexiError::invalid_input(format!("Unknown speaker {}", speaker_id)))?;
+
+    speaker.name = name.to_string();
+    if let Some(color) = color {
+        speaker.color = color;
+    }
+    Ok(speakers)
+}
+
+/// Relabel every word and sentence of `from_ids` as `into_id`, for when
+/// diarization split one voice into several speakers
+#[tauri::command]
+pub fn merge_speakers(
+    speakers: Vec<Speaker>,
+    mut words: Vec<Word>,
+    mut sentences: Vec<Sentence>,
+    from_ids: Vec<String>,
+    into_id: String,
+) -> Result<SpeakerMerge, LexiError> {
+    if !speakers.iter().any(|s| s.id == into_id) {
+        return Err(LexiError::invalid_input(format!("Unknown speaker {}", into_id)));
+    }
+    let from: HashSet<&str> =
+        from_ids.iter().map(String::as_str).filter(|id| *id != into_id).collect();
+
+    let relabel = |speaker_id: &mut Option<String>| {
+        if speaker_id.as_deref().is_some_and(|id| from.contains(id)) {
+            *speaker_id = Some(into_id.clone());
+        }
+    };
+    words.iter_mut().for_each(|w| relabel(&mut w.speaker_id));
+    sentences.iter_mut().for_each(|s| relabel(&mut s.speaker_id));
+
+    println!("[speakers] Merged {} speaker(s) into {}", from.len(), into_id);
+    Ok(SpeakerMerge {
+        speakers: speakers.into_iter().filter(|s| !from.contains(s.id.as_str())).collect(),
+        words,
+        sentences,
+    })
+}
+
+/// Timeline entries whose sentence is spoken by one of `speaker_ids`, in
+/// timeline order. Sentences without a speaker never match.
+#[tauri::command]
+pub fn filter_entries_by_speaker(
+    entries: Vec<TimelineEntry>,
+    sentences: Vec<Sentence>,
+    speaker_ids: Vec<String>,
+) -> Vec<TimelineEntry> {
+    let matching: HashSet<&str> = sentences
+        .iter()
+        .filter(|s| s.speaker_id.as_ref().is_some_and(|id| speaker_ids.contains(id)))
+        .map(|s| s.sentence_id.as_str())
+        .collect();
+
+    entries
+        .into_iter()
+        .filter(|e| matching.contains(e.sentence_id.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speaker(id: &str, name: &str) -> Speaker {
+        Speaker {
+            id: id.to_string(),
+            name: name.to_string(),
+            color: "#888888".to_string(),
+        }
+    }
+
+    fn sentence(id: &str, speaker_id: Option<&str>) -> Sentence {
+        Sentence {
+            sentence_id: id.to_string(),
+            source_id: "src1".to_string(),
+            word_ids: vec![format!("{}-w", id)],
+            text: String::new(),
+            start_time: 0.0,
+            end_time: 1.0,
+            original_group_id: None,
+            speaker_id: speaker_id.map(String::from),
+        }
+    }
+
+    fn entry(sentence_id: &str) -> TimelineEntry {
+        TimelineEntry {
+            sentence_id: sentence_id.to_string(),
+            text: String::new(),
+            source_id: "src1".to_string(),
+            excluded: false,
+            excluded_word_ids: Vec::new(),
+            video_override: None,
+            angle_source_id: None,
+        }
+    }
+
+    #[test]
+    fn renames_and_rejects_unknown_or_blank() {
+        let speakers = vec![speaker("speaker_0", "Speaker 1"), speaker("speaker_1", "Speaker 2")];
+
+        let renamed =
+            rename_speaker(speakers.clone(), "speaker_1".into(), " Host ".into(), None).unwrap();
+        assert_eq!(renamed[1].name, "Host");
+        assert_eq!(renamed[1].color, "#888888");
+
+        assert!(rename_speaker(speakers.clone(), "speaker_9".into(), "X".into(), None).is_err());
+        assert!(rename_speaker(speakers, "speaker_0".into(), "  ".into(), None).is_err());
+    }
+
+    #[test]
+    fn merges_speakers_and_filters_entries() {
+        let speakers = vec![
+            speaker("speaker_0", "Host"),
+            speaker("speaker_1", "Guest"),
+            speaker("speaker_2", "Guest (echo)"),
+        ];
+        let words = vec![Word {
+            id: "s3-w".to_string(),
+            word: "yes".to_string(),
+            start: 0.0,
+            end: 0.5,
+            confidence: 1.0,
+            source_id: "src1".to_string(),
+            speaker_id: Some("speaker_2".to_string()),
+        }];
+        let sentences = vec![
+            sentence("s1", Some("speaker_0")),
+            sentence("s2", Some("speaker_1")),
+            sentence("s3", Some("speaker_2")),
+            sentence("s4", None),
+        ];
+
+        let merged = merge_speakers(
+            speakers,
+            words,
+            sentences,
+            vec!["speaker_2".into()],
+            "speaker_1".into(),
+        )
+        .unwrap();
+        let ids: Vec<&str> = merged.speakers.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["speaker_0", "speaker_1"]);
+        assert_eq!(merged.words[0].speaker_id.as_deref(), Some("speaker_1"));
+        assert_eq!(merged.sentences[2].speaker_id.as_deref(), Some("speaker_1"));
+
+        let entries = vec![entry("s4"), entry("s3"), entry("s1"), entry("s2")];
+        let guest = filter_entries_by_speaker(entries, merged.sentences, vec!["speaker_1".into()]);
+        let order: Vec<&str> = guest.iter().map(|e| e.sentence_id.as_str()).collect();
+        assert_eq!(order, ["s3", "s2"]);
+    }
+}
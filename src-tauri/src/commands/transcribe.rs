@@ -0,0 +1,311 @@
+use super::extract_audio::{extract_audio_chunks, AudioFormat, AudioOptions, ExtractedAudio};
+use crate::error::LexiError;
+use crate::services::{
+    parse_legacy_transcript, transcript_data_type, ArtifactStore, AudioUpload, CacheDb,
+    ElevenLabsProvider, JobHandle, JobKind, JobManager, OpenAiCompatibleProvider, Transcript,
+    TranscriptWord, TranscriptionProvider, DEFAULT_ELEVENLABS_MODEL,
+    LEGACY_DATA_TYPE_TRANSCRIPTION,
+};
+use serde::Deserialize;
+use std::fs;
+use tauri::{AppHandle, Manager, State};
+
+/// Which service to transcribe with. API keys never cross IPC; they come from the
+/// `ELEVENLABS_API_KEY` and `OPENAI_API_KEY` environment variables of the app process.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum TranscriptionConfig {
+    Elevenlabs {
+        model: Option<String>,
+    },
+    /// OpenAI or any compatible server such as a local whisper.cpp
+    Openai {
+        /// e.g. `http://127.0.0.1:8080/v1` (default: OpenAI)
+        #[serde(rename = "baseUrl")]
+        base_url: Option<String>,
+        model: Option<String>,
+    },
+}
+
+impl Default for TranscriptionConfig {
+    fn default() -> Self {
+        TranscriptionConfig::Elevenlabs { model: None }
+    }
+}
+
+/// Transcribe a source's audio into a normalized word list. Only the extracted
+/// audio is uploaded, chunked to the provider's limits. Cached by CID, provider
+/// and model.
+#[tauri::command]
+pub async fn transcribe_source(
+    app: AppHandle,
+    path: String,
+    cid: String,
+    config: Option<TranscriptionConfig>,
+    jobs: State<'_, JobManager>,
+) -> Result<Transcript, LexiError> {
+    let config = config.unwrap_or_default();
+    let label = path.clone();
+    jobs.run_async(JobKind::Transcribe, label, |job| async move {
+        match config {
+            TranscriptionConfig::Elevenlabs { model } => {
+                let api_key = std::env::var("ELEVENLABS_API_KEY")
+                    .map_err(|_| LexiError::invalid_input("Missing ELEVENLABS_API_KEY"))?;
+                let provider = ElevenLabsProvider::new(api_key, model);
+                transcribe_cached(&job, &app, &provider, path, cid).await
+            }
+            TranscriptionConfig::Openai { base_url, model } => {
+                // Optional; local servers usually need none
+                let api_key = std::env::var("OPENAI_API_KEY").ok();
+                let provider = OpenAiCompatibleProvider::new(base_url, api_key, model);
+                transcribe_cached(&job, &app, &provider, path, cid).await
+            }
+        }
+    })
+    .await
+}
+
+async fn transcribe_cached<P: TranscriptionProvider>(
+    job: &JobHandle,
+    app: &AppHandle,
+    provider: &P,
+    path: String,
+    cid: String,
+) -> Result<Transcript, LexiError> {
+    let data_type = transcript_data_type(provider.name(), provider.model(), provider.endpoint());
+    if let Some(transcript) =
+        cached_transcript(&app.state::<CacheDb>(), provider, &cid, &data_type)?
+    {
+        println!("[transcribe] Cache hit for {} ({})", cid, data_type);
+        return Ok(transcript);
+    }
+
+    let options = AudioOptions {
+        format: AudioFormat::Flac,
+        chunk_seconds: provider.max_chunk_seconds(),
+    };
+    let (audio_job, audio_app, audio_cid) = (job.clone(), app.clone(), cid.clone());
+    let audio = tokio::task::spawn_blocking(move || {
+        let db = audio_app.state::<CacheDb>();
+        let artifacts = audio_app.state::<ArtifactStore>();
+        extract_audio_chunks(&audio_job, &path, &audio_cid, &options, &db, &artifacts)
+    })
+    .await??;
+
+    let transcript = transcribe_chunks(job, provider, &audio).await?;
+    println!(
+        "[transcribe] {} words from {} ({}) for {}",
+        transcript.words.len(),
+        provider.name(),
+        provider.model(),
+        cid
+    );
+
+    let json = serde_json::to_string(&transcript)
+        .map_err(|e| LexiError::serialization("Failed to serialize transcript", e))?;
+    app.state::<CacheDb>().set_cached(&cid, &data_type, &json)?;
+    Ok(transcript)
+}
+
+/// The cached transcript, or one cached by older versions under
+/// `LEGACY_DATA_TYPE_TRANSCRIPTION`, normalized and re-stored under `data_type` so
+/// already transcribed sources are not sent again
+fn cached_transcript<P: TranscriptionProvider>(
+    db: &CacheDb,
+    provider: &P,
+    cid: &str,
+    data_type: &str,
+) -> Result<Option<Transcript>, LexiError> {
+    if let Some(json) = db.get_cached(cid, data_type)? {
+        if let Ok(transcript) = serde_json::from_str::<Transcript>(&json) {
+            return Ok(Some(transcript));
+        }
+    }
+
+    if provider.name() != "elevenlabs" || provider.model() != DEFAULT_ELEVENLABS_MODEL {
+        return Ok(None);
+    }
+    let Some(json) = db.get_cached(cid, LEGACY_DATA_TYPE_TRANSCRIPTION)? else {
+        return Ok(None);
+    };
+    let Ok(transcript) = parse_legacy_transcript(&json) else {
+        return Ok(None);
+    };
+
+    let json = serde_json::to_string(&transcript)
+        .map_err(|e| LexiError::serialization("Failed to serialize transcript", e))?;
+    db.set_cached(cid, data_type, &json)?;
+    println!("[transcribe] Migrated legacy transcript for {}", cid);
+    Ok(Some(transcript))
+}
+
+/// Send each chunk in turn and shift its word times to source time
+async fn transcribe_chunks<P: TranscriptionProvider>(
+    job: &JobHandle,
+    provider: &P,
+    audio: &ExtractedAudio,
+) -> Result<Transcript, LexiError> {
+    let mut merged = Transcript {
+        provider: provider.name().to_string(),
+        model: provider.model().to_string(),
+        language: None,
+        words: Vec::new(),
+    };
+
+    for (i, chunk) in audio.chunks.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(
+            i as f64 / audio.chunks.len() as f64 * 100.0,
+            format!("Transcribing part {}/{}", i + 1, audio.chunks.len()),
+        );
+
+        let bytes = fs::read(&chunk.path)
+            .map_err(|e| LexiError::io("Failed to read audio chunk", &chunk.path, e))?;
+        let file_name = chunk.path.rsplit(['/', '\\']).next().unwrap_or("audio").to_string();
+        let part = provider
+            .transcribe(AudioUpload {
+                bytes,
+                file_name,
+                mime_type: audio.mime_type.clone(),
+            })
+            .await?;
+
+        merged.language = merged.language.or(part.language);
+        merged.words.extend(part.words.into_iter().map(|mut word: TranscriptWord| {
+            word.start += chunk.start;
+            word.end += chunk.start;
+            word
+        }));
+    }
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::extract_audio::AudioChunk;
+    use crate::test_support::{test_jobs, FakeRunner};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    /// Returns one word per upload, named after the uploaded bytes
+    #[derive(Default)]
+    struct EchoProvider {
+        uploads: AtomicUsize,
+    }
+
+    impl TranscriptionProvider for EchoProvider {
+        fn name(&self) -> &'static str {
+            "echo"
+        }
+
+        fn model(&self) -> &str {
+            "test"
+        }
+
+        async fn transcribe(&self, audio: AudioUpload) -> Result<Transcript, LexiError> {
+            self.uploads.fetch_add(1, Ordering::SeqCst);
+            Ok(Transcript {
+                provider: "echo".to_string(),
+                model: "test".to_string(),
+                language: Some("en".to_string()),
+                words: vec![TranscriptWord {
+                    text: String::from_utf8(audio.bytes).unwrap(),
+                    start: 1.0,
+                    end: 1.5,
+                    confidence: 0.9,
+                    speaker: None,
+                }],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn offsets_chunk_words_to_source_time() {
+        let dir = TempDir::new().unwrap();
+        let chunks = ["first", "second"]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let path = dir.path().join(format!("cid_c600000_{:03}.flac", i));
+                fs::write(&path, text).unwrap();
+                AudioChunk {
+                    path: path.to_string_lossy().to_string(),
+                    start: i as f64 * 600.0,
+                    end: (i + 1) as f64 * 600.0,
+                    byte_size: text.len() as u64,
+                }
+            })
+            .collect();
+        let audio = ExtractedAudio {
+            format: AudioFormat::Flac,
+            mime_type: "audio/flac".to_string(),
+            duration: 1200.0,
+            chunks,
+        };
+
+        let provider = Arc::new(EchoProvider::default());
+        let echo = provider.clone();
+        let transcript = test_jobs(FakeRunner::new())
+            .run_async(JobKind::Transcribe, "test", |job| async move {
+                transcribe_chunks(&job, echo.as_ref(), &audio).await
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(provider.uploads.load(Ordering::SeqCst), 2);
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        let words: Vec<(&str, f64)> =
+            transcript.words.iter().map(|w| (w.text.as_str(), w.start)).collect();
+        assert_eq!(words, [("first", 1.0), ("second", 601.0)]);
+        assert_eq!(transcript_data_type("echo", "test", None), "transcript_echo_test");
+    }
+
+    /// Stands in for a real service in cache lookups
+    struct NamedProvider(&'static str, &'static str);
+
+    impl TranscriptionProvider for NamedProvider {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn model(&self) -> &str {
+            self.1
+        }
+
+        async fn transcribe(&self, _audio: AudioUpload) -> Result<Transcript, LexiError> {
+            unreachable!("cache lookups never upload")
+        }
+    }
+
+    #[test]
+    fn reuses_legacy_elevenlabs_transcripts() {
+        let dir = TempDir::new().unwrap();
+        let db = CacheDb::init(dir.path().to_path_buf()).unwrap();
+        let legacy = r#"{"language_code": "en", "words": [
+            {"text": "Hi", "start": 0.1, "end": 0.3, "type": "word"}
+        ]}"#;
+        db.set_cached("cid", LEGACY_DATA_TYPE_TRANSCRIPTION, legacy).unwrap();
+
+        // Another model never produced the legacy transcript
+        let other = NamedProvider("elevenlabs", "scribe_v1");
+        let other_type = transcript_data_type(other.name(), other.model(), None);
+        assert_eq!(cached_transcript(&db, &other, "cid", &other_type).unwrap(), None);
+
+        let provider = NamedProvider("elevenlabs", DEFAULT_ELEVENLABS_MODEL);
+        let data_type = transcript_data_type(provider.name(), provider.model(), None);
+        let transcript = cached_transcript(&db, &provider, "cid", &data_type).unwrap().unwrap();
+        assert_eq!(transcript.words[0].text, "Hi");
+        assert!(db.get_cached("cid", &data_type).unwrap().is_some());
+    }
+
+    #[test]
+    fn servers_do_not_share_cached_transcripts() {
+        let openai = transcript_data_type("openai", "whisper-1", Some("https://api.openai.com/v1"));
+        let local = transcript_data_type("openai", "whisper-1", Some("http://127.0.0.1:8080/v1"));
+        assert_ne!(openai, local);
+        assert!(local.starts_with("transcript_openai_whisper-1_"));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::error::LexiError;
+use crate::services::{
+    probe_media_cached, ArtifactStore, CacheDb, JobHandle, JobKind, JobManager, Waveform,
+    WaveformBuilder, WAVEFORM_SAMPLE_RATE,
+};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tauri::ipc::Response;
+use tauri::{AppHandle, Manager, State};
+
+const WAVEFORM_KIND: &str = "waveforms";
+const WAVEFORM_FILE: &str = "peaks.dat";
+
+/// Decode a source's audio once and return min/max peaks at several zoom levels in
+/// the compact binary form documented in `services::waveform`. Cached by CID, so
+/// later calls just read the file.
+#[tauri::command]
+pub async fn compute_waveform(
+    app: AppHandle,
+    path: String,
+    cid: String,
+    jobs: State<'_, JobManager>,
+) -> Result<Response, LexiError> {
+    let label = path.clone();
+    let bytes = jobs
+        .run_blocking(JobKind::Waveform, label, move |job| {
+            let db = app.state::<CacheDb>();
+            let artifacts = app.state::<ArtifactStore>();
+            load_or_compute_waveform(job, &path, &cid, &db, &artifacts)
+        })
+        .await?;
+    Ok(Response::new(bytes))
+}
+
+fn load_or_compute_waveform(
+    job: &JobHandle,
+    path: &str,
+    cid: &str,
+    db: &CacheDb,
+    artifacts: &ArtifactStore,
+) -> Result<Vec<u8>, LexiError> {
+    let file = artifacts.path(WAVEFORM_KIND, cid, WAVEFORM_FILE)?;
+    if let Ok(bytes) = fs::read(&file) {
+        if Waveform::from_bytes(&bytes).is_some() {
+            return Ok(bytes);
+        }
+    }
+
+    let duration = probe_media_cached(job.runner(), Path::new(path), Some(cid), db)?.duration;
+    let bytes = decode_waveform(job, path, duration)?.to_bytes();
+    fs::write(&file, &bytes).map_err(|e| LexiError::io("Failed to write waveform", &file, e))?;
+    Ok(bytes)
+}
+
+/// Stream the audio as mono 16-bit PCM and fold it into peaks as it arrives
+fn decode_waveform(job: &JobHandle, path: &str, duration: f64) -> Result<Waveform, LexiError> {
+    let rate = WAVEFORM_SAMPLE_RATE.to_string();
+    let child = job.spawn(
+        Command::new("ffmpeg")
+            .args([
+                "-hide_banner",
+                "-i", path,
+                "-vn",
+                "-ac", "1",
+                "-ar", &rate,
+                "-f", "s16le",
+                "-acodec", "pcm_s16le",
+                "pipe:1",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )?;
+
+    let stderr = child.take_stderr();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    let mut builder = WaveformBuilder::new(WAVEFORM_SAMPLE_RATE);
+    if let Some(mut stdout) = child.take_stdout() {
+        // Even-sized buffer so samples never straddle two reads
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut carry: Option<u8> = None;
+        let mut last_percent = 0.0;
+        loop {
+            let n = stdout
+                .read(&mut buf)
+                .map_err(|e| LexiError::internal(format!("Failed to read decoded audio: {}", e)))?;
+            if n == 0 {
+                break;
+            }
+
+            let mut chunk = &buf[..n];
+            if let Some(low) = carry.take() {
+                builder.push(i16::from_le_bytes([low, chunk[0]]));
+                chunk = &chunk[1..];
+            }
+            builder.push_s16le(chunk);
+            if chunk.len() % 2 == 1 {
+                carry = chunk.last().copied();
+            }
+
+            if duration > 0.0 {
+                let decoded = builder.samples() as f64 / WAVEFORM_SAMPLE_RATE as f64;
+                let percent = (decoded / duration * 100.0).min(100.0);
+                if percent - last_percent >= 1.0 {
+                    job.progress(percent, "Decoding audio");
+                    last_percent = percent;
+                }
+            }
+        }
+    }
+
+    let status = child.wait()?;
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if !status.success() {
+        return Err(LexiError::ffmpeg_failed("FFmpeg audio decoding failed", &stderr));
+    }
+
+    Ok(builder.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{probe_json, test_jobs, Canned, FakeRunner};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn decodes_once_then_serves_cached_peaks() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+
+        // One second of a full-scale square wave
+        let pcm: Vec<u8> = (0..WAVEFORM_SAMPLE_RATE)
+            .flat_map(|n| if n % 2 == 0 { i16::MAX } else { i16::MIN }.to_le_bytes())
+            .collect();
+
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let runner = FakeRunner::new();
+            runner
+                .on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(1.0, 640, 360, 0)))
+                .on("ffmpeg", &["s16le"], Canned::stdout_bytes(&pcm));
+
+            let (db, artifacts) = (db.clone(), artifacts.clone());
+            let bytes = test_jobs(runner.clone())
+                .run_blocking(JobKind::Waveform, "test", move |job| {
+                    load_or_compute_waveform(job, "/media/clip.mp4", "cid1", &db, &artifacts)
+                })
+                .await
+                .unwrap();
+            runs.push((bytes, runner.calls_to("ffmpeg").len()));
+        }
+
+        assert_eq!(runs[0].1, 1);
+        assert_eq!(runs[1].1, 0);
+        assert_eq!(runs[0].0, runs[1].0);
+
+        let waveform = Waveform::from_bytes(&runs[0].0).unwrap();
+        assert_eq!(waveform.levels[0].peaks.len(), 250);
+        assert!(waveform.levels[0].peaks.iter().all(|p| *p == (-128, 127)));
+    }
+
+    #[tokio::test]
+    async fn failed_decode_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(CacheDb::init(dir.path().to_path_buf()).unwrap());
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+        let runner = FakeRunner::new();
+        runner
+            .on("ffprobe", &["-show_format"], Canned::stdout(&probe_json(1.0, 640, 360, 0)))
+            .on("ffmpeg", &["s16le"], Canned::failure(1, "Output file #0 does not contain any stream"));
+
+        let result = test_jobs(runner)
+            .run_blocking(JobKind::Waveform, "test", move |job| {
+                load_or_compute_waveform(job, "/media/silent.mp4", "cid2", &db, &artifacts)
+            })
+            .await;
+        assert!(matches!(result, Err(LexiError::FfmpegFailed { .. })));
+    }
+}
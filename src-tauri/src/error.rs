@@ -0,0 +1,246 @@
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// Number of trailing stderr lines kept from a failed ffmpeg/ffprobe run
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Error returned by every Tauri command.
+///
+/// Serialized for the frontend as `{ code, message, details? }`, where `code` is a
+/// stable identifier the UI can match on to offer a recovery action and `details`
+/// carries the ffmpeg stderr tail, HTTP status or file path when known.
+#[derive(Debug, Clone)]
+pub enum LexiError {
+    /// ffmpeg or ffprobe is not installed or not on PATH
+    FfmpegMissing { program: String },
+    /// ffmpeg or ffprobe ran but exited with an error
+    FfmpegFailed { context: String, stderr: String },
+    FileNotFound { path: String },
+    PermissionDenied { path: String },
+    DiskFull { path: Option<String> },
+    Io {
+        context: String,
+        path: Option<String>,
+        message: String,
+    },
+    Database { message: String },
+    Serialization { context: String, message: String },
+    /// Network failure or non-success HTTP response
+    Http {
+        context: String,
+        status: Option<u16>,
+        message: String,
+    },
+    /// The remote API rejected our credentials (401/403)
+    AuthRejected { status: u16, message: String },
+    InvalidInput { message: String },
+    /// The job was cancelled by the user
+    Cancelled,
+    Internal { message: String },
+}
+
+#[derive(Serialize)]
+struct ErrorPayload<'a> {
+    code: &'a str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<ErrorDetails<'a>>,
+}
+
+#[derive(Serialize)]
+struct ErrorDetails<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stderr: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>,
+}
+
+impl LexiError {
+    /// Stable machine-readable error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            LexiError::FfmpegMissing { .. } => "FFMPEG_MISSING",
+            LexiError::FfmpegFailed { .. } => "FFMPEG_FAILED",
+            LexiError::FileNotFound { .. } => "FILE_NOT_FOUND",
+            LexiError::PermissionDenied { .. } => "PERMISSION_DENIED",
+            LexiError::DiskFull { .. } => "DISK_FULL",
+            LexiError::Io { .. } => "IO_ERROR",
+            LexiError::Database { .. } => "DATABASE_ERROR",
+            LexiError::Serialization { .. } => "SERIALIZATION_ERROR",
+            LexiError::Http { .. } => "HTTP_ERROR",
+            LexiError::AuthRejected { .. } => "AUTH_REJECTED",
+            LexiError::InvalidInput { .. } => "INVALID_INPUT",
+            LexiError::Cancelled => "CANCELLED",
+            LexiError::Internal { .. } => "INTERNAL",
+        }
+    }
+
+    /// Map an I/O error on `path` to the most specific variant
+    pub fn io(context: &str, path: impl AsRef<Path>, err: io::Error) -> Self {
+        let path = path.as_ref().display().to_string();
+        match err.kind() {
+            io::ErrorKind::NotFound => LexiError::FileNotFound { path },
+            io::ErrorKind::PermissionDenied => LexiError::PermissionDenied { path },
+            io::ErrorKind::StorageFull => LexiError::DiskFull { path: Some(path) },
+            _ => LexiError::Io {
+                context: context.to_string(),
+                path: Some(path),
+                message: err.to_string(),
+            },
+        }
+    }
+
+    /// Map a failure to spawn an external program (ffmpeg/ffprobe)
+    pub fn spawn(program: &str, err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::NotFound => LexiError::FfmpegMissing {
+                program: program.to_string(),
+            },
+            _ => LexiError::Io {
+                context: format!("Failed to run {}", program),
+                path: None,
+                message: err.to_string(),
+            },
+        }
+    }
+
+    /// An ffmpeg/ffprobe run that exited unsuccessfully, keeping only the stderr tail
+    pub fn ffmpeg_failed(context: &str, stderr: &[u8]) -> Self {
+        let stderr = String::from_utf8_lossy(stderr);
+        if stderr.contains("No space left on device") {
+            return LexiError::DiskFull { path: None };
+        }
+
+        let lines: Vec<&str> = stderr.trim_end().lines().collect();
+        let tail = lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n");
+
+        LexiError::FfmpegFailed {
+            context: context.to_string(),
+            stderr: tail,
+        }
+    }
+
+    pub fn database(context: &str, err: impl fmt::Display) -> Self {
+        LexiError::Database {
+            message: format!("{}: {}", context, err),
+        }
+    }
+
+    pub fn serialization(context: &str, err: impl fmt::Display) -> Self {
+        LexiError::Serialization {
+            context: context.to_string(),
+            message: err.to_string(),
+        }
+    }
+
+    /// A transport-level HTTP failure (no response received)
+    pub fn http(context: &str, err: impl fmt::Display) -> Self {
+        LexiError::Http {
+            context: context.to_string(),
+            status: None,
+            message: err.to_string(),
+        }
+    }
+
+    /// A non-success HTTP response; 401/403 become `AuthRejected`
+    pub fn http_status(context: &str, status: u16, message: impl Into<String>) -> Self {
+        let message = message.into();
+        if status == 401 || status == 403 {
+            return LexiError::AuthRejected { status, message };
+        }
+        LexiError::Http {
+            context: context.to_string(),
+            status: Some(status),
+            message,
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        LexiError::InvalidInput {
+            message: message.into(),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        LexiError::Internal {
+            message: message.into(),
+        }
+    }
+
+    fn details(&self) -> Option<ErrorDetails<'_>> {
+        let (stderr, status, path) = match self {
+            LexiError::FfmpegFailed { stderr, .. } => (Some(stderr.as_str()), None, None),
+            LexiError::FileNotFound { path } | LexiError::PermissionDenied { path } => {
+                (None, None, Some(path.as_str()))
+            }
+            LexiError::DiskFull { path } | LexiError::Io { path, .. } => {
+                (None, None, path.as_deref())
+            }
+            LexiError::Http { status, .. } => (None, *status, None),
+            LexiError::AuthRejected { status, .. } => (None, Some(*status), None),
+            _ => return None,
+        };
+
+        if stderr.is_none() && status.is_none() && path.is_none() {
+            return None;
+        }
+
+        Some(ErrorDetails {
+            stderr,
+            status,
+            path,
+        })
+    }
+}
+
+impl fmt::Display for LexiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexiError::FfmpegMissing { program } => write!(
+                f,
+                "{} was not found. Install ffmpeg and make sure it is on your PATH.",
+                program
+            ),
+            LexiError::FfmpegFailed { context, .. } => write!(f, "{}", context),
+            LexiError::FileNotFound { path } => write!(f, "File not found: {}", path),
+            LexiError::PermissionDenied { path } => write!(f, "Permission denied: {}", path),
+            LexiError::DiskFull { .. } => write!(f, "Not enough disk space"),
+            LexiError::Io {
+                context, message, ..
+            } => write!(f, "{}: {}", context, message),
+            LexiError::Database { message } => write!(f, "{}", message),
+            LexiError::Serialization { context, message } => write!(f, "{}: {}", context, message),
+            LexiError::Http {
+                context, message, ..
+            } => write!(f, "{}: {}", context, message),
+            LexiError::AuthRejected { message, .. } => write!(f, "API key rejected: {}", message),
+            LexiError::Cancelled => write!(f, "Cancelled"),
+            LexiError::InvalidInput { message } | LexiError::Internal { message } => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LexiError {}
+
+impl Serialize for LexiError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ErrorPayload {
+            code: self.code(),
+            message: self.to_string(),
+            details: self.details(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl From<tokio::task::JoinError> for LexiError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        LexiError::internal(format!("Task failed: {}", err))
+    }
+}
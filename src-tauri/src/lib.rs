@@ -1,13 +1,26 @@
 mod commands;
+mod error;
+mod media_protocol;
 mod services;
+#[cfg(test)]
+mod test_support;
 
 use commands::{
-    export_video, extract_clip_base64, extract_frames_base64, generate_cid, generate_thumbnail,
-    get_cached, get_dimensions, get_duration, load_project_data, load_projects, read_file_base64,
-    save_project_data, save_projects, set_cached, upload_to_late,
+    cancel_job, cid_is_sampled, compute_waveform, detect_disfluencies, detect_silence, export_video,
+    extract_audio, extract_clip_base64, extract_frames_base64, filter_entries_by_speaker,
+    generate_cid, generate_filmstrip, generate_proxy, generate_thumbnail, get_cached,
+    get_dimensions, get_duration, list_jobs, load_project_data, load_projects, merge_speakers,
+    migrate_cid, probe_media, read_file_base64, register_media_source, rename_speaker,
+    save_project_data, save_projects, set_cached, set_max_concurrent_ffmpeg, sync_sources,
+    transcribe_source, upload_to_late, verify_cid,
 };
-use services::CacheDb;
-use tauri::Manager;
+use media_protocol::{handle_media_request, MEDIA_SCHEME};
+use services::{
+    ArtifactStore, CacheDb, FrameCache, JobManager, SystemRunner, DEFAULT_FRAME_CACHE_BYTES,
+    DEFAULT_MAX_CONCURRENT_FFMPEG,
+};
+use std::sync::Arc;
+use tauri::{Emitter, Manager};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -15,6 +28,13 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_http::init())
+        .register_asynchronous_uri_scheme_protocol(MEDIA_SCHEME, |ctx, request, responder| {
+            // Reads can be large and slow; keep them off the webview thread
+            let app = ctx.app_handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                responder.respond(handle_media_request(&app, &request));
+            });
+        })
         .setup(|app| {
             // Initialize cache database
             let app_data_dir = app
@@ -22,20 +42,50 @@ pub fn run() {
                 .app_data_dir()
                 .expect("Failed to get app data dir");
 
+            // Extracted frames live next to the database, which indexes them
+            let frame_cache =
+                FrameCache::new(app_data_dir.join("frames"), DEFAULT_FRAME_CACHE_BYTES);
+            app.manage(frame_cache);
+            app.manage(ArtifactStore::new(app_data_dir.clone()));
+
             let cache_db =
                 CacheDb::init(app_data_dir).expect("Failed to initialize cache database");
             app.manage(cache_db);
 
+            // Initialize job manager, publishing job updates as `job-progress` events
+            let app_handle = app.handle().clone();
+            let job_manager = JobManager::new(
+                DEFAULT_MAX_CONCURRENT_FFMPEG,
+                Arc::new(SystemRunner),
+                Arc::new(move |job| {
+                    let _ = app_handle.emit("job-progress", job);
+                }),
+            );
+            app.manage(job_manager);
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             generate_thumbnail,
             generate_cid,
+            register_media_source,
             get_duration,
             get_dimensions,
+            probe_media,
             export_video,
             extract_clip_base64,
+            extract_audio,
+            transcribe_source,
+            sync_sources,
             extract_frames_base64,
+            generate_filmstrip,
+            generate_proxy,
+            compute_waveform,
+            detect_silence,
+            detect_disfluencies,
+            rename_speaker,
+            merge_speakers,
+            filter_entries_by_speaker,
             read_file_base64,
             get_cached,
             set_cached,
@@ -43,7 +93,13 @@ pub fn run() {
             save_projects,
             save_project_data,
             load_project_data,
-            upload_to_late
+            upload_to_late,
+            verify_cid,
+            cid_is_sampled,
+            migrate_cid,
+            list_jobs,
+            cancel_job,
+            set_max_concurrent_ffmpeg
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,412 @@
+use crate::error::LexiError;
+use crate::services::{ArtifactStore, CacheDb, FrameCache};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tauri::http::header::{HeaderMap, HeaderValue};
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager};
+
+/// Registered as `lexi://`; the frontend builds URLs with `getMediaUrl`
+pub const MEDIA_SCHEME: &str = "lexi";
+
+/// Most bytes served for one request; players ask for the rest as they go, so a
+/// `bytes=0-` request for a 4 GB file does not load it into memory
+const MAX_RANGE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Origins the app's webview loads from (macOS/Linux, Windows over http and
+/// https) and the dev server; only these may read media with `fetch()`
+const ALLOWED_ORIGINS: [&str; 4] = [
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+    "http://localhost:1420",
+];
+
+/// Cache data type mapping a CID to the source file it was computed from
+const DATA_TYPE_SOURCE_PATH: &str = "source_path";
+
+/// Artifact directories that may be served, as created by `ArtifactStore`
+const ARTIFACT_KINDS: [&str; 4] = ["thumbnails", "filmstrips", "audio", "proxies"];
+
+/// Frame cache files share the `{cid}_{name}` naming of artifacts
+const FRAMES_KIND: &str = "frames";
+
+/// What a `lexi://media/...` URL points at
+#[derive(Debug, PartialEq)]
+enum MediaTarget<'a> {
+    /// `media/<cid>`: the original source file
+    Source { cid: &'a str },
+    /// `media/<cid>/<kind>/<name>`: a file derived from the source
+    Derived {
+        kind: &'a str,
+        cid: &'a str,
+        name: &'a str,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    Full,
+    /// Inclusive start and end
+    Partial(u64, u64),
+    Unsatisfiable,
+}
+
+/// Remember which file a CID was computed from, so `lexi://media/<cid>` can serve it
+pub fn register_source(db: &CacheDb, cid: &str, path: &str) -> Result<(), LexiError> {
+    let json = serde_json::to_string(path)
+        .map_err(|e| LexiError::serialization("Failed to serialize source path", e))?;
+    db.set_cached(cid, DATA_TYPE_SOURCE_PATH, &json)
+}
+
+fn source_path(db: &CacheDb, cid: &str) -> Result<Option<PathBuf>, LexiError> {
+    let Some(json) = db.get_cached(cid, DATA_TYPE_SOURCE_PATH)? else {
+        return Ok(None);
+    };
+    let path: String = serde_json::from_str(&json)
+        .map_err(|e| LexiError::serialization("Failed to parse source path", e))?;
+    Ok(Some(PathBuf::from(path)))
+}
+
+/// Serve a `lexi://` request: source media and derived files by CID, with HTTP
+/// Range support so `<video>` elements and `fetch()` can stream large files.
+///
+/// URLs look like `lexi://localhost/media/<cid>` on macOS and Linux and
+/// `http://lexi.localhost/media/<cid>` on Windows; `lexi://media/<cid>` also works.
+pub fn handle_media_request(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let origin = request.headers().get(header::ORIGIN).and_then(|v| v.to_str().ok());
+    let mut response = respond(app, request);
+    add_cors(response.headers_mut(), origin);
+    response
+}
+
+fn respond(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    if request.method() == Method::OPTIONS {
+        return Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header(header::ACCESS_CONTROL_ALLOW_METHODS, "GET, HEAD, OPTIONS")
+            .header(header::ACCESS_CONTROL_ALLOW_HEADERS, "Range")
+            .body(Vec::new())
+            .unwrap_or_default();
+    }
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return error_response(StatusCode::METHOD_NOT_ALLOWED, "Only GET and HEAD are supported");
+    }
+
+    let uri = request.uri();
+    let Some(target) = parse_target(uri.host(), uri.path()) else {
+        return error_response(StatusCode::NOT_FOUND, "Unknown media URL");
+    };
+
+    let range = request.headers().get(header::RANGE).and_then(|v| v.to_str().ok());
+    let db = app.state::<CacheDb>();
+    let artifacts = app.state::<ArtifactStore>();
+    let frames = app.state::<FrameCache>();
+    resolve(&target, &db, &artifacts, &frames)
+        .and_then(|file| serve_file(&file, range, request.method() == Method::HEAD))
+        .unwrap_or_else(|e| {
+            println!("[media_protocol] {} failed: {}", uri, e);
+            let status = match e {
+                LexiError::FileNotFound { .. } => StatusCode::NOT_FOUND,
+                LexiError::PermissionDenied { .. } => StatusCode::FORBIDDEN,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            error_response(status, &e.to_string())
+        })
+}
+
+fn parse_target<'a>(host: Option<&'a str>, path: &'a str) -> Option<MediaTarget<'a>> {
+    let mut segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    if host != Some("media") {
+        if segments.first() != Some(&"media") {
+            return None;
+        }
+        segments.remove(0);
+    }
+    if !segments.iter().all(|s| is_safe_segment(s)) {
+        return None;
+    }
+
+    match segments[..] {
+        [cid] => Some(MediaTarget::Source { cid }),
+        [cid, kind, name] if ARTIFACT_KINDS.contains(&kind) || kind == FRAMES_KIND => {
+            Some(MediaTarget::Derived { kind, cid, name })
+        }
+        _ => None,
+    }
+}
+
+/// Plain file name characters only, so a URL can never leave the app data dir
+fn is_safe_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment != "."
+        && segment != ".."
+        && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn resolve(
+    target: &MediaTarget,
+    db: &CacheDb,
+    artifacts: &ArtifactStore,
+    frames: &FrameCache,
+) -> Result<PathBuf, LexiError> {
+    match *target {
+        MediaTarget::Source { cid } => {
+            source_path(db, cid)?.ok_or_else(|| LexiError::FileNotFound {
+                path: format!("media/{}", cid),
+            })
+        }
+        MediaTarget::Derived { kind: FRAMES_KIND, cid, name } => {
+            Ok(frames.file(&format!("{}_{}", cid, name)))
+        }
+        MediaTarget::Derived { kind, cid, name } => Ok(artifacts.existing(kind, cid, name)),
+    }
+}
+
+/// Range requested by a `Range` header. A malformed header is ignored, as HTTP
+/// requires; of several ranges only the first is served.
+fn byte_range(header: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    let first = spec.split(',').next().unwrap_or_default().trim();
+    let Some((start, end)) = first.split_once('-') else {
+        return ByteRange::Full;
+    };
+
+    let (start, end) = match (start.parse::<u64>(), end.parse::<u64>()) {
+        // "bytes=-500": the last 500 bytes
+        (Err(_), Ok(suffix)) if start.is_empty() => {
+            if suffix == 0 || len == 0 {
+                return ByteRange::Unsatisfiable;
+            }
+            (len.saturating_sub(suffix), len - 1)
+        }
+        (Ok(start), Err(_)) if end.is_empty() => (start, len.saturating_sub(1)),
+        (Ok(start), Ok(end)) if end >= start => (start, end.min(len.saturating_sub(1))),
+        _ => return ByteRange::Full,
+    };
+
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial(start, end.min(start + MAX_RANGE_BYTES - 1))
+}
+
+fn serve_file(
+    file: &Path,
+    range: Option<&str>,
+    head: bool,
+) -> Result<Response<Vec<u8>>, LexiError> {
+    let mut handle = File::open(file).map_err(|e| LexiError::io("Failed to open media", file, e))?;
+    let len = handle
+        .metadata()
+        .map_err(|e| LexiError::io("Failed to stat media", file, e))?
+        .len();
+
+    let response = Response::builder()
+        .header(header::CONTENT_TYPE, mime_type(file))
+        .header(header::ACCEPT_RANGES, "bytes");
+
+    let range = match byte_range(range, len) {
+        // Too large to send whole; players continue with ranges of their own
+        ByteRange::Full if len > MAX_RANGE_BYTES => ByteRange::Partial(0, MAX_RANGE_BYTES - 1),
+        range => range,
+    };
+    let (response, start, count) = match range {
+        ByteRange::Full => (response.status(StatusCode::OK), 0, len),
+        ByteRange::Partial(start, end) => (
+            response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len)),
+            start,
+            end - start + 1,
+        ),
+        ByteRange::Unsatisfiable => {
+            return response
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+                .body(Vec::new())
+                .map_err(|e| LexiError::internal(format!("Invalid media response: {}", e)));
+        }
+    };
+
+    let mut body = Vec::new();
+    if !head {
+        handle
+            .seek(SeekFrom::Start(start))
+            .map_err(|e| LexiError::io("Failed to seek media", file, e))?;
+        body.reserve_exact(count as usize);
+        handle
+            .take(count)
+            .read_to_end(&mut body)
+            .map_err(|e| LexiError::io("Failed to read media", file, e))?;
+    }
+
+    response
+        .header(header::CONTENT_LENGTH, count)
+        .body(body)
+        .map_err(|e| LexiError::internal(format!("Invalid media response: {}", e)))
+}
+
+/// Echo the request's origin when it is the app's own webview; any other page
+/// gets no CORS headers and so cannot read media
+fn add_cors(headers: &mut HeaderMap, origin: Option<&str>) {
+    headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+    let Some(origin) = ALLOWED_ORIGINS.iter().find(|allowed| origin == Some(**allowed)) else {
+        return;
+    };
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static(origin));
+    headers.insert(
+        header::ACCESS_CONTROL_EXPOSE_HEADERS,
+        HeaderValue::from_static("Content-Range, Content-Length, Accept-Ranges"),
+    );
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(message.as_bytes().to_vec())
+        .unwrap_or_default()
+}
+
+fn mime_type(file: &Path) -> &'static str {
+    let extension = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "mp4" => "video/mp4",
+        "m4v" => "video/x-m4v",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        "avi" => "video/x-msvideo",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "aac" => "audio/aac",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "ogg" | "opus" => "audio/ogg",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "webp" => "image/webp",
+        "gif" => "image/gif",
+        "vtt" => "text/vtt",
+        "json" => "application/json",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn parses_media_urls_on_every_platform_and_rejects_escapes() {
+        let source = Some(MediaTarget::Source { cid: "abc123" });
+        assert_eq!(parse_target(Some("media"), "/abc123"), source);
+        assert_eq!(parse_target(Some("localhost"), "/media/abc123"), source);
+        assert_eq!(parse_target(Some("lexi.localhost"), "/media/abc123"), source);
+        assert_eq!(
+            parse_target(Some("localhost"), "/media/abc123/thumbnails/small.jpg"),
+            Some(MediaTarget::Derived {
+                kind: "thumbnails",
+                cid: "abc123",
+                name: "small.jpg"
+            })
+        );
+
+        assert_eq!(parse_target(Some("localhost"), "/media/abc123/../cache.db"), None);
+        assert_eq!(parse_target(Some("localhost"), "/media/abc123/projects/x.json"), None);
+        assert_eq!(parse_target(Some("localhost"), "/media/%2E%2E/x"), None);
+        assert_eq!(parse_target(Some("localhost"), "/abc123"), None);
+    }
+
+    #[test]
+    fn clamps_ranges_to_the_file() {
+        assert_eq!(byte_range(None, 1000), ByteRange::Full);
+        assert_eq!(byte_range(Some("bytes=0-"), 1000), ByteRange::Partial(0, 999));
+        assert_eq!(byte_range(Some("bytes=100-199"), 1000), ByteRange::Partial(100, 199));
+        assert_eq!(byte_range(Some("bytes=900-5000"), 1000), ByteRange::Partial(900, 999));
+        assert_eq!(byte_range(Some("bytes=-100"), 1000), ByteRange::Partial(900, 999));
+        assert_eq!(byte_range(Some("bytes=0-9, 20-29"), 1000), ByteRange::Partial(0, 9));
+        assert_eq!(byte_range(Some("bytes=1000-"), 1000), ByteRange::Unsatisfiable);
+        assert_eq!(byte_range(Some("bytes=9-3"), 1000), ByteRange::Full);
+        assert_eq!(byte_range(Some("items=0-9"), 1000), ByteRange::Full);
+
+        let huge = 10 * MAX_RANGE_BYTES;
+        assert_eq!(
+            byte_range(Some("bytes=0-"), huge),
+            ByteRange::Partial(0, MAX_RANGE_BYTES - 1)
+        );
+    }
+
+    #[test]
+    fn serves_registered_sources_by_range() {
+        let dir = TempDir::new().unwrap();
+        let db = CacheDb::init(dir.path().to_path_buf()).unwrap();
+        let artifacts = ArtifactStore::new(dir.path().to_path_buf());
+        let frames = FrameCache::new(dir.path().join("frames"), 1024);
+
+        let video = dir.path().join("Interview.MOV");
+        std::fs::write(&video, b"0123456789").unwrap();
+        register_source(&db, "cid1", &video.to_string_lossy()).unwrap();
+
+        let target = parse_target(Some("localhost"), "/media/cid1").unwrap();
+        let file = resolve(&target, &db, &artifacts, &frames).unwrap();
+
+        let partial = serve_file(&file, Some("bytes=2-5"), false).unwrap();
+        assert_eq!(partial.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(partial.body(), b"2345");
+        assert_eq!(partial.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+        assert_eq!(partial.headers()[header::CONTENT_TYPE], "video/quicktime");
+
+        let full = serve_file(&file, None, false).unwrap();
+        assert_eq!(full.status(), StatusCode::OK);
+        assert_eq!(full.body(), b"0123456789");
+
+        let head = serve_file(&file, None, true).unwrap();
+        assert!(head.body().is_empty());
+        assert_eq!(head.headers()[header::CONTENT_LENGTH], "10");
+
+        let past_end = serve_file(&file, Some("bytes=10-"), false).unwrap();
+        assert_eq!(past_end.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+        let unknown = parse_target(Some("localhost"), "/media/cid2").unwrap();
+        assert!(matches!(
+            resolve(&unknown, &db, &artifacts, &frames),
+            Err(LexiError::FileNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn caps_requests_without_a_range_on_large_files() {
+        let dir = TempDir::new().unwrap();
+        let video = dir.path().join("long.mp4");
+        std::fs::write(&video, vec![0u8; MAX_RANGE_BYTES as usize + 10]).unwrap();
+
+        let response = serve_file(&video, None, false).unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body().len() as u64, MAX_RANGE_BYTES);
+        let content_range = format!("bytes 0-{}/{}", MAX_RANGE_BYTES - 1, MAX_RANGE_BYTES + 10);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], content_range.as_str());
+    }
+
+    #[test]
+    fn allows_only_the_app_origins() {
+        let mut headers = HeaderMap::new();
+        add_cors(&mut headers, Some("http://tauri.localhost"));
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "http://tauri.localhost");
+
+        for origin in [Some("https://example.com"), None] {
+            let mut headers = HeaderMap::new();
+            add_cors(&mut headers, origin);
+            assert!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::error::LexiError;
+use std::fs;
+use std::path::PathBuf;
+
+/// Files derived from a source (filmstrips, waveforms, thumbnails, ...) under the app
+/// data dir. Each kind gets its own subdirectory and every file name starts with the
+/// source CID, so everything derived from one source can be found again.
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Path of the artifact `name` of `kind` for `cid`, creating its directory
+    pub fn path(&self, kind: &str, cid: &str, name: &str) -> Result<PathBuf, LexiError> {
+        let dir = self.root.join(kind);
+        fs::create_dir_all(&dir)
+            .map_err(|e| LexiError::io("Failed to create artifact dir", &dir, e))?;
+        Ok(dir.join(format!("{}_{}", cid, name)))
+    }
+
+    /// Rename every file derived from `from` to belong to `to` after a source's CID
+    /// changed, in all kinds (including the frame cache, which shares the naming).
+    /// Files `to` already has are kept and the old copies removed. Returns the
+    /// number of files moved.
+    pub fn rename_cid(&self, from: &str, to: &str) -> Result<usize, LexiError> {
+        let prefix = format!("{}_", from);
+        let Ok(kinds) = fs::read_dir(&self.root) else {
+            return Ok(0);
+        };
+
+        let mut moved = 0;
+        for kind in kinds.flatten().filter(|e| e.path().is_dir()) {
+            let dir = kind.path();
+            let entries =
+                fs::read_dir(&dir).map_err(|e| LexiError::io("Failed to list artifacts", &dir, e))?;
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let Some(rest) = name.strip_prefix(&prefix) else {
+                    continue;
+                };
+                let target = dir.join(format!("{}_{}", to, rest));
+                if target.exists() {
+                    let _ = fs::remove_file(entry.path());
+                    continue;
+                }
+                fs::rename(entry.path(), &target)
+                    .map_err(|e| LexiError::io("Failed to rename artifact", &target, e))?;
+                moved += 1;
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Path of the artifact `name` of `kind` for `cid`, for reading; the file may not exist
+    pub fn existing(&self, kind: &str, cid: &str, name: &str) -> PathBuf {
+        self.root.join(kind).join(format!("{}_{}", cid, name))
+    }
+}
@@ -0,0 +1,212 @@
+/// Sample rate audio is decoded at for syncing; only the loudness contour matters
+pub const SYNC_SAMPLE_RATE: u32 = 8000;
+
+/// Envelope values per second (10 ms resolution)
+pub const ENVELOPE_RATE: u32 = 100;
+
+const SAMPLES_PER_FRAME: usize = (SYNC_SAMPLE_RATE / ENVELOPE_RATE) as usize;
+
+/// Energy floor (normalized samples, squared) so silence does not produce onsets
+const ENERGY_FLOOR: f64 = 1e-7;
+
+/// Peaks closer than this to the best one belong to the same match
+const SECOND_PEAK_EXCLUSION: f64 = 1.0;
+
+/// Result of aligning one envelope against a reference
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncMatch {
+    /// Seconds to add to a time in the other source to get reference time
+    /// (positive when the other source started recording later)
+    pub offset: f64,
+    /// 0-1: how much the best alignment stands out from the next best one
+    pub confidence: f64,
+}
+
+/// Onset envelope of mono 16-bit PCM at [`SYNC_SAMPLE_RATE`]: the rise in log
+/// energy per 10 ms frame, normalized to zero mean and unit variance. Onsets line
+/// up between microphones with very different gain, tone and room sound.
+pub fn onset_envelope(pcm: &[u8]) -> Vec<f32> {
+    let log_energy: Vec<f64> = pcm
+        .chunks_exact(SAMPLES_PER_FRAME * 2)
+        .map(|frame| {
+            let energy = frame
+                .chunks_exact(2)
+                .map(|s| {
+                    let v = i16::from_le_bytes([s[0], s[1]]) as f64 / 32768.0;
+                    v * v
+                })
+                .sum::<f64>()
+                / SAMPLES_PER_FRAME as f64;
+            (energy + ENERGY_FLOOR).ln()
+        })
+        .collect();
+
+    let onsets: Vec<f64> = log_energy
+        .iter()
+        .enumerate()
+        .map(|(i, &e)| if i == 0 { 0.0 } else { (e - log_energy[i - 1]).max(0.0) })
+        .collect();
+
+    let n = onsets.len().max(1) as f64;
+    let mean = onsets.iter().sum::<f64>() / n;
+    let std = (onsets.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+    onsets
+        .iter()
+        .map(|v| if std > 0.0 { ((v - mean) / std) as f32 } else { 0.0 })
+        .collect()
+}
+
+/// Find where `other` lines up with `reference` by cross-correlating their
+/// envelopes, searching offsets up to `max_offset` seconds either way (default:
+/// any offset that leaves a quarter of the shorter recording overlapping).
+pub fn align_envelopes(
+    reference: &[f32],
+    other: &[f32],
+    max_offset: Option<f64>,
+) -> Option<SyncMatch> {
+    if reference.is_empty() || other.is_empty() {
+        return None;
+    }
+
+    let size = (reference.len() + other.len()).next_power_of_two();
+    let mut a = to_complex(reference, size);
+    let mut b = to_complex(other, size);
+    fft(&mut a, false);
+    fft(&mut b, false);
+    // a * conj(b) transforms back to sum_n reference[n + lag] * other[n]
+    let mut product: Vec<(f64, f64)> = a
+        .iter()
+        .zip(&b)
+        .map(|(&(ar, ai), &(br, bi))| (ar * br + ai * bi, ai * br - ar * bi))
+        .collect();
+    fft(&mut product, true);
+
+    let min_overlap = (reference.len().min(other.len()) / 4).max(1) as i64;
+    let max_lag = max_offset.map(|s| (s * ENVELOPE_RATE as f64).round() as i64);
+    let (ref_len, other_len) = (reference.len() as i64, other.len() as i64);
+
+    // Correlation per lag, normalized by how much of the two actually overlaps
+    let scores: Vec<(i64, f64)> = (-(other_len - 1)..ref_len)
+        .filter(|lag| max_lag.is_none_or(|m| lag.abs() <= m))
+        .filter_map(|lag| {
+            let overlap = (ref_len.min(lag + other_len) - lag.max(0)).max(0);
+            if overlap < min_overlap {
+                return None;
+            }
+            let index = lag.rem_euclid(size as i64) as usize;
+            Some((lag, product[index].0 / size as f64 / overlap as f64))
+        })
+        .collect();
+
+    let best = scores
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1))?
+        .0;
+    let (best_lag, best_score) = scores[best];
+
+    let exclusion = (SECOND_PEAK_EXCLUSION * ENVELOPE_RATE as f64) as i64;
+    let second = scores
+        .iter()
+        .filter(|(lag, _)| (lag - best_lag).abs() > exclusion)
+        .map(|&(_, score)| score)
+        .fold(0.0_f64, f64::max);
+    let confidence = if best_score > 0.0 {
+        ((best_score - second) / best_score).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    // Parabolic interpolation between neighbouring lags for sub-frame precision
+    let mut refined = best_lag as f64;
+    if best > 0 && best + 1 < scores.len() {
+        let (l, c, r) = (scores[best - 1].1, best_score, scores[best + 1].1);
+        let denom = l - 2.0 * c + r;
+        if denom < 0.0 {
+            refined += (0.5 * (l - r) / denom).clamp(-0.5, 0.5);
+        }
+    }
+
+    Some(SyncMatch {
+        offset: refined / ENVELOPE_RATE as f64,
+        confidence,
+    })
+}
+
+fn to_complex(values: &[f32], size: usize) -> Vec<(f64, f64)> {
+    let mut out = vec![(0.0, 0.0); size];
+    for (slot, &v) in out.iter_mut().zip(values) {
+        slot.0 = v as f64;
+    }
+    out
+}
+
+/// In-place iterative radix-2 FFT; `data.len()` must be a power of two. The
+/// inverse is unscaled.
+fn fft(data: &mut [(f64, f64)], inverse: bool) {
+    let n = data.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * std::f64::consts::PI / len as f64;
+        let step = (angle.cos(), angle.sin());
+        for start in (0..n).step_by(len) {
+            let mut w = (1.0, 0.0);
+            for k in 0..len / 2 {
+                let (ur, ui) = data[start + k];
+                let (vr, vi) = data[start + k + len / 2];
+                let t = (vr * w.0 - vi * w.1, vr * w.1 + vi * w.0);
+                data[start + k] = (ur + t.0, ui + t.1);
+                data[start + k + len / 2] = (ur - t.0, ui - t.1);
+                w = (w.0 * step.0 - w.1 * step.1, w.0 * step.1 + w.1 * step.0);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{bursty_pcm, s16le_bytes};
+
+    #[test]
+    fn finds_offset_between_differently_gained_recordings() {
+        let reference = bursty_pcm(60.0, SYNC_SAMPLE_RATE, 7);
+        // Started recording 7.25s later, at a third of the level
+        let start = (7.25 * SYNC_SAMPLE_RATE as f64) as usize;
+        let other: Vec<i16> = reference[start..].iter().map(|s| s / 3).collect();
+
+        let found = align_envelopes(
+            &onset_envelope(&s16le_bytes(&reference)),
+            &onset_envelope(&s16le_bytes(&other)),
+            None,
+        )
+        .unwrap();
+        assert!((found.offset - 7.25).abs() < 0.02, "offset {}", found.offset);
+        assert!(found.confidence > 0.5, "confidence {}", found.confidence);
+
+        // Unrelated audio still aligns somewhere, but with low confidence
+        let unrelated = bursty_pcm(40.0, SYNC_SAMPLE_RATE, 99);
+        let found = align_envelopes(
+            &onset_envelope(&s16le_bytes(&reference)),
+            &onset_envelope(&s16le_bytes(&unrelated)),
+            None,
+        )
+        .unwrap();
+        assert!(found.confidence < 0.3, "confidence {}", found.confidence);
+    }
+}
@@ -1,7 +1,35 @@
+use crate::error::LexiError;
 use rusqlite::{Connection, params};
 use std::path::PathBuf;
 use std::sync::Mutex;
 
+/// Index entry for a frame image stored on disk by the frame cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameCacheEntry {
+    pub cid: String,
+    /// Frame timestamp in milliseconds
+    pub timestamp_ms: i64,
+    /// Encoding options the frame was written with
+    pub options_key: String,
+    /// File name inside the frame cache directory
+    pub file_name: String,
+    pub width: u32,
+    pub height: u32,
+    pub byte_size: u64,
+    pub hash: Option<String>,
+}
+
+fn now_secs() -> Result<i64, LexiError> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| LexiError::internal(format!("Time error: {}", e)))?
+        .as_secs() as i64)
+}
+
+/// Next value of the frame cache use counter. Wall-clock seconds could not order
+/// frames used within the same second, so eviction would not be least-recent-first.
+const NEXT_FRAME_USE: &str = "(SELECT COALESCE(MAX(last_used), 0) + 1 FROM frame_cache)";
+
 /// Cache database wrapper with thread-safe connection
 pub struct CacheDb {
     conn: Mutex<Connection>,
@@ -9,14 +37,14 @@ pub struct CacheDb {
 
 impl CacheDb {
     /// Initialize the cache database
-    pub fn init(app_data_dir: PathBuf) -> Result<Self, String> {
+    pub fn init(app_data_dir: PathBuf) -> Result<Self, LexiError> {
         // Ensure directory exists
         std::fs::create_dir_all(&app_data_dir)
-            .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+            .map_err(|e| LexiError::io("Failed to create app data dir", &app_data_dir, e))?;
 
         let db_path = app_data_dir.join("cache.db");
         let conn = Connection::open(&db_path)
-            .map_err(|e| format!("Failed to open database: {}", e))?;
+            .map_err(|e| LexiError::database("Failed to open database", e))?;
 
         // Create tables
         conn.execute(
@@ -30,20 +58,57 @@ impl CacheDb {
             )",
             [],
         )
-        .map_err(|e| format!("Failed to create cache table: {}", e))?;
+        .map_err(|e| LexiError::database("Failed to create cache table", e))?;
 
         // Create indexes
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_cache_type ON cache(data_type)",
             [],
         )
-        .map_err(|e| format!("Failed to create type index: {}", e))?;
+        .map_err(|e| LexiError::database("Failed to create type index", e))?;
 
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_cache_updated ON cache(updated_at)",
             [],
         )
-        .map_err(|e| format!("Failed to create updated index: {}", e))?;
+        .map_err(|e| LexiError::database("Failed to create updated index", e))?;
+
+        // Frame cache index: one row per frame file under the frame cache directory
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS frame_cache (
+                cid TEXT NOT NULL,
+                timestamp_ms INTEGER NOT NULL,
+                options_key TEXT NOT NULL,
+                file_name TEXT NOT NULL,
+                width INTEGER NOT NULL,
+                height INTEGER NOT NULL,
+                byte_size INTEGER NOT NULL,
+                hash TEXT,
+                -- Use counter, bumped on every read and write; higher is more recent
+                last_used INTEGER NOT NULL,
+                PRIMARY KEY (cid, timestamp_ms, options_key)
+            )",
+            [],
+        )
+        .map_err(|e| LexiError::database("Failed to create frame cache table", e))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_frame_cache_used ON frame_cache(last_used)",
+            [],
+        )
+        .map_err(|e| LexiError::database("Failed to create frame cache index", e))?;
+
+        // Perceptual hashes of full-size frames, used by frame dedup
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS frame_hashes (
+                cid TEXT NOT NULL,
+                timestamp_ms INTEGER NOT NULL,
+                hash INTEGER NOT NULL,
+                PRIMARY KEY (cid, timestamp_ms)
+            )",
+            [],
+        )
+        .map_err(|e| LexiError::database("Failed to create frame hash table", e))?;
 
         Ok(Self {
             conn: Mutex::new(conn),
@@ -51,30 +116,27 @@ impl CacheDb {
     }
 
     /// Get cached data by CID and data type
-    pub fn get_cached(&self, cid: &str, data_type: &str) -> Result<Option<String>, String> {
-        let conn = self.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    pub fn get_cached(&self, cid: &str, data_type: &str) -> Result<Option<String>, LexiError> {
+        let conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
 
         let mut stmt = conn
             .prepare("SELECT data_json FROM cache WHERE cid = ?1 AND data_type = ?2")
-            .map_err(|e| format!("Prepare error: {}", e))?;
+            .map_err(|e| LexiError::database("Prepare error", e))?;
 
         let result: Result<String, _> = stmt.query_row(params![cid, data_type], |row| row.get(0));
 
         match result {
             Ok(json) => Ok(Some(json)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(format!("Query error: {}", e)),
+            Err(e) => Err(LexiError::database("Query error", e)),
         }
     }
 
     /// Set cached data by CID and data type
-    pub fn set_cached(&self, cid: &str, data_type: &str, data_json: &str) -> Result<(), String> {
-        let conn = self.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    pub fn set_cached(&self, cid: &str, data_type: &str, data_json: &str) -> Result<(), LexiError> {
+        let conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
 
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| format!("Time error: {}", e))?
-            .as_secs() as i64;
+        let now = now_secs()?;
 
         conn.execute(
             "INSERT OR REPLACE INTO cache (cid, data_type, data_json, created_at, updated_at)
@@ -83,8 +145,219 @@ impl CacheDb {
                 ?4)",
             params![cid, data_type, data_json, now],
         )
-        .map_err(|e| format!("Insert error: {}", e))?;
+        .map_err(|e| LexiError::database("Insert error", e))?;
+
+        Ok(())
+    }
+
+    /// Move every cache entry, frame and frame hash of `from` to `to` after a
+    /// source's CID changed. Entries `to` already has win. Paths inside cached
+    /// JSON and frame file names embed the CID and are rewritten to match the
+    /// renamed files (see `ArtifactStore::rename_cid`).
+    pub fn migrate_cid(&self, from: &str, to: &str) -> Result<(), LexiError> {
+        let mut conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| LexiError::database("Transaction error", e))?;
+        let copies = [
+            "INSERT OR IGNORE INTO cache (cid, data_type, data_json, created_at, updated_at)
+             SELECT ?2, data_type, REPLACE(data_json, ?1, ?2), created_at, updated_at
+             FROM cache WHERE cid = ?1",
+            "INSERT OR IGNORE INTO frame_cache
+                (cid, timestamp_ms, options_key, file_name, width, height, byte_size, hash, last_used)
+             SELECT ?2, timestamp_ms, options_key, REPLACE(file_name, ?1, ?2), width, height,
+                byte_size, hash, last_used
+             FROM frame_cache WHERE cid = ?1",
+            "INSERT OR IGNORE INTO frame_hashes (cid, timestamp_ms, hash)
+             SELECT ?2, timestamp_ms, hash FROM frame_hashes WHERE cid = ?1",
+        ];
+        for sql in copies {
+            tx.execute(sql, params![from, to])
+                .map_err(|e| LexiError::database("Migration error", e))?;
+        }
+        for table in ["cache", "frame_cache", "frame_hashes"] {
+            tx.execute(&format!("DELETE FROM {} WHERE cid = ?1", table), params![from])
+                .map_err(|e| LexiError::database("Migration error", e))?;
+        }
+        tx.commit()
+            .map_err(|e| LexiError::database("Commit error", e))
+    }
+
+    /// Look up a cached frame, marking it as recently used
+    pub fn get_cached_frame(
+        &self,
+        cid: &str,
+        timestamp_ms: i64,
+        options_key: &str,
+    ) -> Result<Option<FrameCacheEntry>, LexiError> {
+        let conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
+
+        let result = conn.query_row(
+            "SELECT file_name, width, height, byte_size, hash FROM frame_cache
+             WHERE cid = ?1 AND timestamp_ms = ?2 AND options_key = ?3",
+            params![cid, timestamp_ms, options_key],
+            |row| {
+                Ok(FrameCacheEntry {
+                    cid: cid.to_string(),
+                    timestamp_ms,
+                    options_key: options_key.to_string(),
+                    file_name: row.get(0)?,
+                    width: row.get(1)?,
+                    height: row.get(2)?,
+                    byte_size: row.get::<_, i64>(3)? as u64,
+                    hash: row.get(4)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(entry) => {
+                conn.execute(
+                    &format!(
+                        "UPDATE frame_cache SET last_used = {}
+                         WHERE cid = ?1 AND timestamp_ms = ?2 AND options_key = ?3",
+                        NEXT_FRAME_USE
+                    ),
+                    params![cid, timestamp_ms, options_key],
+                )
+                .map_err(|e| LexiError::database("Update error", e))?;
+                Ok(Some(entry))
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(LexiError::database("Query error", e)),
+        }
+    }
+
+    /// Record a frame written to the frame cache directory
+    pub fn set_cached_frame(&self, entry: &FrameCacheEntry) -> Result<(), LexiError> {
+        let conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
+
+        conn.execute(
+            &format!(
+                "INSERT OR REPLACE INTO frame_cache
+                    (cid, timestamp_ms, options_key, file_name, width, height, byte_size, hash,
+                     last_used)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, {})",
+                NEXT_FRAME_USE
+            ),
+            params![
+                entry.cid,
+                entry.timestamp_ms,
+                entry.options_key,
+                entry.file_name,
+                entry.width,
+                entry.height,
+                entry.byte_size as i64,
+                entry.hash
+            ],
+        )
+        .map_err(|e| LexiError::database("Insert error", e))?;
+
+        Ok(())
+    }
+
+    /// Forget a cached frame (the caller deletes the file)
+    pub fn remove_cached_frame(&self, entry: &FrameCacheEntry) -> Result<(), LexiError> {
+        let conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
+
+        conn.execute(
+            "DELETE FROM frame_cache WHERE cid = ?1 AND timestamp_ms = ?2 AND options_key = ?3",
+            params![entry.cid, entry.timestamp_ms, entry.options_key],
+        )
+        .map_err(|e| LexiError::database("Delete error", e))?;
 
         Ok(())
     }
+
+    /// Total bytes of all cached frame files
+    pub fn frame_cache_size(&self) -> Result<u64, LexiError> {
+        let conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
+
+        conn.query_row(
+            "SELECT COALESCE(SUM(byte_size), 0) FROM frame_cache",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|size| size as u64)
+        .map_err(|e| LexiError::database("Query error", e))
+    }
+
+    /// Least recently used cached frames, oldest first
+    pub fn least_recent_frames(&self, limit: usize) -> Result<Vec<FrameCacheEntry>, LexiError> {
+        let conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT cid, timestamp_ms, options_key, file_name, width, height, byte_size, hash
+                 FROM frame_cache ORDER BY last_used ASC LIMIT ?1",
+            )
+            .map_err(|e| LexiError::database("Prepare error", e))?;
+
+        let rows = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok(FrameCacheEntry {
+                    cid: row.get(0)?,
+                    timestamp_ms: row.get(1)?,
+                    options_key: row.get(2)?,
+                    file_name: row.get(3)?,
+                    width: row.get(4)?,
+                    height: row.get(5)?,
+                    byte_size: row.get::<_, i64>(6)? as u64,
+                    hash: row.get(7)?,
+                })
+            })
+            .map_err(|e| LexiError::database("Query error", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| LexiError::database("Query error", e))
+    }
+
+    /// Perceptual hashes already computed for a source, by timestamp in milliseconds
+    pub fn get_frame_hashes(&self, cid: &str) -> Result<Vec<(i64, u64)>, LexiError> {
+        let conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
+
+        let mut stmt = conn
+            .prepare("SELECT timestamp_ms, hash FROM frame_hashes WHERE cid = ?1")
+            .map_err(|e| LexiError::database("Prepare error", e))?;
+
+        // SQLite integers are signed; hashes are stored bit-for-bit
+        let rows = stmt
+            .query_map(params![cid], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)? as u64))
+            })
+            .map_err(|e| LexiError::database("Query error", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| LexiError::database("Query error", e))
+    }
+
+    /// Drop hashes of sources that no longer have any cached frame, so the table
+    /// shrinks along with the frame cache. Returns the number of rows removed.
+    pub fn prune_frame_hashes(&self) -> Result<usize, LexiError> {
+        let conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
+
+        conn.execute(
+            "DELETE FROM frame_hashes WHERE cid NOT IN (SELECT DISTINCT cid FROM frame_cache)",
+            [],
+        )
+        .map_err(|e| LexiError::database("Delete error", e))
+    }
+
+    pub fn set_frame_hashes(&self, cid: &str, hashes: &[(i64, u64)]) -> Result<(), LexiError> {
+        let mut conn = self.conn.lock().map_err(|e| LexiError::database("Lock error", e))?;
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| LexiError::database("Transaction error", e))?;
+        for (timestamp_ms, hash) in hashes {
+            tx.execute(
+                "INSERT OR REPLACE INTO frame_hashes (cid, timestamp_ms, hash) VALUES (?1, ?2, ?3)",
+                params![cid, timestamp_ms, *hash as i64],
+            )
+            .map_err(|e| LexiError::database("Insert error", e))?;
+        }
+        tx.commit()
+            .map_err(|e| LexiError::database("Commit error", e))
+    }
 }
@@ -0,0 +1,199 @@
+use crate::error::LexiError;
+use crate::services::cache_db::{CacheDb, FrameCacheEntry};
+use std::fs;
+use std::path::PathBuf;
+
+/// Default upper bound for frame images kept on disk
+pub const DEFAULT_FRAME_CACHE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Frames evicted per index query while shrinking the cache
+const EVICTION_BATCH: usize = 64;
+
+/// Identifies a cached frame: source, timestamp and encoding options
+pub struct FrameKey<'a> {
+    pub cid: &'a str,
+    pub timestamp: f64,
+    pub options_key: &'a str,
+}
+
+impl FrameKey<'_> {
+    /// Timestamps are keyed at millisecond precision
+    fn timestamp_ms(&self) -> i64 {
+        (self.timestamp * 1000.0).round() as i64
+    }
+}
+
+/// A frame image served from the cache
+pub struct CachedFrame {
+    pub bytes: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub hash: Option<String>,
+}
+
+/// Extracted frame images on disk under the app data dir, indexed in [`CacheDb`]
+/// by source CID, timestamp and encoding options.
+pub struct FrameCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl FrameCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self { dir, max_bytes }
+    }
+
+    /// Path of a frame file by its name in the index; the file may have been evicted
+    pub fn file(&self, file_name: &str) -> PathBuf {
+        self.dir.join(file_name)
+    }
+
+    pub fn get(&self, db: &CacheDb, key: &FrameKey) -> Result<Option<CachedFrame>, LexiError> {
+        let Some(entry) = db.get_cached_frame(key.cid, key.timestamp_ms(), key.options_key)? else {
+            return Ok(None);
+        };
+
+        // The file may have been removed behind our back; drop the stale row
+        match fs::read(self.dir.join(&entry.file_name)) {
+            Ok(bytes) => Ok(Some(CachedFrame {
+                bytes,
+                width: entry.width,
+                height: entry.height,
+                hash: entry.hash,
+            })),
+            Err(_) => {
+                db.remove_cached_frame(&entry)?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn put(
+        &self,
+        db: &CacheDb,
+        key: &FrameKey,
+        extension: &str,
+        bytes: &[u8],
+        (width, height): (u32, u32),
+        hash: Option<&str>,
+    ) -> Result<(), LexiError> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| LexiError::io("Failed to create frame cache dir", &self.dir, e))?;
+
+        let timestamp_ms = key.timestamp_ms();
+        let file_name = format!(
+            "{}_{}_{}.{}",
+            key.cid, timestamp_ms, key.options_key, extension
+        );
+        let path = self.dir.join(&file_name);
+        fs::write(&path, bytes)
+            .map_err(|e| LexiError::io("Failed to write cached frame", &path, e))?;
+
+        db.set_cached_frame(&FrameCacheEntry {
+            cid: key.cid.to_string(),
+            timestamp_ms,
+            options_key: key.options_key.to_string(),
+            file_name,
+            width,
+            height,
+            byte_size: bytes.len() as u64,
+            hash: hash.map(|h| h.to_string()),
+        })
+    }
+
+    /// Delete least recently used frames until the cache fits its size bound, and
+    /// the perceptual hashes of sources left without frames. Returns the bytes freed.
+    pub fn evict(&self, db: &CacheDb) -> Result<u64, LexiError> {
+        let mut size = db.frame_cache_size()?;
+        let mut freed = 0;
+
+        while size > self.max_bytes {
+            let oldest = db.least_recent_frames(EVICTION_BATCH)?;
+            if oldest.is_empty() {
+                break;
+            }
+
+            for entry in oldest {
+                if size <= self.max_bytes {
+                    break;
+                }
+                let _ = fs::remove_file(self.dir.join(&entry.file_name));
+                db.remove_cached_frame(&entry)?;
+                size = size.saturating_sub(entry.byte_size);
+                freed += entry.byte_size;
+            }
+        }
+
+        if freed > 0 {
+            db.prune_frame_hashes()?;
+        }
+        Ok(freed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn key(timestamp: f64, options_key: &str) -> FrameKey<'_> {
+        FrameKey {
+            cid: "cid1",
+            timestamp,
+            options_key,
+        }
+    }
+
+    #[test]
+    fn stores_serves_and_evicts_least_recent_frames() {
+        let dir = TempDir::new().unwrap();
+        let db = CacheDb::init(dir.path().to_path_buf()).unwrap();
+        let cache = FrameCache::new(dir.path().join("frames"), 25);
+
+        cache
+            .put(&db, &key(1.0, "jpg"), "jpg", &[1; 10], (64, 36), Some("00ff"))
+            .unwrap();
+        cache
+            .put(&db, &key(2.0, "jpg"), "jpg", &[2; 10], (64, 36), None)
+            .unwrap();
+
+        let hit = cache.get(&db, &key(1.0004, "jpg")).unwrap().unwrap();
+        assert_eq!(hit.bytes, vec![1; 10]);
+        assert_eq!((hit.width, hit.height, hit.hash.as_deref()), (64, 36, Some("00ff")));
+        assert!(cache.get(&db, &key(1.0, "webp")).unwrap().is_none());
+
+        // Hashes of a source without cached frames go with the next eviction
+        db.set_frame_hashes("cid1", &[(1000, 1)]).unwrap();
+        db.set_frame_hashes("gone", &[(1000, 2)]).unwrap();
+
+        // A third frame takes the cache over its 25 byte bound. All of this happens
+        // within one second; the frame at 2.0 is still the least recently used.
+        cache
+            .put(&db, &key(3.0, "jpg"), "jpg", &[3; 10], (64, 36), None)
+            .unwrap();
+        assert_eq!(cache.evict(&db).unwrap(), 10);
+        assert_eq!(db.frame_cache_size().unwrap(), 20);
+        let kept: Vec<i64> =
+            db.least_recent_frames(10).unwrap().iter().map(|e| e.timestamp_ms).collect();
+        assert_eq!(kept, [1000, 3000]);
+        assert!(cache.get(&db, &key(2.0, "jpg")).unwrap().is_none());
+
+        assert_eq!(db.get_frame_hashes("cid1").unwrap(), [(1000, 1)]);
+        assert!(db.get_frame_hashes("gone").unwrap().is_empty());
+    }
+
+    #[test]
+    fn missing_file_is_a_miss() {
+        let dir = TempDir::new().unwrap();
+        let db = CacheDb::init(dir.path().to_path_buf()).unwrap();
+        let cache = FrameCache::new(dir.path().join("frames"), 1024);
+
+        cache
+            .put(&db, &key(1.0, "jpg"), "jpg", &[1; 10], (64, 36), None)
+            .unwrap();
+        fs::remove_dir_all(dir.path().join("frames")).unwrap();
+
+        assert!(cache.get(&db, &key(1.0, "jpg")).unwrap().is_none());
+        assert_eq!(db.frame_cache_size().unwrap(), 0);
+    }
+}
@@ -0,0 +1,92 @@
+/// dHash compares horizontally adjacent pixels on a 9x8 grayscale grid
+const HASH_WIDTH: usize = 9;
+const HASH_HEIGHT: usize = 8;
+
+/// Size of one raw grayscale frame produced by [`DHASH_FILTER`]
+pub const DHASH_FRAME_BYTES: usize = HASH_WIDTH * HASH_HEIGHT;
+
+/// ffmpeg filter chain that shrinks a frame to the grid [`dhash`] expects
+pub const DHASH_FILTER: &str = "scale=9:8:flags=area,format=gray";
+
+/// 64-bit difference hash of a 9x8 grayscale frame (row-major, one byte per pixel).
+///
+/// Each bit records whether a pixel is brighter than its right neighbour, so the
+/// hash survives re-encoding, small exposure changes and resizing.
+pub fn dhash(pixels: &[u8]) -> Option<u64> {
+    if pixels.len() != DHASH_FRAME_BYTES {
+        return None;
+    }
+
+    let mut hash: u64 = 0;
+    for row in pixels.chunks_exact(HASH_WIDTH) {
+        for pair in row.windows(2) {
+            hash = (hash << 1) | u64::from(pair[0] > pair[1]);
+        }
+    }
+    Some(hash)
+}
+
+/// Number of differing bits; 0-5 is usually the same shot, above 10 a different one
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Hex form exposed to the frontend
+pub fn format_hash(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+/// Keep items whose hash is more than `threshold` bits away from every item kept so
+/// far, so a shot that recurs later in the video is not sampled twice.
+pub fn dedupe_by_hash<T>(items: Vec<T>, hash: impl Fn(&T) -> u64, threshold: u32) -> Vec<T> {
+    let mut kept: Vec<T> = Vec::new();
+    for item in items {
+        let h = hash(&item);
+        if kept
+            .iter()
+            .all(|k| hamming_distance(hash(k), h) > threshold)
+        {
+            kept.push(item);
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(reverse_rows: usize) -> Vec<u8> {
+        (0..HASH_HEIGHT)
+            .flat_map(|row| {
+                (0..HASH_WIDTH).map(move |col| {
+                    let v = (col * 20) as u8;
+                    if row < reverse_rows {
+                        255 - v
+                    } else {
+                        v
+                    }
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dhash_records_brightness_direction() {
+        // Brightening left to right: no pixel is brighter than its right neighbour
+        assert_eq!(dhash(&gradient(0)), Some(0));
+        assert_eq!(dhash(&gradient(HASH_HEIGHT)), Some(u64::MAX));
+        assert_eq!(dhash(&gradient(1)), Some(0xff00_0000_0000_0000));
+        assert_eq!(dhash(&[0; 10]), None);
+    }
+
+    #[test]
+    fn dedupe_keeps_first_of_each_lookalike_group() {
+        let items = vec![(0.0, 0x00u64), (1.0, 0x01), (2.0, 0xff00), (3.0, 0x03), (4.0, 0xff01)];
+        let kept = dedupe_by_hash(items, |(_, h)| *h, 4);
+        assert_eq!(kept, vec![(0.0, 0x00), (2.0, 0xff00)]);
+
+        assert_eq!(format_hash(0xff00), "000000000000ff00");
+        assert_eq!(hamming_distance(0xff00, 0xff01), 1);
+    }
+}
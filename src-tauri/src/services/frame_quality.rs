@@ -0,0 +1,207 @@
+/// Frames are scored on a small fixed-size grayscale copy so thresholds do not
+/// depend on the source resolution
+const QUALITY_WIDTH: usize = 96;
+const QUALITY_HEIGHT: usize = 64;
+
+/// Size of one raw grayscale frame produced by [`QUALITY_FILTER`]
+pub const QUALITY_FRAME_BYTES: usize = QUALITY_WIDTH * QUALITY_HEIGHT;
+
+/// ffmpeg filter chain that shrinks a frame to the grid [`measure_frame`] expects
+pub const QUALITY_FILTER: &str = "scale=96:64:flags=area,format=gray";
+
+/// Image statistics used to reject unusable frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameQuality {
+    /// Mean luma, 0-255
+    pub brightness: f64,
+    /// Standard deviation of luma; near zero for flat frames such as fades
+    pub contrast: f64,
+    /// Variance of the Laplacian; low for blurred frames
+    pub sharpness: f64,
+}
+
+/// Limits a frame has to meet to be worth describing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityThresholds {
+    /// Darker frames are treated as black
+    pub min_brightness: f64,
+    /// Flatter frames are treated as blank (fades, title cards in transition)
+    pub min_contrast: f64,
+    /// Blurrier frames are rejected
+    pub min_sharpness: f64,
+    /// Frames differing this much (mean absolute luma difference) from both
+    /// neighbours are mid-transition or heavily motion blurred
+    pub max_motion: f64,
+}
+
+impl Default for QualityThresholds {
+    fn default() -> Self {
+        Self {
+            min_brightness: 20.0,
+            min_contrast: 10.0,
+            min_sharpness: 15.0,
+            max_motion: 35.0,
+        }
+    }
+}
+
+impl QualityThresholds {
+    /// Why a frame is unusable, or `None` if it passes
+    pub fn rejects(&self, quality: &FrameQuality, motion: f64) -> Option<&'static str> {
+        if quality.brightness < self.min_brightness {
+            Some("black")
+        } else if quality.contrast < self.min_contrast {
+            Some("blank")
+        } else if quality.sharpness < self.min_sharpness {
+            Some("blurry")
+        } else if motion > self.max_motion {
+            Some("transition")
+        } else {
+            None
+        }
+    }
+}
+
+/// Score a 96x64 grayscale frame (row-major, one byte per pixel)
+pub fn measure_frame(pixels: &[u8]) -> Option<FrameQuality> {
+    if pixels.len() != QUALITY_FRAME_BYTES {
+        return None;
+    }
+
+    let n = pixels.len() as f64;
+    let brightness = pixels.iter().map(|&p| p as f64).sum::<f64>() / n;
+    let contrast = (pixels
+        .iter()
+        .map(|&p| (p as f64 - brightness).powi(2))
+        .sum::<f64>()
+        / n)
+        .sqrt();
+
+    let at = |x: usize, y: usize| pixels[y * QUALITY_WIDTH + x] as f64;
+    let mut laplacian: Vec<f64> = Vec::with_capacity((QUALITY_WIDTH - 2) * (QUALITY_HEIGHT - 2));
+    for y in 1..QUALITY_HEIGHT - 1 {
+        for x in 1..QUALITY_WIDTH - 1 {
+            laplacian.push(
+                4.0 * at(x, y) - at(x - 1, y) - at(x + 1, y) - at(x, y - 1) - at(x, y + 1),
+            );
+        }
+    }
+    let mean = laplacian.iter().sum::<f64>() / laplacian.len() as f64;
+    let sharpness =
+        laplacian.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / laplacian.len() as f64;
+
+    Some(FrameQuality {
+        brightness,
+        contrast,
+        sharpness,
+    })
+}
+
+/// Mean absolute luma difference between two frames of the same size
+pub fn frame_difference(a: &[u8], b: &[u8]) -> f64 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter()
+        .zip(b)
+        .map(|(&x, &y)| (x as f64 - y as f64).abs())
+        .sum::<f64>()
+        / a.len() as f64
+}
+
+/// Pick the sharpest usable frame from a window of candidates around `target`,
+/// sorted by time. Ties go to the candidate closest to `target`; `None` if every
+/// candidate is rejected.
+pub fn best_in_window(
+    candidates: &[(f64, &[u8])],
+    target: f64,
+    thresholds: &QualityThresholds,
+) -> Option<f64> {
+    let mut best: Option<(f64, f64)> = None;
+
+    for (i, (timestamp, pixels)) in candidates.iter().enumerate() {
+        let Some(quality) = measure_frame(pixels) else {
+            continue;
+        };
+
+        // A frame mid-transition differs from the frames on both sides of it
+        let before = i.checked_sub(1).map(|j| frame_difference(candidates[j].1, pixels));
+        let after = candidates.get(i + 1).map(|c| frame_difference(pixels, c.1));
+        let motion = match (before, after) {
+            (Some(b), Some(a)) => b.min(a),
+            (Some(d), None) | (None, Some(d)) => d,
+            (None, None) => 0.0,
+        };
+
+        if let Some(reason) = thresholds.rejects(&quality, motion) {
+            println!("[frame_quality] Skipping {} frame at {:.2}s", reason, timestamp);
+            continue;
+        }
+
+        let better = best.is_none_or(|(best_ts, best_sharpness)| {
+            quality.sharpness > best_sharpness
+                || (quality.sharpness == best_sharpness
+                    && (timestamp - target).abs() < (best_ts - target).abs())
+        });
+        if better {
+            best = Some((*timestamp, quality.sharpness));
+        }
+    }
+
+    best.map(|(timestamp, _)| timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checkerboard of `cell`-pixel squares between `low` and `high`
+    fn checkerboard(cell: usize, low: u8, high: u8) -> Vec<u8> {
+        (0..QUALITY_FRAME_BYTES)
+            .map(|i| {
+                let (x, y) = (i % QUALITY_WIDTH, i / QUALITY_WIDTH);
+                if (x / cell + y / cell).is_multiple_of(2) {
+                    low
+                } else {
+                    high
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn measures_brightness_contrast_and_sharpness() {
+        let black = measure_frame(&[4; QUALITY_FRAME_BYTES]).unwrap();
+        assert_eq!((black.brightness, black.contrast, black.sharpness), (4.0, 0.0, 0.0));
+
+        // Fine detail is sharper than the same pattern at a coarser scale
+        let fine = measure_frame(&checkerboard(2, 40, 200)).unwrap();
+        let coarse = measure_frame(&checkerboard(16, 40, 200)).unwrap();
+        assert_eq!(fine.brightness, 120.0);
+        assert!(fine.sharpness > coarse.sharpness * 4.0);
+        assert!(measure_frame(&[0; 10]).is_none());
+    }
+
+    #[test]
+    fn picks_sharpest_usable_frame_in_window() {
+        let thresholds = QualityThresholds::default();
+        let black = vec![0; QUALITY_FRAME_BYTES];
+        let soft = checkerboard(16, 60, 160);
+        let sharp = checkerboard(16, 40, 200);
+
+        // The black target is nudged to the sharpest neighbour
+        let window: Vec<(f64, &[u8])> =
+            vec![(4.5, &soft), (4.75, &sharp), (5.0, &black), (5.25, &soft), (5.5, &soft)];
+        assert_eq!(best_in_window(&window, 5.0, &thresholds), Some(4.75));
+
+        // A frame halfway through a dissolve differs from both sides and is skipped
+        let other = checkerboard(16, 200, 40);
+        let mid: Vec<u8> = soft.iter().zip(&other).map(|(&a, &b)| a / 2 + b / 2).collect();
+        let window: Vec<(f64, &[u8])> =
+            vec![(1.0, &soft), (1.25, &soft), (1.5, &mid), (1.75, &other), (2.0, &other)];
+        assert_eq!(best_in_window(&window, 1.5, &thresholds), Some(1.75));
+
+        let all_black: Vec<(f64, &[u8])> = vec![(1.0, &black), (1.25, &black)];
+        assert_eq!(best_in_window(&all_black, 1.0, &thresholds), None);
+    }
+}
@@ -1,13 +1,84 @@
+use crate::error::LexiError;
 use sha2::{Digest, Sha256};
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
-pub fn compute_file_hash(path: &Path) -> Result<String, String> {
-    if !path.exists() {
-        return Err(format!("File not found: {}", path.display()));
+/// Version tag of sampled CIDs; bump it if the sampling scheme ever changes.
+/// Full CIDs stay untagged SHA-256 hex so caches keyed by them remain valid.
+pub const SAMPLED_CID_PREFIX: &str = "s1-";
+
+/// Bytes hashed at each end of the file, where container headers and indexes live
+const EDGE_SAMPLE_BYTES: u64 = 1024 * 1024;
+
+/// Evenly spaced blocks hashed between the head and tail
+const MIDDLE_SAMPLES: u64 = 32;
+const MIDDLE_SAMPLE_BYTES: u64 = 64 * 1024;
+
+/// Whether a CID came from [`compute_sampled_cid`] and still needs a full hash
+pub fn is_sampled_cid(cid: &str) -> bool {
+    cid.starts_with(SAMPLED_CID_PREFIX)
+}
+
+/// Content ID from the file size plus its head, tail and evenly spaced blocks,
+/// reading about 4 MB whatever the file size. Files small enough to be read in
+/// full are. Returned with [`SAMPLED_CID_PREFIX`].
+pub fn compute_sampled_cid(path: &Path) -> Result<String, LexiError> {
+    let mut file =
+        std::fs::File::open(path).map_err(|e| LexiError::io("Failed to open file", path, e))?;
+    let size = file
+        .metadata()
+        .map_err(|e| LexiError::io("Failed to stat file", path, e))?
+        .len();
+
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+    for (offset, length) in sample_ranges(size) {
+        let mut block = Vec::with_capacity(length as usize);
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| LexiError::io("Failed to seek file", path, e))?;
+        (&mut file)
+            .take(length)
+            .read_to_end(&mut block)
+            .map_err(|e| LexiError::io("Failed to read file", path, e))?;
+        hasher.update(&block);
     }
 
-    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    Ok(format!("{}{}", SAMPLED_CID_PREFIX, hex::encode(hasher.finalize())))
+}
+
+/// Byte ranges read for a sampled CID, in file order and never overlapping
+fn sample_ranges(size: u64) -> Vec<(u64, u64)> {
+    let sampled = 2 * EDGE_SAMPLE_BYTES + MIDDLE_SAMPLES * MIDDLE_SAMPLE_BYTES;
+    if size <= sampled {
+        return vec![(0, size)];
+    }
+
+    let middle_start = EDGE_SAMPLE_BYTES;
+    let middle_len = size - 2 * EDGE_SAMPLE_BYTES;
+    let stride = middle_len / MIDDLE_SAMPLES;
+
+    // Each block sits in the middle of its share of the file
+    let centered = (stride - MIDDLE_SAMPLE_BYTES) / 2;
+    let mut ranges = vec![(0, EDGE_SAMPLE_BYTES)];
+    for i in 0..MIDDLE_SAMPLES {
+        ranges.push((middle_start + i * stride + centered, MIDDLE_SAMPLE_BYTES));
+    }
+    ranges.push((size - EDGE_SAMPLE_BYTES, EDGE_SAMPLE_BYTES));
+    ranges
+}
+
+/// SHA-256 of a file's contents as hex.
+///
+/// `on_progress` is called after every chunk with (bytes read, total bytes) and
+/// can abort the hash by returning an error.
+pub fn compute_file_hash(
+    path: &Path,
+    mut on_progress: impl FnMut(u64, u64) -> Result<(), LexiError>,
+) -> Result<String, LexiError> {
+    let file =
+        std::fs::File::open(path).map_err(|e| LexiError::io("Failed to open file", path, e))?;
+    let total = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let mut read_total: u64 = 0;
 
     let mut reader = std::io::BufReader::with_capacity(1024 * 1024, file);
     let mut hasher = Sha256::new();
@@ -16,14 +87,60 @@ pub fn compute_file_hash(path: &Path) -> Result<String, String> {
     loop {
         let bytes_read = reader
             .read(&mut buffer)
-            .map_err(|e| format!("Failed to read file: {}", e))?;
+            .map_err(|e| LexiError::io("Failed to read file", path, e))?;
 
         if bytes_read == 0 {
             break;
         }
 
         hasher.update(&buffer[..bytes_read]);
+        read_total += bytes_read as u64;
+        on_progress(read_total, total)?;
     }
 
     Ok(hex::encode(hasher.finalize()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = dir.path().join(name);
+        std::fs::File::create(&path).unwrap().write_all(bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn samples_stay_inside_the_file_without_overlapping() {
+        assert_eq!(sample_ranges(1000), vec![(0, 1000)]);
+
+        let size = 50 * 1024 * 1024 * 1024;
+        let ranges = sample_ranges(size);
+        assert_eq!(ranges.len() as u64, MIDDLE_SAMPLES + 2);
+        assert!(ranges.windows(2).all(|w| w[0].0 + w[0].1 <= w[1].0));
+        assert_eq!(ranges.last().unwrap().0 + ranges.last().unwrap().1, size);
+    }
+
+    #[test]
+    fn sampled_cid_tracks_sampled_bytes_and_size() {
+        let dir = TempDir::new().unwrap();
+        let bytes: Vec<u8> = (0..8 * 1024 * 1024u32).map(|i| (i * 31 % 251) as u8).collect();
+        let original = compute_sampled_cid(&write(&dir, "a.mov", &bytes)).unwrap();
+        assert!(is_sampled_cid(&original));
+        assert_eq!(compute_sampled_cid(&write(&dir, "copy.mov", &bytes)).unwrap(), original);
+
+        // A change inside the head is seen; the full hash catches the rest
+        let mut edited = bytes.clone();
+        edited[10] ^= 1;
+        assert_ne!(compute_sampled_cid(&write(&dir, "b.mov", &edited)).unwrap(), original);
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert_ne!(compute_sampled_cid(&write(&dir, "c.mov", truncated)).unwrap(), original);
+
+        let full = compute_file_hash(&dir.path().join("a.mov"), |_, _| Ok(())).unwrap();
+        assert!(!is_sampled_cid(&full));
+    }
+}
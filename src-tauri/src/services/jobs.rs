@@ -0,0 +1,770 @@
+use crate::error::LexiError;
+use crate::services::{ProcessHandle, ProcessOutput, ProcessRunner, ProcessStatus};
+use serde::Serialize;
+use std::future::Future;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+
+/// Default number of ffmpeg/ffprobe processes allowed to run at once
+pub const DEFAULT_MAX_CONCURRENT_FFMPEG: usize = 2;
+
+/// Finished jobs kept around for `list_jobs`
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// How often blocked waits re-check for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Minimum time between two progress events for the same job
+const PROGRESS_THROTTLE: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Not started yet, or waiting for a free ffmpeg slot
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Done | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Hash,
+    ExtractFrames,
+    ExtractClip,
+    ExtractAudio,
+    Transcribe,
+    Sync,
+    Filmstrip,
+    Thumbnail,
+    Silence,
+    Waveform,
+    Proxy,
+    Export,
+    Upload,
+}
+
+/// Snapshot of a job, returned by `list_jobs` and sent with every `job-progress` event
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: JobKind,
+    /// Human-readable description, usually the file being processed
+    pub label: String,
+    pub status: JobStatus,
+    /// Progress in percent (0-100), when known
+    pub percent: Option<f64>,
+    pub message: Option<String>,
+    pub error: Option<LexiError>,
+    #[serde(rename = "createdAt")]
+    pub created_at: u64,
+    #[serde(rename = "finishedAt")]
+    pub finished_at: Option<u64>,
+}
+
+/// A spawned process shared between its job (for cancellation) and the worker
+type SharedProcess = Arc<Mutex<Box<dyn ProcessHandle>>>;
+
+/// Callback used to publish job updates (the app emits them as `job-progress`)
+pub type JobEmitter = Arc<dyn Fn(&JobInfo) + Send + Sync>;
+
+/// Tracks every long-running backend task and limits concurrent ffmpeg processes.
+///
+/// Work runs through [`JobManager::run_blocking`] or [`JobManager::run_async`], which
+/// drive the job through queued -> running -> done/failed/cancelled and hand the
+/// work a [`JobHandle`] for progress reporting, cancellation checks and spawning
+/// ffmpeg under the global process limit.
+#[derive(Clone)]
+pub struct JobManager {
+    inner: Arc<JobsInner>,
+}
+
+struct JobsInner {
+    jobs: Mutex<Vec<JobEntry>>,
+    next_id: AtomicU64,
+    limiter: Arc<ProcessLimiter>,
+    runner: Arc<dyn ProcessRunner>,
+    emitter: JobEmitter,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    control: Arc<JobControl>,
+}
+
+struct JobControl {
+    cancelled: AtomicBool,
+    cancel_tx: watch::Sender<bool>,
+    children: Mutex<Vec<SharedProcess>>,
+    last_progress: Mutex<Option<Instant>>,
+}
+
+impl JobControl {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.cancel_tx.send_replace(true);
+
+        if let Ok(children) = self.children.lock() {
+            for child in children.iter() {
+                if let Ok(mut child) = child.lock() {
+                    let _ = child.kill();
+                }
+            }
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl JobManager {
+    pub fn new(
+        max_concurrent_ffmpeg: usize,
+        runner: Arc<dyn ProcessRunner>,
+        emitter: JobEmitter,
+    ) -> Self {
+        Self {
+            inner: Arc::new(JobsInner {
+                jobs: Mutex::new(Vec::new()),
+                next_id: AtomicU64::new(1),
+                limiter: Arc::new(ProcessLimiter::new(max_concurrent_ffmpeg)),
+                runner,
+                emitter,
+            }),
+        }
+    }
+
+    /// All active jobs plus recently finished ones, oldest first
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.inner
+            .jobs
+            .lock()
+            .map(|jobs| jobs.iter().map(|j| j.info.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Request cancellation of a job; running ffmpeg processes are killed
+    pub fn cancel(&self, job_id: &str) -> Result<JobInfo, LexiError> {
+        let (info, control) = {
+            let mut jobs = self.inner.lock_jobs()?;
+            let entry = jobs
+                .iter_mut()
+                .find(|j| j.info.id == job_id)
+                .ok_or_else(|| LexiError::invalid_input(format!("Unknown job: {}", job_id)))?;
+
+            if entry.info.status.is_finished() {
+                return Ok(entry.info.clone());
+            }
+
+            entry.info.message = Some("Cancelling".to_string());
+            (entry.info.clone(), entry.control.clone())
+        };
+
+        control.cancel();
+        (self.inner.emitter)(&info);
+        Ok(info)
+    }
+
+    pub fn max_concurrent_ffmpeg(&self) -> usize {
+        self.inner.limiter.limit()
+    }
+
+    pub fn set_max_concurrent_ffmpeg(&self, limit: usize) {
+        self.inner.limiter.set_limit(limit);
+    }
+
+    /// Run blocking work as a job on the blocking thread pool
+    pub async fn run_blocking<T, F>(
+        &self,
+        kind: JobKind,
+        label: impl Into<String>,
+        work: F,
+    ) -> Result<T, LexiError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&JobHandle) -> Result<T, LexiError> + Send + 'static,
+    {
+        let job = self.create(kind, label.into());
+        let worker = job.clone();
+
+        let result = tokio::task::spawn_blocking(move || {
+            worker.start()?;
+            work(&worker)
+        })
+        .await
+        .map_err(LexiError::from)
+        .and_then(|r| r);
+
+        job.finish(&result);
+        result
+    }
+
+    /// Run async work as a job; the future is dropped as soon as the job is cancelled
+    pub async fn run_async<T, F, Fut>(
+        &self,
+        kind: JobKind,
+        label: impl Into<String>,
+        work: F,
+    ) -> Result<T, LexiError>
+    where
+        F: FnOnce(JobHandle) -> Fut,
+        Fut: Future<Output = Result<T, LexiError>>,
+    {
+        let job = self.create(kind, label.into());
+
+        let result = match job.start() {
+            Ok(()) => {
+                tokio::select! {
+                    result = work(job.clone()) => result,
+                    _ = job.cancelled() => Err(LexiError::Cancelled),
+                }
+            }
+            Err(e) => Err(e),
+        };
+
+        job.finish(&result);
+        result
+    }
+
+    fn create(&self, kind: JobKind, label: String) -> JobHandle {
+        let id = format!("job-{}", self.inner.next_id.fetch_add(1, Ordering::SeqCst));
+        let (cancel_tx, _) = watch::channel(false);
+        let control = Arc::new(JobControl {
+            cancelled: AtomicBool::new(false),
+            cancel_tx,
+            children: Mutex::new(Vec::new()),
+            last_progress: Mutex::new(None),
+        });
+
+        let info = JobInfo {
+            id: id.clone(),
+            kind,
+            label,
+            status: JobStatus::Queued,
+            percent: None,
+            message: None,
+            error: None,
+            created_at: now_millis(),
+            finished_at: None,
+        };
+
+        if let Ok(mut jobs) = self.inner.jobs.lock() {
+            jobs.push(JobEntry {
+                info: info.clone(),
+                control: control.clone(),
+            });
+        }
+        (self.inner.emitter)(&info);
+
+        JobHandle {
+            id,
+            control,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl JobsInner {
+    fn lock_jobs(&self) -> Result<std::sync::MutexGuard<'_, Vec<JobEntry>>, LexiError> {
+        self.jobs
+            .lock()
+            .map_err(|e| LexiError::internal(format!("Job list lock error: {}", e)))
+    }
+
+    /// Apply `update` to a job's info and publish the new snapshot
+    fn update(&self, job_id: &str, update: impl FnOnce(&mut JobInfo)) {
+        let info = match self.jobs.lock() {
+            Ok(mut jobs) => match jobs.iter_mut().find(|j| j.info.id == job_id) {
+                Some(entry) => {
+                    update(&mut entry.info);
+                    entry.info.clone()
+                }
+                None => return,
+            },
+            Err(_) => return,
+        };
+        (self.emitter)(&info);
+    }
+
+    fn prune_finished(&self) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            let finished = jobs.iter().filter(|j| j.info.status.is_finished()).count();
+            let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+            jobs.retain(|j| {
+                if excess > 0 && j.info.status.is_finished() {
+                    excess -= 1;
+                    return false;
+                }
+                true
+            });
+        }
+    }
+}
+
+/// Handle given to job work for progress, cancellation and process spawning
+#[derive(Clone)]
+pub struct JobHandle {
+    id: String,
+    control: Arc<JobControl>,
+    inner: Arc<JobsInner>,
+}
+
+impl JobHandle {
+    pub fn is_cancelled(&self) -> bool {
+        self.control.is_cancelled()
+    }
+
+    /// Runner for short helper processes (ffprobe metadata) that skip the process limit
+    pub fn runner(&self) -> &dyn ProcessRunner {
+        self.inner.runner.as_ref()
+    }
+
+    /// Returns `Err(LexiError::Cancelled)` once cancellation was requested
+    pub fn check_cancelled(&self) -> Result<(), LexiError> {
+        if self.is_cancelled() {
+            return Err(LexiError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Resolves once the job is cancelled
+    pub async fn cancelled(&self) {
+        let mut rx = self.control.cancel_tx.subscribe();
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+
+    /// Report progress; events are throttled except for completion
+    pub fn progress(&self, percent: f64, message: impl Into<String>) {
+        let percent = percent.clamp(0.0, 100.0);
+
+        if let Ok(mut last) = self.control.last_progress.lock() {
+            let due = last.is_none_or(|t| t.elapsed() >= PROGRESS_THROTTLE);
+            if !due && percent < 100.0 {
+                return;
+            }
+            *last = Some(Instant::now());
+        }
+
+        let message = message.into();
+        self.inner.update(&self.id, |info| {
+            info.percent = Some(percent);
+            info.message = Some(message);
+        });
+    }
+
+    /// Report the current stage without a percentage, for work whose progress is unknown
+    pub fn stage(&self, message: impl Into<String>) {
+        let message = message.into();
+        self.inner.update(&self.id, |info| {
+            info.percent = None;
+            info.message = Some(message);
+        });
+    }
+
+    /// Spawn an ffmpeg/ffprobe process once a global process slot is free.
+    /// The process is killed if the job is cancelled.
+    pub fn spawn(&self, cmd: &mut Command) -> Result<JobChild, LexiError> {
+        let permit = match self.inner.limiter.try_acquire() {
+            Some(permit) => permit,
+            None => {
+                self.set_status(JobStatus::Queued);
+                let permit = self.inner.limiter.acquire(&self.control)?;
+                self.set_status(JobStatus::Running);
+                permit
+            }
+        };
+        let program = cmd.get_program().to_string_lossy().to_string();
+        let child = self
+            .inner
+            .runner
+            .spawn(cmd)
+            .map_err(|e| LexiError::spawn(&program, e))?;
+        let child = Arc::new(Mutex::new(child));
+
+        if let Ok(mut children) = self.control.children.lock() {
+            children.push(child.clone());
+        }
+
+        // Cancellation may have raced with the spawn
+        if self.is_cancelled() {
+            if let Ok(mut child) = child.lock() {
+                let _ = child.kill();
+            }
+        }
+
+        Ok(JobChild {
+            child,
+            control: self.control.clone(),
+            _permit: permit,
+        })
+    }
+
+    /// Run a process to completion and collect its output, like `Command::output`
+    pub fn output(&self, cmd: &mut Command) -> Result<ProcessOutput, LexiError> {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let child = self.spawn(cmd)?;
+
+        let stdout = child.take_stdout();
+        let stdout_reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut stdout) = stdout {
+                let _ = stdout.read_to_end(&mut buf);
+            }
+            buf
+        });
+
+        let mut stderr = Vec::new();
+        if let Some(mut pipe) = child.take_stderr() {
+            let _ = pipe.read_to_end(&mut stderr);
+        }
+
+        let status = child.wait()?;
+        let stdout = stdout_reader.join().unwrap_or_default();
+
+        Ok(ProcessOutput {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+
+    fn start(&self) -> Result<(), LexiError> {
+        self.check_cancelled()?;
+        self.set_status(JobStatus::Running);
+        Ok(())
+    }
+
+    fn set_status(&self, status: JobStatus) {
+        self.inner.update(&self.id, |info| {
+            info.status = status;
+        });
+    }
+
+    fn finish<T>(&self, result: &Result<T, LexiError>) {
+        let cancelled = self.is_cancelled();
+        self.inner.update(&self.id, |info| {
+            info.finished_at = Some(now_millis());
+            match result {
+                Ok(_) => {
+                    info.status = JobStatus::Done;
+                    info.percent = Some(100.0);
+                    info.message = None;
+                }
+                Err(LexiError::Cancelled) => {
+                    info.status = JobStatus::Cancelled;
+                    info.message = None;
+                }
+                Err(_) if cancelled => {
+                    info.status = JobStatus::Cancelled;
+                    info.message = None;
+                }
+                Err(e) => {
+                    info.status = JobStatus::Failed;
+                    info.error = Some(e.clone());
+                }
+            }
+        });
+        self.inner.prune_finished();
+    }
+}
+
+/// A process spawned by a job. Holds one global ffmpeg slot until dropped.
+pub struct JobChild {
+    child: SharedProcess,
+    control: Arc<JobControl>,
+    _permit: ProcessPermit,
+}
+
+impl JobChild {
+    pub fn take_stdout(&self) -> Option<Box<dyn Read + Send>> {
+        self.child.lock().ok().and_then(|mut c| c.take_stdout())
+    }
+
+    pub fn take_stderr(&self) -> Option<Box<dyn Read + Send>> {
+        self.child.lock().ok().and_then(|mut c| c.take_stderr())
+    }
+
+    /// Wait for the process to exit; returns `Err(Cancelled)` if the job was cancelled
+    pub fn wait(&self) -> Result<ProcessStatus, LexiError> {
+        loop {
+            let status = self
+                .child
+                .lock()
+                .map_err(|e| LexiError::internal(format!("Process lock error: {}", e)))?
+                .try_wait()
+                .map_err(|e| LexiError::internal(format!("Process wait failed: {}", e)))?;
+
+            if let Some(status) = status {
+                if self.control.is_cancelled() {
+                    return Err(LexiError::Cancelled);
+                }
+                return Ok(status);
+            }
+
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for JobChild {
+    fn drop(&mut self) {
+        if let Ok(mut children) = self.control.children.lock() {
+            children.retain(|c| !Arc::ptr_eq(c, &self.child));
+        }
+    }
+}
+
+/// Counting semaphore bounding concurrent ffmpeg processes across all jobs
+struct ProcessLimiter {
+    state: Mutex<LimiterState>,
+    available: Condvar,
+}
+
+struct LimiterState {
+    running: usize,
+    limit: usize,
+}
+
+impl ProcessLimiter {
+    fn new(limit: usize) -> Self {
+        Self {
+            state: Mutex::new(LimiterState {
+                running: 0,
+                limit: limit.max(1),
+            }),
+            available: Condvar::new(),
+        }
+    }
+
+    fn limit(&self) -> usize {
+        self.state.lock().map(|s| s.limit).unwrap_or(1)
+    }
+
+    fn set_limit(&self, limit: usize) {
+        if let Ok(mut state) = self.state.lock() {
+            state.limit = limit.max(1);
+        }
+        self.available.notify_all();
+    }
+
+    /// Take a slot if one is free right now
+    fn try_acquire(self: &Arc<Self>) -> Option<ProcessPermit> {
+        let mut state = self.state.lock().ok()?;
+        if state.running < state.limit {
+            state.running += 1;
+            return Some(ProcessPermit {
+                limiter: self.clone(),
+            });
+        }
+        None
+    }
+
+    fn acquire(self: &Arc<Self>, control: &JobControl) -> Result<ProcessPermit, LexiError> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|e| LexiError::internal(format!("Limiter lock error: {}", e)))?;
+
+        loop {
+            if control.is_cancelled() {
+                return Err(LexiError::Cancelled);
+            }
+            if state.running < state.limit {
+                state.running += 1;
+                return Ok(ProcessPermit {
+                    limiter: self.clone(),
+                });
+            }
+            state = self
+                .available
+                .wait_timeout(state, POLL_INTERVAL)
+                .map_err(|e| LexiError::internal(format!("Limiter lock error: {}", e)))?
+                .0;
+        }
+    }
+}
+
+struct ProcessPermit {
+    limiter: Arc<ProcessLimiter>,
+}
+
+impl Drop for ProcessPermit {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.limiter.state.lock() {
+            state.running = state.running.saturating_sub(1);
+        }
+        self.limiter.available.notify_all();
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_jobs, Canned, FakeRunner};
+
+    /// Poll `cond` until it holds; blocking work runs on its own threads meanwhile
+    async fn wait_until(cond: impl Fn() -> bool) {
+        for _ in 0..200 {
+            if cond() {
+                return;
+            }
+            tokio::task::yield_now().await;
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("condition not reached");
+    }
+
+    fn statuses(jobs: &JobManager) -> Vec<JobStatus> {
+        jobs.list().iter().map(|j| j.status).collect()
+    }
+
+    /// Job that runs one ffmpeg process to completion
+    fn encode(jobs: &JobManager) -> tokio::task::JoinHandle<Result<(), LexiError>> {
+        let jobs = jobs.clone();
+        tokio::spawn(async move {
+            jobs.run_blocking(JobKind::Export, "clip.mp4", |job| {
+                let child = job.spawn(&mut Command::new("ffmpeg"))?;
+                child.wait()?;
+                Ok(())
+            })
+            .await
+        })
+    }
+
+    #[tokio::test]
+    async fn cancelling_kills_the_running_process() {
+        let runner = FakeRunner::new();
+        runner.on("ffmpeg", &[], Canned::running_until_killed());
+        let jobs = test_jobs(runner.clone());
+
+        let task = encode(&jobs);
+        wait_until(|| runner.calls_to("ffmpeg").len() == 1).await;
+        assert_eq!(statuses(&jobs), vec![JobStatus::Running]);
+
+        jobs.cancel("job-1").unwrap();
+
+        assert!(matches!(task.await.unwrap(), Err(LexiError::Cancelled)));
+        assert_eq!(runner.kills(), 1);
+        assert_eq!(statuses(&jobs), vec![JobStatus::Cancelled]);
+    }
+
+    #[tokio::test]
+    async fn queues_processes_beyond_the_concurrency_cap() {
+        let runner = FakeRunner::new();
+        runner.on("ffmpeg", &[], Canned::running_until_killed());
+        let jobs = test_jobs(runner.clone());
+        jobs.set_max_concurrent_ffmpeg(1);
+        assert_eq!(jobs.max_concurrent_ffmpeg(), 1);
+
+        let first = encode(&jobs);
+        wait_until(|| runner.calls_to("ffmpeg").len() == 1).await;
+        let second = encode(&jobs);
+        wait_until(|| statuses(&jobs) == [JobStatus::Running, JobStatus::Queued]).await;
+        assert_eq!(runner.calls_to("ffmpeg").len(), 1);
+
+        // Raising the cap lets the queued job start
+        jobs.set_max_concurrent_ffmpeg(2);
+        wait_until(|| runner.calls_to("ffmpeg").len() == 2).await;
+        assert_eq!(statuses(&jobs), vec![JobStatus::Running, JobStatus::Running]);
+
+        jobs.cancel("job-1").unwrap();
+        jobs.cancel("job-2").unwrap();
+        assert!(matches!(first.await.unwrap(), Err(LexiError::Cancelled)));
+        assert!(matches!(second.await.unwrap(), Err(LexiError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn publishes_status_changes() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let jobs = JobManager::new(
+            2,
+            FakeRunner::new(),
+            Arc::new(move |info: &JobInfo| {
+                sink.lock().unwrap().push((info.id.clone(), info.status, info.percent));
+            }),
+        );
+
+        jobs.run_blocking(JobKind::Hash, "a.mp4", |job| {
+            job.progress(40.0, "Hashing");
+            job.stage("Verifying");
+            Ok(())
+        })
+        .await
+        .unwrap();
+        let failed = jobs
+            .run_async(JobKind::Upload, "b.mp4", |_| async {
+                Err::<(), _>(LexiError::invalid_input("no file"))
+            })
+            .await;
+        assert!(failed.is_err());
+
+        let events = events.lock().unwrap().clone();
+        let done: Vec<_> = events.iter().filter(|e| e.0 == "job-1").map(|e| (e.1, e.2)).collect();
+        assert_eq!(
+            done,
+            vec![
+                (JobStatus::Queued, None),
+                (JobStatus::Running, None),
+                (JobStatus::Running, Some(40.0)),
+                (JobStatus::Running, None),
+                (JobStatus::Done, Some(100.0)),
+            ]
+        );
+        let failed: Vec<_> = events.iter().filter(|e| e.0 == "job-2").map(|e| e.1).collect();
+        assert_eq!(failed, vec![JobStatus::Queued, JobStatus::Running, JobStatus::Failed]);
+        assert!(matches!(jobs.list()[1].error, Some(LexiError::InvalidInput { .. })));
+    }
+
+    #[tokio::test]
+    async fn prunes_the_oldest_finished_jobs() {
+        let jobs = test_jobs(FakeRunner::new());
+
+        // Still running while the others finish, so it must survive pruning
+        let active = jobs.clone();
+        let pending = tokio::spawn(async move {
+            active
+                .run_async(JobKind::Transcribe, "long.mp4", |job| async move {
+                    job.cancelled().await;
+                    Err::<(), _>(LexiError::Cancelled)
+                })
+                .await
+        });
+        wait_until(|| jobs.list().len() == 1).await;
+
+        for i in 0..MAX_FINISHED_JOBS + 5 {
+            jobs.run_async(JobKind::Hash, format!("{}.mp4", i), |_| async { Ok(()) })
+                .await
+                .unwrap();
+        }
+
+        let list = jobs.list();
+        assert_eq!(list.len(), MAX_FINISHED_JOBS + 1);
+        assert_eq!(list[0].id, "job-1");
+        assert_eq!(list[0].status, JobStatus::Running);
+        assert_eq!(list[1].id, "job-7");
+
+        jobs.cancel("job-1").unwrap();
+        assert!(matches!(pending.await.unwrap(), Err(LexiError::Cancelled)));
+    }
+}
@@ -1,7 +1,35 @@
+mod artifacts;
+mod audio_sync;
 mod cache_db;
+mod frame_cache;
+mod frame_hash;
+mod frame_quality;
 mod hash;
+mod jobs;
+mod probe;
+mod runner;
 mod thumbnail;
+mod transcription;
+mod waveform;
 
+pub use artifacts::ArtifactStore;
+pub use audio_sync::{align_envelopes, onset_envelope, SYNC_SAMPLE_RATE};
 pub use cache_db::CacheDb;
-pub use hash::compute_file_hash;
-pub use thumbnail::{extract_thumbnail, get_video_dimensions, get_video_duration};
+pub use frame_cache::{FrameCache, FrameKey, DEFAULT_FRAME_CACHE_BYTES};
+pub use frame_hash::{dedupe_by_hash, dhash, format_hash, DHASH_FILTER, DHASH_FRAME_BYTES};
+pub use frame_quality::{best_in_window, QualityThresholds, QUALITY_FILTER, QUALITY_FRAME_BYTES};
+pub use hash::{compute_file_hash, compute_sampled_cid, is_sampled_cid};
+pub use jobs::{
+    JobHandle, JobInfo, JobKind, JobManager, DEFAULT_MAX_CONCURRENT_FFMPEG,
+};
+pub use probe::{get_video_duration, probe_media, probe_media_cached, video_dimensions, MediaInfo};
+pub use runner::{
+    ProcessHandle, ProcessOutput, ProcessRunner, ProcessStatus, SystemRunner,
+};
+pub use thumbnail::{generate_thumbnails, Thumbnails};
+pub use transcription::{
+    parse_legacy_transcript, transcript_data_type, AudioUpload, ElevenLabsProvider,
+    OpenAiCompatibleProvider, Transcript, TranscriptWord, TranscriptionProvider,
+    DEFAULT_ELEVENLABS_MODEL, LEGACY_DATA_TYPE_TRANSCRIPTION,
+};
+pub use waveform::{Waveform, WaveformBuilder, WAVEFORM_SAMPLE_RATE};
@@ -0,0 +1,539 @@
+use crate::error::LexiError;
+use crate::services::{CacheDb, ProcessRunner};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// Cache data type used for probe results
+const DATA_TYPE_MEDIA_INFO: &str = "media_info";
+
+/// Full metadata for a media file, as reported by ffprobe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaInfo {
+    /// Container format name (e.g. "mov,mp4,m4a,3gp,3g2,mj2")
+    #[serde(rename = "formatName")]
+    pub format_name: String,
+    /// Duration in seconds
+    pub duration: f64,
+    /// Container start time in seconds (nonzero for e.g. MPEG-TS). Timestamps in the
+    /// app are relative to it; ffmpeg filters see absolute times.
+    #[serde(rename = "startTime")]
+    pub start_time: f64,
+    /// File size in bytes
+    pub size: Option<u64>,
+    /// Overall bit rate in bits per second
+    #[serde(rename = "bitRate")]
+    pub bit_rate: Option<u64>,
+    /// Creation time from container or stream tags (ISO 8601)
+    #[serde(rename = "creationTime")]
+    pub creation_time: Option<String>,
+    /// Start timecode from container or stream tags (HH:MM:SS:FF)
+    pub timecode: Option<String>,
+    /// First video stream, if any
+    pub video: Option<VideoStreamInfo>,
+    /// First audio stream, if any
+    pub audio: Option<AudioStreamInfo>,
+    /// Number of streams of any type in the container
+    #[serde(rename = "streamCount")]
+    pub stream_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoStreamInfo {
+    pub index: u32,
+    pub codec: String,
+    pub profile: Option<String>,
+    /// Coded width, before rotation
+    pub width: u32,
+    /// Coded height, before rotation
+    pub height: u32,
+    /// Width as displayed, with rotation applied
+    #[serde(rename = "displayWidth")]
+    pub display_width: u32,
+    /// Height as displayed, with rotation applied
+    #[serde(rename = "displayHeight")]
+    pub display_height: u32,
+    #[serde(rename = "pixelFormat")]
+    pub pixel_format: Option<String>,
+    #[serde(rename = "bitDepth")]
+    pub bit_depth: Option<u32>,
+    /// Nominal frame rate (r_frame_rate)
+    #[serde(rename = "frameRate")]
+    pub frame_rate: Option<f64>,
+    /// Average frame rate over the whole stream (avg_frame_rate)
+    #[serde(rename = "avgFrameRate")]
+    pub avg_frame_rate: Option<f64>,
+    /// True when the nominal and average frame rates disagree
+    #[serde(rename = "isVfr")]
+    pub is_vfr: bool,
+    /// Clockwise rotation needed for display, normalized to 0, 90, 180 or 270
+    pub rotation: u32,
+    #[serde(rename = "bitRate")]
+    pub bit_rate: Option<u64>,
+    pub duration: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioStreamInfo {
+    pub index: u32,
+    pub codec: String,
+    pub channels: u32,
+    #[serde(rename = "channelLayout")]
+    pub channel_layout: Option<String>,
+    #[serde(rename = "sampleRate")]
+    pub sample_rate: u32,
+    #[serde(rename = "sampleFormat")]
+    pub sample_format: Option<String>,
+    #[serde(rename = "bitDepth")]
+    pub bit_depth: Option<u32>,
+    #[serde(rename = "bitRate")]
+    pub bit_rate: Option<u64>,
+    pub duration: Option<f64>,
+}
+
+// --- Raw ffprobe JSON ---
+
+#[derive(Debug, Deserialize)]
+struct FfprobeOutput {
+    #[serde(default)]
+    streams: Vec<FfprobeStream>,
+    format: Option<FfprobeFormat>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FfprobeStream {
+    index: u32,
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    profile: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    pix_fmt: Option<String>,
+    r_frame_rate: Option<String>,
+    avg_frame_rate: Option<String>,
+    bits_per_raw_sample: Option<String>,
+    bits_per_sample: Option<u32>,
+    sample_fmt: Option<String>,
+    sample_rate: Option<String>,
+    channels: Option<u32>,
+    channel_layout: Option<String>,
+    bit_rate: Option<String>,
+    duration: Option<String>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    #[serde(default)]
+    side_data_list: Vec<FfprobeSideData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FfprobeSideData {
+    side_data_type: Option<String>,
+    rotation: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FfprobeFormat {
+    format_name: Option<String>,
+    duration: Option<String>,
+    start_time: Option<String>,
+    size: Option<String>,
+    bit_rate: Option<String>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+/// Probe a media file with a single ffprobe call
+pub fn probe_media(runner: &dyn ProcessRunner, path: &Path) -> Result<MediaInfo, LexiError> {
+    let output = runner
+        .output(Command::new("ffprobe").args([
+            "-v", "error",
+            "-show_streams",
+            "-show_format",
+            "-of", "json",
+            &path.to_string_lossy(),
+        ]))
+        .map_err(|e| LexiError::spawn("ffprobe", e))?;
+
+    if !output.status.success() {
+        return Err(LexiError::ffmpeg_failed(
+            &format!("ffprobe failed for {}", path.display()),
+            &output.stderr,
+        ));
+    }
+
+    parse_ffprobe_json(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Probe a media file, serving and storing the result in the cache when a CID is known
+pub fn probe_media_cached(
+    runner: &dyn ProcessRunner,
+    path: &Path,
+    cid: Option<&str>,
+    cache_db: &CacheDb,
+) -> Result<MediaInfo, LexiError> {
+    if let Some(cid) = cid {
+        if let Some(json) = cache_db.get_cached(cid, DATA_TYPE_MEDIA_INFO)? {
+            if let Ok(info) = serde_json::from_str::<MediaInfo>(&json) {
+                return Ok(info);
+            }
+        }
+    }
+
+    let info = probe_media(runner, path)?;
+
+    if let Some(cid) = cid {
+        let json = serde_json::to_string(&info)
+            .map_err(|e| LexiError::serialization("Failed to serialize media info", e))?;
+        cache_db.set_cached(cid, DATA_TYPE_MEDIA_INFO, &json)?;
+    }
+
+    Ok(info)
+}
+
+fn parse_ffprobe_json(json: &str) -> Result<MediaInfo, LexiError> {
+    let raw: FfprobeOutput = serde_json::from_str(json)
+        .map_err(|e| LexiError::serialization("Failed to parse ffprobe output", e))?;
+
+    let format = raw
+        .format
+        .ok_or_else(|| LexiError::internal("ffprobe output has no format section"))?;
+    let stream_count = raw.streams.len();
+
+    let video_stream = raw
+        .streams
+        .iter()
+        .find(|s| s.codec_type.as_deref() == Some("video"));
+    let audio_stream = raw
+        .streams
+        .iter()
+        .find(|s| s.codec_type.as_deref() == Some("audio"));
+
+    let video = video_stream.map(to_video_info);
+    let audio = audio_stream.map(to_audio_info);
+
+    // Container duration, falling back to the longest stream duration
+    let duration = parse_num::<f64>(&format.duration)
+        .or_else(|| {
+            raw.streams
+                .iter()
+                .filter_map(|s| parse_num::<f64>(&s.duration))
+                .reduce(f64::max)
+        })
+        .ok_or_else(|| LexiError::internal("ffprobe reported no duration"))?;
+
+    let stream_tag = |key: &str| {
+        video_stream
+            .into_iter()
+            .chain(audio_stream)
+            .find_map(|s| s.tags.get(key).cloned())
+    };
+
+    let creation_time = format
+        .tags
+        .get("creation_time")
+        .cloned()
+        .or_else(|| stream_tag("creation_time"));
+
+    // Timecode lives on the format, the video stream or a dedicated data (tmcd) stream
+    let timecode = format
+        .tags
+        .get("timecode")
+        .cloned()
+        .or_else(|| stream_tag("timecode"))
+        .or_else(|| raw.streams.iter().find_map(|s| s.tags.get("timecode").cloned()));
+
+    Ok(MediaInfo {
+        format_name: format.format_name.unwrap_or_default(),
+        duration,
+        start_time: parse_num(&format.start_time).unwrap_or(0.0),
+        size: parse_num(&format.size),
+        bit_rate: parse_num(&format.bit_rate),
+        creation_time,
+        timecode,
+        video,
+        audio,
+        stream_count,
+    })
+}
+
+fn to_video_info(stream: &FfprobeStream) -> VideoStreamInfo {
+    let frame_rate = stream.r_frame_rate.as_deref().and_then(parse_rational);
+    let avg_frame_rate = stream.avg_frame_rate.as_deref().and_then(parse_rational);
+
+    // A container's nominal rate is the finest timebase seen; if frames arrive at a
+    // measurably different average rate, the stream is variable frame rate
+    let is_vfr = match (frame_rate, avg_frame_rate) {
+        (Some(r), Some(avg)) => (r - avg).abs() / r > 0.01,
+        _ => false,
+    };
+
+    let bit_depth = parse_num::<u32>(&stream.bits_per_raw_sample)
+        .or_else(|| stream.pix_fmt.as_deref().map(pix_fmt_bit_depth));
+
+    let rotation = stream_rotation(stream);
+    let width = stream.width.unwrap_or(0);
+    let height = stream.height.unwrap_or(0);
+    let (display_width, display_height) = if rotation % 180 == 90 {
+        (height, width)
+    } else {
+        (width, height)
+    };
+
+    VideoStreamInfo {
+        index: stream.index,
+        codec: stream.codec_name.clone().unwrap_or_default(),
+        profile: stream.profile.clone(),
+        width,
+        height,
+        display_width,
+        display_height,
+        pixel_format: stream.pix_fmt.clone(),
+        bit_depth,
+        frame_rate,
+        avg_frame_rate,
+        is_vfr,
+        rotation,
+        bit_rate: parse_num(&stream.bit_rate),
+        duration: parse_num(&stream.duration),
+    }
+}
+
+/// Clockwise display rotation of a video stream.
+///
+/// Phones record in sensor orientation and mark portrait clips with a display
+/// matrix (counter-clockwise degrees, e.g. -90) and, in older muxers, a `rotate`
+/// tag (clockwise degrees, e.g. 90). The display matrix wins when both exist.
+fn stream_rotation(stream: &FfprobeStream) -> u32 {
+    let matrix_rotation = stream
+        .side_data_list
+        .iter()
+        .filter(|sd| sd.side_data_type.as_deref() == Some("Display Matrix"))
+        .find_map(|sd| sd.rotation)
+        .map(|r| -r);
+
+    let tag_rotation = stream
+        .tags
+        .get("rotate")
+        .and_then(|r| r.trim().parse::<f64>().ok());
+
+    matrix_rotation
+        .or(tag_rotation)
+        .map(normalize_rotation)
+        .unwrap_or(0)
+}
+
+/// Snap an arbitrary angle in degrees to 0, 90, 180 or 270 clockwise
+fn normalize_rotation(degrees: f64) -> u32 {
+    let quarter_turns = (degrees / 90.0).round() as i64;
+    (quarter_turns.rem_euclid(4) * 90) as u32
+}
+
+fn to_audio_info(stream: &FfprobeStream) -> AudioStreamInfo {
+    let bit_depth = parse_num::<u32>(&stream.bits_per_raw_sample)
+        .filter(|&b| b > 0)
+        .or(stream.bits_per_sample.filter(|&b| b > 0))
+        .or_else(|| stream.sample_fmt.as_deref().and_then(sample_fmt_bit_depth));
+
+    AudioStreamInfo {
+        index: stream.index,
+        codec: stream.codec_name.clone().unwrap_or_default(),
+        channels: stream.channels.unwrap_or(0),
+        channel_layout: stream.channel_layout.clone(),
+        sample_rate: parse_num(&stream.sample_rate).unwrap_or(0),
+        sample_format: stream.sample_fmt.clone(),
+        bit_depth,
+        bit_rate: parse_num(&stream.bit_rate),
+        duration: parse_num(&stream.duration),
+    }
+}
+
+fn parse_num<T: std::str::FromStr>(value: &Option<String>) -> Option<T> {
+    value.as_deref().and_then(|v| v.trim().parse().ok())
+}
+
+/// Parse an ffprobe rational such as "30000/1001"; "0/0" yields None
+fn parse_rational(value: &str) -> Option<f64> {
+    let (num, den) = value.split_once('/')?;
+    let num: f64 = num.trim().parse().ok()?;
+    let den: f64 = den.trim().parse().ok()?;
+    if num <= 0.0 || den <= 0.0 {
+        return None;
+    }
+    Some(num / den)
+}
+
+/// Infer bit depth from a pixel format name (yuv420p10le -> 10)
+fn pix_fmt_bit_depth(pix_fmt: &str) -> u32 {
+    for depth in [16, 14, 12, 10, 9] {
+        if pix_fmt.contains(&format!("p{}", depth)) || pix_fmt.ends_with(&format!("{}le", depth))
+        {
+            return depth;
+        }
+    }
+    8
+}
+
+fn sample_fmt_bit_depth(sample_fmt: &str) -> Option<u32> {
+    match sample_fmt.trim_end_matches('p') {
+        "u8" => Some(8),
+        "s16" => Some(16),
+        "s32" | "flt" => Some(32),
+        "s64" | "dbl" => Some(64),
+        _ => None,
+    }
+}
+
+/// Video duration in seconds, from the probe cache when a CID is known
+pub fn get_video_duration(
+    runner: &dyn ProcessRunner,
+    video_path: &Path,
+    cid: Option<&str>,
+    cache_db: &CacheDb,
+) -> Result<f64, LexiError> {
+    Ok(probe_media_cached(runner, video_path, cid, cache_db)?.duration)
+}
+
+/// Display dimensions of the first video stream of already-probed media
+pub fn video_dimensions(info: &MediaInfo) -> Result<(u32, u32), LexiError> {
+    info.video
+        .as_ref()
+        .map(|v| (v.display_width, v.display_height))
+        .ok_or_else(|| LexiError::invalid_input("No video stream found"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{probe_json, Canned, FakeRunner};
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn video_stream(extra: serde_json::Value) -> serde_json::Value {
+        let mut stream = json!({
+            "index": 0,
+            "codec_type": "video",
+            "codec_name": "h264",
+            "width": 1920,
+            "height": 1080,
+            "pix_fmt": "yuv420p",
+            "r_frame_rate": "30/1",
+            "avg_frame_rate": "30/1"
+        });
+        stream.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        stream
+    }
+
+    fn parse(streams: Vec<serde_json::Value>, format: serde_json::Value) -> MediaInfo {
+        let output = json!({ "streams": streams, "format": format });
+        parse_ffprobe_json(&output.to_string()).unwrap()
+    }
+
+    fn parse_video(extra: serde_json::Value) -> VideoStreamInfo {
+        parse(vec![video_stream(extra)], json!({ "duration": "10.0" })).video.unwrap()
+    }
+
+    #[test]
+    fn tells_variable_from_constant_frame_rate() {
+        let ntsc = "30000/1001";
+        let cfr = parse_video(json!({ "r_frame_rate": ntsc, "avg_frame_rate": ntsc }));
+        assert!(!cfr.is_vfr);
+        assert!((cfr.frame_rate.unwrap() - 29.97).abs() < 0.01);
+
+        // Phones report the timebase as the nominal rate while frames average lower
+        let vfr = parse_video(json!({ "r_frame_rate": "60/1", "avg_frame_rate": "2997/100" }));
+        assert!(vfr.is_vfr);
+
+        let unknown = parse_video(json!({ "avg_frame_rate": "0/0" }));
+        assert_eq!(unknown.avg_frame_rate, None);
+        assert!(!unknown.is_vfr);
+    }
+
+    #[test]
+    fn reads_bit_depth_from_high_bit_depth_pixel_formats() {
+        assert_eq!(pix_fmt_bit_depth("yuv420p"), 8);
+        assert_eq!(pix_fmt_bit_depth("yuv420p10le"), 10);
+        assert_eq!(pix_fmt_bit_depth("yuv422p10be"), 10);
+        assert_eq!(pix_fmt_bit_depth("p010le"), 10);
+        assert_eq!(pix_fmt_bit_depth("yuv444p12le"), 12);
+
+        let hdr = parse_video(json!({ "pix_fmt": "yuv420p10le" }));
+        assert_eq!(hdr.bit_depth, Some(10));
+        // bits_per_raw_sample wins when ffprobe reports it
+        let raw = parse_video(json!({ "pix_fmt": "yuv420p", "bits_per_raw_sample": "12" }));
+        assert_eq!(raw.bit_depth, Some(12));
+    }
+
+    #[test]
+    fn parses_audio_only_input() {
+        let audio = json!({
+            "index": 0,
+            "codec_type": "audio",
+            "codec_name": "pcm_s24le",
+            "sample_rate": "48000",
+            "channels": 2,
+            "bits_per_sample": 24,
+            "sample_fmt": "s32"
+        });
+        let info = parse(vec![audio], json!({ "format_name": "wav", "duration": "62.5" }));
+
+        assert!(info.video.is_none());
+        assert!(video_dimensions(&info).is_err());
+        assert_eq!(info.duration, 62.5);
+        let audio = info.audio.unwrap();
+        assert_eq!((audio.sample_rate, audio.channels), (48000, 2));
+        assert_eq!(audio.bit_depth, Some(24));
+    }
+
+    #[test]
+    fn falls_back_to_stream_duration_when_the_container_has_none() {
+        let streams = vec![
+            video_stream(json!({ "duration": "9.5" })),
+            json!({ "index": 1, "codec_type": "audio", "duration": "10.02" }),
+        ];
+        let info = parse(streams, json!({ "format_name": "matroska,webm" }));
+        assert_eq!(info.duration, 10.02);
+        assert_eq!(info.start_time, 0.0);
+
+        let output = json!({ "streams": [video_stream(json!({}))], "format": {} });
+        assert!(parse_ffprobe_json(&output.to_string()).is_err());
+    }
+
+    #[test]
+    fn applies_display_matrix_rotation() {
+        // The display matrix is counter-clockwise; -90 is a portrait phone clip
+        let portrait = parse_video(json!({
+            "side_data_list": [{ "side_data_type": "Display Matrix", "rotation": -90 }]
+        }));
+        assert_eq!(portrait.rotation, 90);
+        assert_eq!((portrait.width, portrait.height), (1920, 1080));
+        assert_eq!((portrait.display_width, portrait.display_height), (1080, 1920));
+
+        // The matrix wins over an older rotate tag
+        let both = parse_video(json!({
+            "tags": { "rotate": "90" },
+            "side_data_list": [{ "side_data_type": "Display Matrix", "rotation": 180 }]
+        }));
+        assert_eq!(both.rotation, 180);
+        assert_eq!((both.display_width, both.display_height), (1920, 1080));
+
+        let tagged = parse_video(json!({ "tags": { "rotate": "270" } }));
+        assert_eq!(tagged.rotation, 270);
+        assert_eq!(normalize_rotation(-450.0), 270);
+    }
+
+    #[test]
+    fn duration_comes_from_the_probe_cache() {
+        let dir = TempDir::new().unwrap();
+        let db = CacheDb::init(dir.path().to_path_buf()).unwrap();
+        let runner = FakeRunner::new();
+        runner.on("ffprobe", &[], Canned::stdout(&probe_json(12.0, 1280, 720, 0)));
+
+        for _ in 0..2 {
+            let duration =
+                get_video_duration(runner.as_ref(), Path::new("/media/a.mp4"), Some("cid"), &db);
+            assert_eq!(duration.unwrap(), 12.0);
+        }
+        assert_eq!(runner.calls_to("ffprobe").len(), 1);
+    }
+}
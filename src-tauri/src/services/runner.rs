@@ -0,0 +1,113 @@
+use std::io::{self, Read};
+use std::process::{Child, Command, Stdio};
+
+/// Exit status of a process started through a [`ProcessRunner`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessStatus {
+    code: Option<i32>,
+}
+
+impl ProcessStatus {
+    pub fn from_code(code: Option<i32>) -> Self {
+        Self { code }
+    }
+
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// Collected output of a finished process, like `std::process::Output`
+#[derive(Debug, Clone)]
+pub struct ProcessOutput {
+    pub status: ProcessStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// A process started by a [`ProcessRunner`]
+pub trait ProcessHandle: Send {
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>>;
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>>;
+    fn try_wait(&mut self) -> io::Result<Option<ProcessStatus>>;
+    fn kill(&mut self) -> io::Result<()>;
+}
+
+/// Starts external programs (ffmpeg/ffprobe).
+///
+/// Production code uses [`SystemRunner`]; tests inject a fake that records the
+/// arguments and replays canned output.
+pub trait ProcessRunner: Send + Sync {
+    fn spawn(&self, cmd: &mut Command) -> io::Result<Box<dyn ProcessHandle>>;
+
+    /// Run a process to completion and collect its output
+    fn output(&self, cmd: &mut Command) -> io::Result<ProcessOutput> {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let mut process = self.spawn(cmd)?;
+
+        let stdout = process.take_stdout();
+        let stdout_reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut stdout) = stdout {
+                let _ = stdout.read_to_end(&mut buf);
+            }
+            buf
+        });
+
+        let mut stderr = Vec::new();
+        if let Some(mut pipe) = process.take_stderr() {
+            let _ = pipe.read_to_end(&mut stderr);
+        }
+
+        let status = loop {
+            if let Some(status) = process.try_wait()? {
+                break status;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+
+        Ok(ProcessOutput {
+            status,
+            stdout: stdout_reader.join().unwrap_or_default(),
+            stderr,
+        })
+    }
+}
+
+/// Runs real processes with `std::process::Command`
+pub struct SystemRunner;
+
+impl ProcessRunner for SystemRunner {
+    fn spawn(&self, cmd: &mut Command) -> io::Result<Box<dyn ProcessHandle>> {
+        Ok(Box::new(SystemProcess(cmd.spawn()?)))
+    }
+}
+
+struct SystemProcess(Child);
+
+impl ProcessHandle for SystemProcess {
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.0
+            .stdout
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send>)
+    }
+
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.0
+            .stderr
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send>)
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<ProcessStatus>> {
+        Ok(self
+            .0
+            .try_wait()?
+            .map(|status| ProcessStatus::from_code(status.code())))
+    }
+
+    fn kill(&mut self) -> io::Result<()> {
+        self.0.kill()
+    }
+}
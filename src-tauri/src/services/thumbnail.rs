@@ -1,120 +1,253 @@
-use base64::{engine::general_purpose::STANDARD, Engine};
-use std::path::Path;
+use crate::error::LexiError;
+use crate::services::{
+    best_in_window, ArtifactStore, JobHandle, QualityThresholds, QUALITY_FILTER,
+    QUALITY_FRAME_BYTES,
+};
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
 use std::process::Command;
-use tempfile::NamedTempFile;
 
-pub fn extract_thumbnail(video_path: &Path) -> Result<String, String> {
-    let output_file = NamedTempFile::with_suffix(".jpg").map_err(|e| e.to_string())?;
-    let output_path = output_file.path().to_string_lossy().to_string();
-    let video_path_str = video_path.to_string_lossy();
+const THUMBNAIL_KIND: &str = "thumbnails";
 
-    if !try_extract_at_timestamp(&video_path_str, &output_path, "1")? {
-        // Fallback: extract first frame for short videos
-        if !try_extract_at_timestamp(&video_path_str, &output_path, "0")? {
-            return Err("Failed to extract thumbnail".to_string());
-        }
-    }
-
-    encode_image_to_data_uri(&output_path)
-}
+/// Only the start of a clip is searched for a thumbnail
+const SEARCH_SECONDS: f64 = 10.0;
 
-fn try_extract_at_timestamp(
-    video_path: &str,
-    output_path: &str,
-    timestamp: &str,
-) -> Result<bool, String> {
-    let mut args = vec!["-i", video_path];
-
-    if timestamp != "0" {
-        args.insert(0, timestamp);
-        args.insert(0, "-ss");
-    }
+/// Candidate frames scored per second of the search
+const SEARCH_FPS: f64 = 2.0;
 
-    args.extend([
-        "-frames:v",
-        "1",
-        "-vf",
-        "scale=320:-1",
-        "-q:v",
-        "5",
-        "-y",
-        output_path,
-    ]);
+/// Preferred time when candidates score the same, as before scoring existed
+const PREFERRED_TIMESTAMP: f64 = 1.0;
 
-    let output = Command::new("ffmpeg")
-        .args(&args)
-        .output()
-        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+/// Output widths; frames narrower than this are not upscaled
+const SIZES: [(&str, u32); 3] = [("small", 160), ("medium", 320), ("large", 640)];
 
-    Ok(output.status.success())
+/// Thumbnail files under the app data dir (load through the asset protocol)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Thumbnails {
+    pub small: String,
+    pub medium: String,
+    pub large: String,
 }
 
-fn encode_image_to_data_uri(path: &str) -> Result<String, String> {
-    let image_data = std::fs::read(path).map_err(|e| e.to_string())?;
-    let base64_data = STANDARD.encode(&image_data);
-    Ok(format!("data:image/jpeg;base64,{}", base64_data))
+/// Small, medium and large thumbnails of the sharpest well-exposed frame near the
+/// start of the video, so clips opening on black or a slate get a useful picture.
+/// Files are keyed by CID and reused on later calls.
+pub fn generate_thumbnails(
+    job: &JobHandle,
+    video_path: &str,
+    cid: &str,
+    artifacts: &ArtifactStore,
+) -> Result<Thumbnails, LexiError> {
+    let paths = SIZES
+        .iter()
+        .map(|(name, _)| artifacts.path(THUMBNAIL_KIND, cid, &format!("{}.jpg", name)))
+        .collect::<Result<Vec<PathBuf>, LexiError>>()?;
+
+    if !paths.iter().all(|p| p.exists()) {
+        let timestamp = pick_thumbnail_timestamp(job, video_path)?;
+        println!(
+            "[thumbnail] Using frame at {:.1}s of {}",
+            timestamp, video_path
+        );
+
+        // Fallback: first frame, for very short videos
+        if !try_extract_at_timestamp(job, video_path, &paths, timestamp)?
+            && !try_extract_at_timestamp(job, video_path, &paths, 0.0)?
+        {
+            return Err(LexiError::FfmpegFailed {
+                context: format!("Failed to extract thumbnail from {}", video_path),
+                stderr: String::new(),
+            });
+        }
+    }
+
+    let [small, medium, large] = [0, 1, 2].map(|i| paths[i].to_string_lossy().to_string());
+    Ok(Thumbnails {
+        small,
+        medium,
+        large,
+    })
 }
 
-/// Extract video duration in seconds using ffprobe
-pub fn get_video_duration(video_path: &Path) -> Result<f64, String> {
-    let output = Command::new("ffprobe")
-        .args([
-            "-v", "error",
-            "-show_entries", "format=duration",
-            "-of", "default=noprint_wrappers=1:nokey=1",
-            &video_path.to_string_lossy(),
-        ])
-        .output()
-        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+/// Score frames from the first seconds and pick the sharpest usable one
+fn pick_thumbnail_timestamp(job: &JobHandle, video_path: &str) -> Result<f64, LexiError> {
+    let filter = format!("fps={},{}", SEARCH_FPS, QUALITY_FILTER);
+    let output = job.output(Command::new("ffmpeg").args([
+        "-t", &SEARCH_SECONDS.to_string(),
+        "-i", video_path,
+        "-an",
+        "-vf", &filter,
+        "-f", "rawvideo",
+        "pipe:1",
+    ]))?;
 
     if !output.status.success() {
-        return Err(format!(
-            "ffprobe failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+        println!("[thumbnail] Warning: could not score frames, using the default time");
+        return Ok(PREFERRED_TIMESTAMP);
     }
 
-    let duration_str = String::from_utf8_lossy(&output.stdout);
-    duration_str
-        .trim()
-        .parse::<f64>()
-        .map_err(|e| format!("Failed to parse duration '{}': {}", duration_str.trim(), e))
+    // fps outputs frames at exact multiples of 1 / SEARCH_FPS
+    let candidates: Vec<(f64, &[u8])> = output
+        .stdout
+        .chunks_exact(QUALITY_FRAME_BYTES)
+        .enumerate()
+        .map(|(i, pixels)| (i as f64 / SEARCH_FPS, pixels))
+        .collect();
+
+    // Neighbouring candidates are half a second apart, so motion says little here
+    let thresholds = QualityThresholds {
+        max_motion: f64::INFINITY,
+        ..QualityThresholds::default()
+    };
+    Ok(best_in_window(&candidates, PREFERRED_TIMESTAMP, &thresholds)
+        .unwrap_or(if candidates.len() > 2 { PREFERRED_TIMESTAMP } else { 0.0 }))
 }
 
-/// Extract video dimensions (width, height) using ffprobe
-pub fn get_video_dimensions(video_path: &Path) -> Result<(u32, u32), String> {
-    let output = Command::new("ffprobe")
-        .args([
-            "-v", "error",
-            "-select_streams", "v:0",
-            "-show_entries", "stream=width,height",
-            "-of", "csv=s=x:p=0",
-            &video_path.to_string_lossy(),
-        ])
-        .output()
-        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+/// Write every size from a single seek and decode. Each goes to a `.part` file that
+/// is renamed into place once all succeeded, so an interrupted run never leaves a
+/// truncated thumbnail behind to be reused.
+fn try_extract_at_timestamp(
+    job: &JobHandle,
+    video_path: &str,
+    paths: &[PathBuf],
+    timestamp: f64,
+) -> Result<bool, LexiError> {
+    let partials: Vec<PathBuf> = paths.iter().map(|p| p.with_extension("part")).collect();
+
+    let splits: String = (0..SIZES.len()).map(|n| format!("[in{}]", n)).collect();
+    let mut graph = format!("[0:v]split={}{}", SIZES.len(), splits);
+    for (n, (_, width)) in SIZES.iter().enumerate() {
+        graph.push_str(&format!(";[in{n}]scale='min({w},iw)':-2[out{n}]", n = n, w = width));
+    }
 
-    if !output.status.success() {
-        return Err(format!(
-            "ffprobe failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+    // ffmpeg applies the display rotation while decoding, so portrait phone clips
+    // get portrait thumbnails
+    let mut args: Vec<String> = [
+        "-ss", &format!("{:.3}", timestamp),
+        "-i", video_path,
+        "-filter_complex", &graph,
+        "-y",
+    ]
+    .iter()
+    .map(|a| a.to_string())
+    .collect();
+    for (n, partial) in partials.iter().enumerate() {
+        let label = format!("[out{}]", n);
+        let partial = partial.to_string_lossy();
+        args.extend(
+            [
+                "-map", &label,
+                "-frames:v", "1",
+                "-c:v", "mjpeg",
+                "-q:v", "3",
+                "-f", "image2",
+                "-update", "1",
+                &partial,
+            ]
+            .iter()
+            .map(|a| a.to_string()),
+        );
     }
 
-    let dims_str = String::from_utf8_lossy(&output.stdout);
-    let dims_str = dims_str.trim();
-    let parts: Vec<&str> = dims_str.split('x').collect();
+    let output = job.output(Command::new("ffmpeg").args(&args))?;
+    if !output.status.success() || !partials.iter().all(|p| p.exists()) {
+        for partial in &partials {
+            let _ = fs::remove_file(partial);
+        }
+        return Ok(false);
+    }
 
-    if parts.len() != 2 {
-        return Err(format!("Unexpected dimensions format: '{}'", dims_str));
+    for (partial, path) in partials.iter().zip(paths) {
+        fs::rename(partial, path)
+            .map_err(|e| LexiError::io("Failed to store thumbnail", path, e))?;
     }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::JobKind;
+    use crate::test_support::{test_jobs, Canned, FakeRunner};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn skips_black_opening_and_reuses_cached_files() {
+        let dir = TempDir::new().unwrap();
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+
+        // Two black frames, then a soft and a sharp one (vertical stripes)
+        let stripes = |swing: u8| -> Vec<u8> {
+            (0..QUALITY_FRAME_BYTES)
+                .map(|i| if i.is_multiple_of(2) { 30 } else { 30 + swing })
+                .collect()
+        };
+        let mut scored = vec![0; 2 * QUALITY_FRAME_BYTES];
+        scored.extend(stripes(60));
+        scored.extend(stripes(180));
+
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let runner = FakeRunner::new();
+            runner
+                .on("ffmpeg", &["rawvideo"], Canned::stdout_bytes(&scored))
+                .on(
+                    "ffmpeg",
+                    &["-frames:v"],
+                    Canned::ok().writing_outputs_ending(".part", b"jpeg"),
+                );
+
+            let artifacts = artifacts.clone();
+            let thumbnails = test_jobs(runner.clone())
+                .run_blocking(JobKind::Thumbnail, "test", move |job| {
+                    generate_thumbnails(job, "/media/clip.mp4", "cid1", &artifacts)
+                })
+                .await
+                .unwrap();
+            runs.push((thumbnails, runner.calls_to("ffmpeg")));
+        }
 
-    let width = parts[0]
-        .parse::<u32>()
-        .map_err(|e| format!("Failed to parse width '{}': {}", parts[0], e))?;
-    let height = parts[1]
-        .parse::<u32>()
-        .map_err(|e| format!("Failed to parse height '{}': {}", parts[1], e))?;
+        // One scoring pass, then a single seek writing all three sizes
+        let (thumbnails, calls) = &runs[0];
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].arg_after("-ss"), Some("1.500"));
+        assert!(calls[1]
+            .arg_after("-filter_complex")
+            .unwrap()
+            .ends_with(";[in2]scale='min(640,iw)':-2[out2]"));
+        assert!(thumbnails.small.ends_with("cid1_small.jpg"));
+        assert_eq!(std::fs::read(&thumbnails.large).unwrap(), b"jpeg");
+        assert!(!PathBuf::from(&thumbnails.large).with_extension("part").exists());
+
+        let (cached, calls) = &runs[1];
+        assert!(calls.is_empty());
+        assert_eq!(cached, thumbnails);
+    }
 
-    Ok((width, height))
+    #[tokio::test]
+    async fn failed_extraction_leaves_no_thumbnail_to_reuse() {
+        let dir = TempDir::new().unwrap();
+        let artifacts = Arc::new(ArtifactStore::new(dir.path().to_path_buf()));
+
+        // ffmpeg dies after writing partial output
+        let runner = FakeRunner::new();
+        runner.on(
+            "ffmpeg",
+            &["-frames:v"],
+            Canned::failure(1, "killed").writing_outputs_ending(".part", b"trunc"),
+        );
+
+        let result = test_jobs(runner.clone())
+            .run_blocking(JobKind::Thumbnail, "test", move |job| {
+                generate_thumbnails(job, "/media/clip.mp4", "cid1", &artifacts)
+            })
+            .await;
+        assert!(result.is_err());
+        let left: Vec<_> = std::fs::read_dir(dir.path().join(THUMBNAIL_KIND))
+            .unwrap()
+            .collect();
+        assert!(left.is_empty());
+    }
 }
@@ -0,0 +1,368 @@
+use crate::error::LexiError;
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+
+const ELEVENLABS_URL: &str = "https://api.elevenlabs.io/v1/speech-to-text";
+pub const DEFAULT_ELEVENLABS_MODEL: &str = "scribe_v2";
+
+pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+pub const DEFAULT_OPENAI_MODEL: &str = "whisper-1";
+
+/// OpenAI rejects uploads over 25 MB; ten minutes of 16 kHz mono FLAC stays well under
+const OPENAI_CHUNK_SECONDS: f64 = 600.0;
+
+/// Cache data type prefix; the provider and model are appended
+const DATA_TYPE_TRANSCRIPT: &str = "transcript";
+
+/// Raw ElevenLabs responses cached by the frontend before transcription moved
+/// to the backend, always from the default model
+pub const LEGACY_DATA_TYPE_TRANSCRIPTION: &str = "transcription";
+
+/// One recognized word, the same shape whichever service produced it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptWord {
+    pub text: String,
+    /// Seconds from the start of the source
+    pub start: f64,
+    pub end: f64,
+    /// 0-1; 1 when the service does not report one
+    pub confidence: f64,
+    /// Diarization label as returned by the service, when it has one
+    pub speaker: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub provider: String,
+    pub model: String,
+    pub language: Option<String>,
+    pub words: Vec<TranscriptWord>,
+}
+
+/// An audio file to send for transcription
+pub struct AudioUpload {
+    pub bytes: Vec<u8>,
+    pub file_name: String,
+    pub mime_type: String,
+}
+
+/// A speech-to-text service. Implementations only talk to the service; audio
+/// extraction, chunking and caching are handled by the caller.
+pub trait TranscriptionProvider: Send + Sync {
+    /// Stable identifier used in cache keys
+    fn name(&self) -> &'static str;
+
+    fn model(&self) -> &str;
+
+    /// Server the requests go to, when it is configurable; part of the cache key
+    fn endpoint(&self) -> Option<&str> {
+        None
+    }
+
+    /// Longest audio the service accepts in one request, if it has a limit
+    fn max_chunk_seconds(&self) -> Option<f64> {
+        None
+    }
+
+    /// Transcribe one file; word times are relative to the start of the upload
+    fn transcribe(
+        &self,
+        audio: AudioUpload,
+    ) -> impl Future<Output = Result<Transcript, LexiError>> + Send;
+}
+
+/// Cache data type for a provider, model and server, so switching any of them
+/// re-transcribes. The server is hashed to keep the key short.
+pub fn transcript_data_type(provider: &str, model: &str, endpoint: Option<&str>) -> String {
+    let data_type = format!("{}_{}_{}", DATA_TYPE_TRANSCRIPT, provider, model);
+    match endpoint {
+        Some(endpoint) => {
+            let digest = hex::encode(Sha256::digest(endpoint.as_bytes()));
+            format!("{}_{}", data_type, &digest[..12])
+        }
+        None => data_type,
+    }
+}
+
+/// Normalize a transcript cached under `LEGACY_DATA_TYPE_TRANSCRIPTION`
+pub fn parse_legacy_transcript(json: &str) -> Result<Transcript, LexiError> {
+    parse_elevenlabs(json, DEFAULT_ELEVENLABS_MODEL)
+}
+
+/// ElevenLabs Scribe, with word timestamps and speaker diarization
+pub struct ElevenLabsProvider {
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+}
+
+impl ElevenLabsProvider {
+    pub fn new(api_key: String, model: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key,
+            model: model.unwrap_or_else(|| DEFAULT_ELEVENLABS_MODEL.to_string()),
+        }
+    }
+}
+
+impl TranscriptionProvider for ElevenLabsProvider {
+    fn name(&self) -> &'static str {
+        "elevenlabs"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn transcribe(&self, audio: AudioUpload) -> Result<Transcript, LexiError> {
+        let form = Form::new()
+            .part("file", upload_part(audio)?)
+            .text("model_id", self.model.clone())
+            .text("timestamps_granularity", "word")
+            .text("tag_audio_events", "false")
+            .text("diarize", "true");
+
+        let request = self.client.post(ELEVENLABS_URL).header("xi-api-key", &self.api_key);
+        let body = send_form(request, form, "ElevenLabs transcription failed").await?;
+        parse_elevenlabs(&body, &self.model)
+    }
+}
+
+/// Any server speaking OpenAI's `/audio/transcriptions` API: OpenAI itself, or a
+/// local whisper.cpp server (`whisper-server --inference-path /v1/audio/transcriptions`)
+/// for offline use
+pub struct OpenAiCompatibleProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(base_url: Option<String>, api_key: Option<String>, model: Option<String>) -> Self {
+        let base_url = base_url.unwrap_or_else(|| DEFAULT_OPENAI_BASE_URL.to_string());
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model: model.unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string()),
+        }
+    }
+}
+
+impl TranscriptionProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn endpoint(&self) -> Option<&str> {
+        Some(&self.base_url)
+    }
+
+    fn max_chunk_seconds(&self) -> Option<f64> {
+        Some(OPENAI_CHUNK_SECONDS)
+    }
+
+    async fn transcribe(&self, audio: AudioUpload) -> Result<Transcript, LexiError> {
+        let form = Form::new()
+            .part("file", upload_part(audio)?)
+            .text("model", self.model.clone())
+            .text("response_format", "verbose_json")
+            .text("timestamp_granularities[]", "word");
+
+        let url = format!("{}/audio/transcriptions", self.base_url);
+        let mut request = self.client.post(&url);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+        let body = send_form(request, form, "Transcription request failed").await?;
+        parse_openai(&body, &self.model)
+    }
+}
+
+fn upload_part(audio: AudioUpload) -> Result<Part, LexiError> {
+    Part::bytes(audio.bytes)
+        .file_name(audio.file_name)
+        .mime_str(&audio.mime_type)
+        .map_err(|e| LexiError::invalid_input(format!("Invalid audio MIME type: {}", e)))
+}
+
+/// Post a multipart form and return the body of a successful response
+async fn send_form(
+    request: reqwest::RequestBuilder,
+    form: Form,
+    context: &str,
+) -> Result<String, LexiError> {
+    let response = request
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| LexiError::http(context, e))?;
+
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| LexiError::http(context, e))?;
+
+    if !status.is_success() {
+        return Err(LexiError::http_status(context, status.as_u16(), body));
+    }
+    Ok(body)
+}
+
+#[derive(Deserialize)]
+struct ElevenLabsResponse {
+    language_code: Option<String>,
+    #[serde(default)]
+    words: Option<Vec<ElevenLabsWord>>,
+}
+
+#[derive(Deserialize)]
+struct ElevenLabsWord {
+    text: String,
+    start: f64,
+    end: f64,
+    #[serde(rename = "type")]
+    kind: String,
+    speaker_id: Option<String>,
+    logprob: Option<f64>,
+}
+
+fn parse_elevenlabs(body: &str, model: &str) -> Result<Transcript, LexiError> {
+    let response: ElevenLabsResponse = serde_json::from_str(body)
+        .map_err(|e| LexiError::serialization("Failed to parse ElevenLabs transcript", e))?;
+
+    // Spacing and audio events are interleaved with the words
+    let words = response
+        .words
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|w| w.kind == "word")
+        .map(|w| TranscriptWord {
+            text: w.text,
+            start: w.start,
+            end: w.end,
+            confidence: w.logprob.map(f64::exp).unwrap_or(1.0),
+            speaker: w.speaker_id,
+        })
+        .collect();
+
+    Ok(Transcript {
+        provider: "elevenlabs".to_string(),
+        model: model.to_string(),
+        language: response.language_code,
+        words,
+    })
+}
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    language: Option<String>,
+    words: Option<Vec<OpenAiWord>>,
+    #[serde(default)]
+    segments: Vec<OpenAiSegment>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiSegment {
+    #[serde(default)]
+    words: Vec<OpenAiWord>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiWord {
+    word: String,
+    start: f64,
+    end: f64,
+    probability: Option<f64>,
+}
+
+fn parse_openai(body: &str, model: &str) -> Result<Transcript, LexiError> {
+    let response: OpenAiResponse = serde_json::from_str(body)
+        .map_err(|e| LexiError::serialization("Failed to parse transcript", e))?;
+
+    // OpenAI returns a flat word list; whisper.cpp nests words in segments
+    let words = match response.words {
+        Some(words) => words,
+        None => response.segments.into_iter().flat_map(|s| s.words).collect(),
+    };
+
+    let words = words
+        .into_iter()
+        .filter(|w| !w.word.trim().is_empty())
+        .map(|w| TranscriptWord {
+            text: w.word.trim().to_string(),
+            start: w.start,
+            end: w.end,
+            confidence: w.probability.unwrap_or(1.0),
+            speaker: None,
+        })
+        .collect();
+
+    Ok(Transcript {
+        provider: "openai".to_string(),
+        model: model.to_string(),
+        language: response.language,
+        words,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_elevenlabs_words() {
+        let body = r#"{
+            "language_code": "en",
+            "text": "Hi there",
+            "words": [
+                {"text": "Hi", "start": 0.1, "end": 0.3, "type": "word", "speaker_id": "speaker_0", "logprob": 0.0},
+                {"text": " ", "start": 0.3, "end": 0.35, "type": "spacing"},
+                {"text": "(laughs)", "start": 0.35, "end": 0.6, "type": "audio_event"},
+                {"text": "there", "start": 0.6, "end": 0.9, "type": "word", "speaker_id": "speaker_1"}
+            ]
+        }"#;
+
+        let transcript = parse_elevenlabs(body, "scribe_v2").unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(transcript.words.len(), 2);
+        assert_eq!(transcript.words[0].confidence, 1.0);
+        assert_eq!(transcript.words[1].speaker.as_deref(), Some("speaker_1"));
+
+        // Videos without speech come back with no word list at all
+        let silent = parse_elevenlabs(r#"{"language_code": "en", "text": ""}"#, "m").unwrap();
+        assert!(silent.words.is_empty());
+    }
+
+    #[test]
+    fn normalizes_openai_and_whisper_cpp_words() {
+        let openai = r#"{
+            "language": "english",
+            "words": [{"word": "Hello", "start": 0.0, "end": 0.4}, {"word": "world", "start": 0.5, "end": 0.9}]
+        }"#;
+        let transcript = parse_openai(openai, "whisper-1").unwrap();
+        assert_eq!(transcript.words[1].text, "world");
+        assert_eq!(transcript.words[1].confidence, 1.0);
+
+        let whisper_cpp = r#"{
+            "language": "en",
+            "segments": [
+                {"words": [{"word": " Hello", "start": 0.0, "end": 0.4, "probability": 0.9}]},
+                {"words": [{"word": " ", "start": 0.4, "end": 0.5}, {"word": " world", "start": 0.5, "end": 0.9, "probability": 0.8}]}
+            ]
+        }"#;
+        let transcript = parse_openai(whisper_cpp, "base.en").unwrap();
+        let texts: Vec<&str> = transcript.words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, ["Hello", "world"]);
+        assert_eq!(transcript.words[0].confidence, 0.9);
+    }
+}
@@ -0,0 +1,232 @@
+//! Min/max waveform peaks at several zoom levels, in the spirit of audiowaveform's
+//! `.dat` files.
+//!
+//! Binary layout (little endian):
+//! - `b"LXWF"`, `u16` version, `u32` sample rate, `u16` level count
+//! - per level: `u32` samples per peak, `u32` peak count, then one `i8` min and one
+//!   `i8` max per peak
+
+/// Sample rate audio is decoded at; plenty for drawing speech
+pub const WAVEFORM_SAMPLE_RATE: u32 = 16_000;
+
+/// Samples per peak at the finest level (250 peaks per second at 16 kHz)
+const BASE_SAMPLES_PER_PEAK: u32 = 64;
+
+/// Each level merges this many peaks of the level below it
+const ZOOM_STEP: u32 = 4;
+
+/// 64, 256, 1024, 4096 and 16384 samples per peak
+const LEVEL_COUNT: usize = 5;
+
+const MAGIC: &[u8; 4] = b"LXWF";
+const FORMAT_VERSION: u16 = 1;
+
+/// Peaks at one zoom level
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveformLevel {
+    pub samples_per_peak: u32,
+    /// (min, max) per peak, scaled to 8 bits
+    pub peaks: Vec<(i8, i8)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waveform {
+    pub sample_rate: u32,
+    /// Finest level first
+    pub levels: Vec<WaveformLevel>,
+}
+
+/// Folds a stream of 16-bit mono samples into finest-level peaks
+pub struct WaveformBuilder {
+    sample_rate: u32,
+    peaks: Vec<(i8, i8)>,
+    min: i16,
+    max: i16,
+    count: u32,
+}
+
+impl WaveformBuilder {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            peaks: Vec::new(),
+            min: i16::MAX,
+            max: i16::MIN,
+            count: 0,
+        }
+    }
+
+    /// Add little-endian `s16le` bytes; a trailing odd byte is ignored
+    pub fn push_s16le(&mut self, bytes: &[u8]) {
+        for pair in bytes.chunks_exact(2) {
+            self.push(i16::from_le_bytes([pair[0], pair[1]]));
+        }
+    }
+
+    pub fn push(&mut self, sample: i16) {
+        self.min = self.min.min(sample);
+        self.max = self.max.max(sample);
+        self.count += 1;
+        if self.count == BASE_SAMPLES_PER_PEAK {
+            self.flush();
+        }
+    }
+
+    /// Number of samples pushed so far
+    pub fn samples(&self) -> u64 {
+        self.peaks.len() as u64 * BASE_SAMPLES_PER_PEAK as u64 + self.count as u64
+    }
+
+    pub fn finish(mut self) -> Waveform {
+        if self.count > 0 {
+            self.flush();
+        }
+
+        let mut levels = vec![WaveformLevel {
+            samples_per_peak: BASE_SAMPLES_PER_PEAK,
+            peaks: self.peaks,
+        }];
+        while levels.len() < LEVEL_COUNT {
+            let finer = levels.last().unwrap();
+            levels.push(WaveformLevel {
+                samples_per_peak: finer.samples_per_peak * ZOOM_STEP,
+                peaks: finer
+                    .peaks
+                    .chunks(ZOOM_STEP as usize)
+                    .map(|group| {
+                        let min = group.iter().map(|p| p.0).min().unwrap_or(0);
+                        let max = group.iter().map(|p| p.1).max().unwrap_or(0);
+                        (min, max)
+                    })
+                    .collect(),
+            });
+        }
+
+        Waveform {
+            sample_rate: self.sample_rate,
+            levels,
+        }
+    }
+
+    fn flush(&mut self) {
+        self.peaks.push((to_8_bit(self.min), to_8_bit(self.max)));
+        self.min = i16::MAX;
+        self.max = i16::MIN;
+        self.count = 0;
+    }
+}
+
+fn to_8_bit(sample: i16) -> i8 {
+    (sample >> 8) as i8
+}
+
+impl Waveform {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let peak_count: usize = self.levels.iter().map(|l| l.peaks.len()).sum();
+        let mut bytes = Vec::with_capacity(12 + self.levels.len() * 8 + peak_count * 2);
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(self.levels.len() as u16).to_le_bytes());
+        for level in &self.levels {
+            bytes.extend_from_slice(&level.samples_per_peak.to_le_bytes());
+            bytes.extend_from_slice(&(level.peaks.len() as u32).to_le_bytes());
+            for (min, max) in &level.peaks {
+                bytes.push(*min as u8);
+                bytes.push(*max as u8);
+            }
+        }
+        bytes
+    }
+
+    /// Parse [`Waveform::to_bytes`] output; `None` if truncated or another version
+    pub fn from_bytes(bytes: &[u8]) -> Option<Waveform> {
+        let mut reader = ByteReader { bytes, pos: 0 };
+        if reader.take(4)? != MAGIC || reader.u16()? != FORMAT_VERSION {
+            return None;
+        }
+
+        let sample_rate = reader.u32()?;
+        let level_count = reader.u16()?;
+        let mut levels = Vec::with_capacity(level_count as usize);
+        for _ in 0..level_count {
+            let samples_per_peak = reader.u32()?;
+            let count = reader.u32()? as usize;
+            let data = reader.take(count.checked_mul(2)?)?;
+            levels.push(WaveformLevel {
+                samples_per_peak,
+                peaks: data
+                    .chunks_exact(2)
+                    .map(|p| (p[0] as i8, p[1] as i8))
+                    .collect(),
+            });
+        }
+
+        (reader.pos == bytes.len()).then_some(Waveform {
+            sample_rate,
+            levels,
+        })
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let slice = self.bytes.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(slice)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_min_max_peaks_per_level() {
+        let mut builder = WaveformBuilder::new(WAVEFORM_SAMPLE_RATE);
+        // Five finest peaks: silence, then a loud swing, then three quiet ones
+        builder.push_s16le(&[0; 128]);
+        for n in 0..64 {
+            builder.push(if n % 2 == 0 { 12_800 } else { -12_800 });
+        }
+        for _ in 0..(3 * 64 - 10) {
+            builder.push(256);
+        }
+        assert_eq!(builder.samples(), 5 * 64 - 10);
+
+        let waveform = builder.finish();
+        assert_eq!(waveform.levels.len(), LEVEL_COUNT);
+        assert_eq!(waveform.levels[0].peaks, vec![(0, 0), (-50, 50), (1, 1), (1, 1), (1, 1)]);
+        assert_eq!(waveform.levels[1].samples_per_peak, 256);
+        assert_eq!(waveform.levels[1].peaks, vec![(-50, 50), (1, 1)]);
+        assert_eq!(waveform.levels[4].peaks, vec![(-50, 50)]);
+    }
+
+    #[test]
+    fn binary_form_round_trips() {
+        let mut builder = WaveformBuilder::new(WAVEFORM_SAMPLE_RATE);
+        for n in 0..1000 {
+            builder.push((n * 37 % 2000) as i16 - 1000);
+        }
+        let waveform = builder.finish();
+
+        let bytes = waveform.to_bytes();
+        assert_eq!(&bytes[..4], b"LXWF");
+        assert_eq!(Waveform::from_bytes(&bytes), Some(waveform));
+        assert_eq!(Waveform::from_bytes(&bytes[..bytes.len() - 1]), None);
+    }
+}
@@ -0,0 +1,357 @@
+//! Test helpers: a fake ffmpeg/ffprobe runner and a generator for tiny real clips.
+
+use crate::services::{JobManager, ProcessHandle, ProcessRunner, ProcessStatus};
+use std::io::{self, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A process invocation recorded by [`FakeRunner`]
+#[derive(Debug, Clone)]
+pub struct RecordedCall {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl RecordedCall {
+    pub fn has_arg(&self, arg: &str) -> bool {
+        self.args.iter().any(|a| a == arg)
+    }
+
+    /// Value following a flag, e.g. `arg_after("-ss")`
+    pub fn arg_after(&self, flag: &str) -> Option<&str> {
+        let idx = self.args.iter().position(|a| a == flag)?;
+        self.args.get(idx + 1).map(|s| s.as_str())
+    }
+
+    pub fn last_arg(&self) -> Option<&str> {
+        self.args.last().map(|s| s.as_str())
+    }
+}
+
+/// Canned result replayed by [`FakeRunner`]
+#[derive(Debug, Clone, Default)]
+pub struct Canned {
+    code: i32,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    /// Bytes written to the path given as the last argument (the ffmpeg output file)
+    output_file: Option<Vec<u8>>,
+    /// Number of files written to a `%04d` output pattern given as the last argument
+    numbered_outputs: usize,
+    /// Write to every argument ending in this suffix instead (several output files)
+    output_suffix: Option<String>,
+    /// Simulate the program not being installed
+    missing: bool,
+    /// Keep the process running until it is killed
+    until_killed: bool,
+}
+
+impl Canned {
+    pub fn ok() -> Self {
+        Self::default()
+    }
+
+    pub fn stdout(stdout: &str) -> Self {
+        Self {
+            stdout: stdout.as_bytes().to_vec(),
+            ..Self::default()
+        }
+    }
+
+    pub fn stdout_bytes(stdout: &[u8]) -> Self {
+        Self {
+            stdout: stdout.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    pub fn failure(code: i32, stderr: &str) -> Self {
+        Self {
+            code,
+            stderr: stderr.as_bytes().to_vec(),
+            ..Self::default()
+        }
+    }
+
+    pub fn missing() -> Self {
+        Self {
+            missing: true,
+            ..Self::default()
+        }
+    }
+
+    /// A process that never exits on its own, like a long ffmpeg encode
+    pub fn running_until_killed() -> Self {
+        Self {
+            until_killed: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_stderr(mut self, stderr: &str) -> Self {
+        self.stderr = stderr.as_bytes().to_vec();
+        self
+    }
+
+    pub fn writing_output(mut self, bytes: &[u8]) -> Self {
+        self.output_file = Some(bytes.to_vec());
+        self
+    }
+
+    /// Write `count` copies of `bytes` to the numbered outputs of an image2 pattern
+    pub fn writing_numbered_outputs(mut self, count: usize, bytes: &[u8]) -> Self {
+        self.output_file = Some(bytes.to_vec());
+        self.numbered_outputs = count;
+        self
+    }
+
+    /// Write `bytes` to every argument ending in `suffix`, for runs with several outputs
+    pub fn writing_outputs_ending(mut self, suffix: &str, bytes: &[u8]) -> Self {
+        self.output_file = Some(bytes.to_vec());
+        self.output_suffix = Some(suffix.to_string());
+        self
+    }
+}
+
+struct Rule {
+    program: String,
+    needles: Vec<String>,
+    response: Canned,
+}
+
+/// Stand-in for ffmpeg/ffprobe that records every call and replays canned output.
+///
+/// Rules match on program name plus a set of arguments that must all be present;
+/// the first matching rule wins and unmatched calls succeed with empty output.
+#[derive(Default)]
+pub struct FakeRunner {
+    rules: Mutex<Vec<Rule>>,
+    calls: Mutex<Vec<RecordedCall>>,
+    kills: Arc<AtomicUsize>,
+}
+
+impl FakeRunner {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn on(&self, program: &str, needles: &[&str], response: Canned) -> &Self {
+        self.rules.lock().unwrap().push(Rule {
+            program: program.to_string(),
+            needles: needles.iter().map(|n| n.to_string()).collect(),
+            response,
+        });
+        self
+    }
+
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    pub fn calls_to(&self, program: &str) -> Vec<RecordedCall> {
+        self.calls()
+            .into_iter()
+            .filter(|c| c.program == program)
+            .collect()
+    }
+
+    /// Number of processes killed so far
+    pub fn kills(&self) -> usize {
+        self.kills.load(Ordering::SeqCst)
+    }
+}
+
+impl ProcessRunner for FakeRunner {
+    fn spawn(&self, cmd: &mut Command) -> io::Result<Box<dyn ProcessHandle>> {
+        let call = RecordedCall {
+            program: cmd.get_program().to_string_lossy().to_string(),
+            args: cmd
+                .get_args()
+                .map(|a| a.to_string_lossy().to_string())
+                .collect(),
+        };
+        self.calls.lock().unwrap().push(call.clone());
+
+        let response = self
+            .rules
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|r| r.program == call.program && r.needles.iter().all(|n| call.has_arg(n)))
+            .map(|r| r.response.clone())
+            .unwrap_or_default();
+
+        if response.missing {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "not installed"));
+        }
+
+        if let (Some(bytes), Some(suffix)) = (&response.output_file, &response.output_suffix) {
+            for path in call.args.iter().filter(|a| a.ends_with(suffix.as_str())) {
+                std::fs::write(path, bytes)?;
+            }
+        } else if let (Some(bytes), Some(path)) = (&response.output_file, call.last_arg()) {
+            if response.numbered_outputs > 0 {
+                for n in 1..=response.numbered_outputs {
+                    std::fs::write(path.replace("%04d", &format!("{:04}", n)), bytes)?;
+                }
+            } else {
+                std::fs::write(path, bytes)?;
+            }
+        }
+
+        Ok(Box::new(FakeProcess {
+            stdout: Some(response.stdout),
+            stderr: Some(response.stderr),
+            status: ProcessStatus::from_code(Some(response.code)),
+            running: response.until_killed,
+            kills: self.kills.clone(),
+        }))
+    }
+}
+
+struct FakeProcess {
+    stdout: Option<Vec<u8>>,
+    stderr: Option<Vec<u8>>,
+    status: ProcessStatus,
+    running: bool,
+    kills: Arc<AtomicUsize>,
+}
+
+impl ProcessHandle for FakeProcess {
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.stdout
+            .take()
+            .map(|b| Box::new(Cursor::new(b)) as Box<dyn Read + Send>)
+    }
+
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.stderr
+            .take()
+            .map(|b| Box::new(Cursor::new(b)) as Box<dyn Read + Send>)
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<ProcessStatus>> {
+        if self.running {
+            return Ok(None);
+        }
+        Ok(Some(self.status))
+    }
+
+    fn kill(&mut self) -> io::Result<()> {
+        if self.running {
+            self.running = false;
+            self.status = ProcessStatus::from_code(None);
+        }
+        self.kills.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// Job manager backed by `runner` that discards progress events
+pub fn test_jobs(runner: Arc<dyn ProcessRunner>) -> JobManager {
+    JobManager::new(2, runner, Arc::new(|_| {}))
+}
+
+/// Minimal ffprobe JSON for a video file with one video and one audio stream
+pub fn probe_json(duration: f64, width: u32, height: u32, display_rotation: i32) -> String {
+    serde_json::json!({
+        "streams": [
+            {
+                "index": 0,
+                "codec_type": "video",
+                "codec_name": "h264",
+                "width": width,
+                "height": height,
+                "pix_fmt": "yuv420p",
+                "r_frame_rate": "30/1",
+                "avg_frame_rate": "30/1",
+                "duration": duration.to_string(),
+                "side_data_list": [
+                    { "side_data_type": "Display Matrix", "rotation": display_rotation }
+                ]
+            },
+            {
+                "index": 1,
+                "codec_type": "audio",
+                "codec_name": "aac",
+                "sample_rate": "48000",
+                "channels": 2,
+                "channel_layout": "stereo",
+                "sample_fmt": "fltp"
+            }
+        ],
+        "format": {
+            "format_name": "mov,mp4,m4a,3gp,3g2,mj2",
+            "duration": duration.to_string()
+        }
+    })
+    .to_string()
+}
+
+/// Speech-like mono PCM: noise bursts with pseudo-random lengths, levels and gaps
+pub fn bursty_pcm(seconds: f64, sample_rate: u32, seed: u64) -> Vec<i16> {
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) as u32
+    };
+
+    let total = (seconds * sample_rate as f64) as usize;
+    let mut samples = Vec::with_capacity(total);
+    while samples.len() < total {
+        let burst = 400 + (next() % 4000) as usize;
+        let gap = 200 + (next() % 3000) as usize;
+        let amplitude = 2000 + (next() % 12000) as i32;
+        for _ in 0..burst {
+            samples.push((((next() % 2001) as i32 - 1000) * amplitude / 1000) as i16);
+        }
+        samples.extend(std::iter::repeat_n(0, gap));
+    }
+    samples.truncate(total);
+    samples
+}
+
+pub fn s16le_bytes(samples: &[i16]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+/// True when a real ffmpeg binary is on PATH
+pub fn ffmpeg_available() -> bool {
+    Command::new("ffmpeg")
+        .arg("-version")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Render a tiny H.264/AAC clip with `lavfi` test sources.
+/// Returns `None` when ffmpeg is not installed so callers can skip.
+pub fn make_test_clip(dir: &Path, name: &str, seconds: f64) -> Option<PathBuf> {
+    if !ffmpeg_available() {
+        eprintln!("ffmpeg not available, skipping real-media test");
+        return None;
+    }
+
+    let path = dir.join(name);
+    let status = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-v", "error",
+            "-f", "lavfi",
+            "-i", &format!("testsrc=duration={}:size=320x240:rate=25", seconds),
+            "-f", "lavfi",
+            "-i", &format!("sine=frequency=440:duration={}", seconds),
+            "-c:v", "libx264",
+            "-pix_fmt", "yuv420p",
+            "-g", "25",
+            "-c:a", "aac",
+            "-shortest",
+        ])
+        .arg(&path)
+        .status()
+        .ok()?;
+
+    status.success().then_some(path)
+}
@@ -18,6 +18,7 @@ import {
 } from "../stores/useAgenticStore";
 import { useProjectStore } from "../stores/useProjectStore";
 import { classifyAsBroll } from "./brollClassification";
+import { errorMessage } from "../utils/errors";
 import type { BrollReason } from "../types";
 
 const API_URL = "https://api.anthropic.com/v1/messages";
@@ -352,7 +353,7 @@ function executeTool(
         return { success: false, result: `Unknown tool: ${name}` };
     }
   } catch (error) {
-    const message = error instanceof Error ? error.message : String(error);
+    const message = errorMessage(error);
     return { success: false, result: `Error: ${message}` };
   }
 }
@@ -617,7 +618,7 @@ User request: ${userInstruction}`;
       commandIds,
     };
   } catch (error) {
-    const err = error instanceof Error ? error : new Error(String(error));
+    const err = error instanceof Error ? error : new Error(errorMessage(error));
     callbacks.onError?.(err);
     throw err;
   }
@@ -812,7 +813,7 @@ Please perform an assembly cut on this timeline. Identify and remove retakes/dup
       commandIds,
     };
   } catch (error) {
-    const err = error instanceof Error ? error : new Error(String(error));
+    const err = error instanceof Error ? error : new Error(errorMessage(error));
     callbacks.onError?.(err);
     throw err;
   }
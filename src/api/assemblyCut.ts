@@ -41,8 +41,12 @@ export function groupWordsForAssembly(
   for (const word of words) {
     const wordSourceId = word.sourceId;
 
-    // Flush if source changes
-    if (currentSourceId !== null && wordSourceId !== currentSourceId) {
+    // Flush if source or speaker changes, so each group has a single speaker
+    const previous = currentGroup[currentGroup.length - 1];
+    if (
+      (currentSourceId !== null && wordSourceId !== currentSourceId) ||
+      (previous && previous.speakerId !== word.speakerId)
+    ) {
       flushGroup();
     }
 
@@ -0,0 +1,38 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export type AudioFormat = "flac" | "opus" | "mp3";
+
+export interface AudioOptions {
+  /** Default flac */
+  format?: AudioFormat;
+  /** Split sources longer than this into chunks of at most this many seconds */
+  chunkSeconds?: number;
+}
+
+export interface AudioChunk {
+  /** File under the app data dir */
+  path: string;
+  /** Position of the chunk in the source, in seconds */
+  start: number;
+  end: number;
+  byteSize: number;
+}
+
+export interface ExtractedAudio {
+  format: AudioFormat;
+  mimeType: string;
+  duration: number;
+  chunks: AudioChunk[];
+}
+
+/**
+ * Write a source's audio as mono 16 kHz files (cached by CID). Only paths come
+ * back; the files stay on disk for backend commands to upload.
+ */
+export function extractAudio(
+  path: string,
+  cid: string,
+  options?: AudioOptions
+): Promise<ExtractedAudio> {
+  return invoke<ExtractedAudio>("extract_audio", { path, cid, options });
+}
@@ -1,31 +1,12 @@
-import { invoke } from "@tauri-apps/api/core";
-import { getCachedTranscription, getCachedDescriptions } from "./cache";
-import { transcribeFile } from "./transcribe";
+import { getCachedDescriptions } from "./cache";
+import { transcribeSource } from "./transcribe";
 import { describeSourceWithFrames } from "./describeSegments";
+import { trackCidWork } from "../utils/cid";
 
 // Track in-flight requests by CID
 const inFlightTranscriptions = new Map<string, Promise<void>>();
 const inFlightDescriptions = new Map<string, Promise<void>>();
 
-/**
- * Load a file from Tauri filesystem to browser File object
- */
-async function loadVideoAsFile(path: string): Promise<File> {
-  const name = path.split("/").pop() || path;
-  const base64Data = await invoke<string>("read_file_base64", { path });
-
-  const binaryString = atob(base64Data);
-  const bytes = new Uint8Array(binaryString.length);
-  for (let i = 0; i < binaryString.length; i++) {
-    bytes[i] = binaryString.charCodeAt(i);
-  }
-
-  const ext = name.split(".").pop()?.toLowerCase();
-  const mimeType = ext === "mov" ? "video/quicktime" : "video/mp4";
-
-  return new File([bytes], name, { type: mimeType });
-}
-
 /**
  * Start transcription in the background for a source.
  * Returns immediately - the work continues in the background.
@@ -35,12 +16,6 @@ export async function transcribeSourceBackground(
   sourcePath: string,
   cid: string
 ): Promise<void> {
-  // Check cache first
-  if (await getCachedTranscription(cid)) {
-    console.log(`[background] Transcription cache hit: ${cid.slice(0, 8)}...`);
-    return;
-  }
-
   // Check if already in-flight, wait for it
   const existing = inFlightTranscriptions.get(cid);
   if (existing) {
@@ -52,8 +27,8 @@ export async function transcribeSourceBackground(
   console.log(`[background] Starting transcription: ${cid.slice(0, 8)}...`);
   const promise = (async () => {
     try {
-      const file = await loadVideoAsFile(sourcePath);
-      await transcribeFile(file, cid);
+      // Cached by CID in the backend, so a repeat call returns immediately
+      await transcribeSource(sourcePath, cid);
       console.log(`[background] Transcription complete: ${cid.slice(0, 8)}...`);
     } catch (e) {
       console.warn(`[background] Transcription failed: ${cid.slice(0, 8)}...`, e);
@@ -63,6 +38,7 @@ export async function transcribeSourceBackground(
   })();
 
   inFlightTranscriptions.set(cid, promise);
+  trackCidWork(cid, promise);
   return promise;
 }
 
@@ -103,6 +79,7 @@ export async function describeSourceBackground(
   })();
 
   inFlightDescriptions.set(cid, promise);
+  trackCidWork(cid, promise);
   return promise;
 }
 
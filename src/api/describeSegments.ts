@@ -6,9 +6,49 @@ import { getCachedDescriptions, setCachedDescriptions } from "./cache";
 const MAX_RETRIES = 3;
 const MAX_FRAMES = 60; // Limit frames sent to Gemini (hybrid extraction is smart about selection)
 
+/** Frame sampling strategy for extract_frames_base64 (defaults to keyframes) */
+export type SamplingStrategy =
+  | { mode: "keyframes" }
+  | { mode: "sceneDetect"; threshold?: number }
+  | { mode: "uniform"; interval?: number }
+  | { mode: "hybrid"; threshold?: number };
+
+/** Part of a source to sample, e.g. a segment group; `id` is echoed on each frame */
+export interface FrameRange {
+  id: string;
+  start: number;
+  end: number;
+  maxFrames?: number;
+}
+
+/** Size and encoding of extracted frames (defaults to full-size JPEG) */
+export interface FrameEncoding {
+  maxLongEdge?: number;
+  format?: "jpeg" | "webp" | "png";
+  quality?: number;
+  maxTotalBytes?: number;
+}
+
+/** Skip black, blurry and transitional frames; unset limits use backend defaults */
+export interface FrameQualityOptions {
+  window?: number;
+  minBrightness?: number;
+  minSharpness?: number;
+  maxMotion?: number;
+}
+
 interface ExtractedFrame {
   timestamp: number;
   data: string;
+  mimeType: string;
+  width: number;
+  height: number;
+  byteSize: number;
+  strategy: SamplingStrategy;
+  sceneScore: number | null;
+  /** 64-bit perceptual hash as hex; similar shots differ in few bits */
+  hash: string | null;
+  rangeId: string | null;
 }
 
 function delay(ms: number): Promise<void> {
@@ -50,7 +90,9 @@ export async function describeSourceWithFrames(
 
   const extractedFrames = await invoke<ExtractedFrame[]>("extract_frames_base64", {
     path: sourcePath,
+    cid,
     maxFrames: MAX_FRAMES,
+    quality: {},
   });
 
   console.log(`[describeSource] Extracted ${extractedFrames.length} frames`);
@@ -59,6 +101,7 @@ export async function describeSourceWithFrames(
   const frames: FrameData[] = extractedFrames.map((f) => ({
     timestamp: f.timestamp,
     data: f.data,
+    mimeType: f.mimeType,
   }));
 
   // Call Gemini with frames
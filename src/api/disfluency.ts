@@ -0,0 +1,45 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Sentence, TimelineEntry, Word } from "../types";
+
+export interface DisfluencyOptions {
+  /** ISO 639-1 code of the built-in lexicon (default "en") */
+  language?: string;
+  /** Extra hesitation words, always flagged */
+  extraFillers?: string[];
+  /** Extra multi-word fillers, e.g. "you know what I mean" */
+  extraPhrases?: string[];
+  /** Words or phrases never flagged */
+  ignore?: string[];
+  /** Drop candidates scoring below this, 0-1 (default 0.5) */
+  minScore?: number;
+}
+
+export type DisfluencyReason = "filler" | "fillerPhrase" | "repetition" | "falseStart";
+
+export interface DisfluencyCandidate {
+  wordId: string;
+  word: string;
+  reason: DisfluencyReason;
+  /** How sure the backend is the word can go, 0-1 */
+  score: number;
+}
+
+export interface EntryDisfluencies {
+  sentenceId: string;
+  candidates: DisfluencyCandidate[];
+}
+
+/** Filler words, filler phrases and stutters per timeline entry */
+export function detectDisfluencies(
+  entries: TimelineEntry[],
+  sentences: Sentence[],
+  words: Word[],
+  options?: DisfluencyOptions,
+): Promise<EntryDisfluencies[]> {
+  return invoke<EntryDisfluencies[]>("detect_disfluencies", {
+    entries,
+    sentences,
+    words,
+    options,
+  });
+}
@@ -198,7 +198,8 @@ export class RateLimitError extends Error {
 
 export interface FrameData {
   timestamp: number;
-  data: string; // base64-encoded JPEG
+  data: string; // base64-encoded image
+  mimeType?: string; // defaults to image/jpeg
 }
 
 /**
@@ -219,7 +220,7 @@ export async function describeFrames(
   for (const frame of frames) {
     parts.push({
       inlineData: {
-        mimeType: "image/jpeg",
+        mimeType: frame.mimeType ?? "image/jpeg",
         data: frame.data,
       },
     });
@@ -0,0 +1,21 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { SyncGroup } from "../types";
+
+export interface SyncSource {
+  sourceId: string;
+  path: string;
+  cid: string;
+}
+
+/**
+ * Align sources recorded at the same time by cross-correlating their audio.
+ * Offsets are relative to `referenceSourceId` (default: the first source); store
+ * the result with `setSyncGroup` so it is saved with the project.
+ */
+export function syncSources(
+  sources: SyncSource[],
+  referenceSourceId?: string,
+  maxOffset?: number
+): Promise<SyncGroup> {
+  return invoke<SyncGroup>("sync_sources", { sources, referenceSourceId, maxOffset });
+}
@@ -1,10 +1,12 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { Source, Word, SegmentGroup, Sentence, SourceDescription, ProcessingProgress, BrollClassification } from "../types";
-import { transcribeFile, mapTranscriptToWords } from "./transcribe";
+import type { Source, Word, SegmentGroup, Sentence, SourceDescription, ProcessingProgress, BrollClassification, Speaker } from "../types";
+import { buildSpeakerTable, majoritySpeaker } from "./speakers";
+import { transcribeFile, mapTranscriptToWords, transcribeSource, mapTranscriptWords } from "./transcribe";
 import { groupWords } from "./segmentGrouping";
 import { describeSourceWithFrames } from "./describeSegments";
 import { groupWordsForAssembly } from "./assemblyCut";
 import { waitForInFlight } from "./backgroundProcessing";
+import { trackCidWork } from "../utils/cid";
 
 /**
  * Ensure all sources have CIDs computed.
@@ -54,6 +56,7 @@ export interface PipelineResult {
   sentences: Sentence[];
   transcriptlessSourceIds: string[];
   brollClassifications: BrollClassification[];
+  speakers: Speaker[];
 }
 
 export type ProgressCallback = (progress: ProcessingProgress) => void;
@@ -87,7 +90,20 @@ async function loadFileFromPath(path: string, name: string): Promise<File> {
  * 2. Group segments into SegmentGroups
  * 3. Call assembly cut API → get ordered groups
  */
-export async function runPipeline(
+export function runPipeline(
+  sources: Source[],
+  onProgress?: ProgressCallback,
+  onDescriptions?: DescriptionCallback
+): Promise<PipelineResult> {
+  const run = processSources(sources, onProgress, onDescriptions);
+  // Results are cached under the CIDs sources have now; an upgrade moves them once the run ends
+  for (const source of sources) {
+    if (source.cid) trackCidWork(source.cid, run);
+  }
+  return run;
+}
+
+async function processSources(
   sources: Source[],
   onProgress?: ProgressCallback,
   onDescriptions?: DescriptionCallback
@@ -130,12 +146,21 @@ export async function runPipeline(
       message: `Transcribing ${source.name}...`,
     });
 
-    console.log(`[pipeline] Phase 1: Loading file "${source.name}" from ${source.path}`);
-    const file = await loadFileFromPath(source.path, source.name);
-    console.log(`[pipeline] Phase 1: File loaded (${(file.size / 1024 / 1024).toFixed(1)} MB), transcribing...`);
-    const transcript = await transcribeFile(file, cid);
-    const words = mapTranscriptToWords(transcript, source.id);
-    console.log(`[pipeline] Phase 1: "${source.name}" → ${words.length} words from ElevenLabs`);
+    // The backend uploads only the audio track; without a CID to key its cache,
+    // fall back to sending the whole file from here
+    let words: Word[];
+    if (cid) {
+      console.log(`[pipeline] Phase 1: Transcribing "${source.name}" from ${source.path}`);
+      const transcript = await transcribeSource(source.path, cid);
+      words = mapTranscriptWords(transcript, source.id);
+      console.log(`[pipeline] Phase 1: "${source.name}" → ${words.length} words from ${transcript.provider}`);
+    } else {
+      console.log(`[pipeline] Phase 1: Loading file "${source.name}" from ${source.path}`);
+      const file = await loadFileFromPath(source.path, source.name);
+      const transcript = await transcribeFile(file);
+      words = mapTranscriptToWords(transcript, source.id);
+      console.log(`[pipeline] Phase 1: "${source.name}" → ${words.length} words from ElevenLabs`);
+    }
 
     // Track sources with no speech for video-only group creation later
     if (words.length === 0) {
@@ -254,6 +279,7 @@ export async function runPipeline(
 
   // Generate sentences by splitting words on sentence boundaries
   const sentenceGroups = groupWordsForAssembly(allWords);
+  const wordMap = new Map(allWords.map((w) => [w.id, w]));
   const sentences: Sentence[] = sentenceGroups.map((g, idx) => ({
     sentenceId: `sentence-${idx}`,
     sourceId: g.sourceId,
@@ -262,6 +288,7 @@ export async function runPipeline(
     startTime: g.startTime,
     endTime: g.endTime,
     originalGroupId: g.groupId,
+    speakerId: majoritySpeaker(g.segmentIds, wordMap),
   }));
 
   // Create sentences for transcriptless sources (videos with no audio)
@@ -294,6 +321,7 @@ export async function runPipeline(
   }
 
   const transcriptlessSourceIds = sourcesWithNoSpeech.map((s) => s.sourceId);
+  const speakers = buildSpeakerTable(allWords);
 
   console.log(`[pipeline] ===== PIPELINE COMPLETE =====`);
   console.log(`[pipeline] Result: ${allWords.length} words, ${allGroups.length} groups, ${sentences.length} sentences, ${orderedGroupIds.length} ordered IDs`);
@@ -308,5 +336,6 @@ export async function runPipeline(
     sentences,
     transcriptlessSourceIds,
     brollClassifications,
+    speakers,
   };
 }
@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { ProjectMeta, Source, Word, Sentence, SegmentGroup, Timeline, TimelineEntry, BrollClassification } from "../types";
+import type { ProjectMeta, Source, Word, Sentence, SegmentGroup, Timeline, TimelineEntry, BrollClassification, SyncGroup, AngleGroup, Speaker } from "../types";
 
 export async function loadProjects(): Promise<ProjectMeta[]> {
   return invoke<ProjectMeta[]>("load_projects");
@@ -25,6 +25,11 @@ export interface ProjectData {
   excludedSentenceIds?: string[];
   excludedWordIds?: string[];
   transcriptlessSourceIds: string[];
+  // Multicam sources aligned by audio
+  syncGroups?: SyncGroup[];
+  angleGroups?: AngleGroup[];
+  // Diarized speakers with their display names
+  speakers?: Speaker[];
   // B-roll classifications (stored as array, converted to Map in store)
   brollClassifications?: BrollClassification[];
   savedAt: number;
@@ -0,0 +1,26 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface ProxyOptions {
+  /** Output height in pixels (default 540); sources are never upscaled */
+  maxHeight?: number;
+  /** Every frame a keyframe: larger files, instant frame-accurate seeks */
+  allIntra?: boolean;
+}
+
+export interface Proxy {
+  /** Name for getMediaUrl(cid, { kind: "proxies", name }) */
+  name: string;
+  /** File under the app data dir */
+  path: string;
+  width: number;
+  height: number;
+}
+
+/**
+ * Transcode a source to a small H.264 proxy for playback, reported as a `proxy`
+ * job. Cached by CID, so sources shared between projects reuse their proxy.
+ * Resolves to null for sources without video.
+ */
+export function generateProxy(path: string, cid: string, options?: ProxyOptions): Promise<Proxy | null> {
+  return invoke<Proxy | null>("generate_proxy", { path, cid, options });
+}
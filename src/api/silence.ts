@@ -0,0 +1,47 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { TimelineEntry, Word } from "../types";
+
+export interface SilenceOptions {
+  /** Noise floor in dB (default -35) */
+  noiseDb?: number;
+  /** Shortest silence reported, in seconds (default 0.5) */
+  minDuration?: number;
+}
+
+export interface SilenceRange {
+  start: number;
+  end: number;
+}
+
+export interface PauseCut {
+  start: number;
+  end: number;
+  afterWordId: string;
+  beforeWordId: string;
+}
+
+export interface PauseProposal {
+  sentenceId: string;
+  /** Words sitting almost entirely in silence */
+  excludedWordIds: string[];
+  /** Source-time ranges of dead air between kept words */
+  cuts: PauseCut[];
+}
+
+export interface SilenceReport {
+  silences: SilenceRange[];
+  proposal: PauseProposal | null;
+}
+
+/**
+ * Silent ranges of a source (cached by CID). Pass an entry and its sentence's
+ * words to also get proposed cuts for pauses longer than `minPauseMs`.
+ */
+export function detectSilence(
+  path: string,
+  cid: string,
+  options?: SilenceOptions,
+  pauses?: { entry: TimelineEntry; words: Word[]; minPauseMs?: number },
+): Promise<SilenceReport> {
+  return invoke<SilenceReport>("detect_silence", { path, cid, options, pauses });
+}
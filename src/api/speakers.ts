@@ -0,0 +1,75 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Sentence, Speaker, TimelineEntry, Word } from "../types";
+
+/** Speakers, words and sentences after merging speakers into one */
+export interface SpeakerMerge {
+  speakers: Speaker[];
+  words: Word[];
+  sentences: Sentence[];
+}
+
+/** Colors handed out to speakers in order of first appearance */
+const SPEAKER_COLORS = ["#60a5fa", "#f472b6", "#34d399", "#fbbf24", "#a78bfa", "#f87171", "#22d3ee", "#a3e635"];
+
+/** Set a speaker's display name and optionally its color */
+export function renameSpeaker(
+  speakers: Speaker[],
+  speakerId: string,
+  name: string,
+  color?: string,
+): Promise<Speaker[]> {
+  return invoke<Speaker[]>("rename_speaker", { speakers, speakerId, name, color });
+}
+
+/** Relabel `fromIds` as `intoId` everywhere and drop them from the table */
+export function mergeSpeakers(
+  speakers: Speaker[],
+  words: Word[],
+  sentences: Sentence[],
+  fromIds: string[],
+  intoId: string,
+): Promise<SpeakerMerge> {
+  return invoke<SpeakerMerge>("merge_speakers", { speakers, words, sentences, fromIds, intoId });
+}
+
+/** Timeline entries spoken by any of `speakerIds`, in timeline order */
+export function filterEntriesBySpeaker(
+  entries: TimelineEntry[],
+  sentences: Sentence[],
+  speakerIds: string[],
+): Promise<TimelineEntry[]> {
+  return invoke<TimelineEntry[]>("filter_entries_by_speaker", { entries, sentences, speakerIds });
+}
+
+/** The speaker of most of a sentence's words, if any word has one */
+export function majoritySpeaker(wordIds: string[], wordMap: Map<string, Word>): string | undefined {
+  const counts = new Map<string, number>();
+  for (const id of wordIds) {
+    const speakerId = wordMap.get(id)?.speakerId;
+    if (speakerId) counts.set(speakerId, (counts.get(speakerId) ?? 0) + 1);
+  }
+  let best: string | undefined;
+  for (const [speakerId, count] of counts) {
+    if (!best || count > counts.get(best)!) best = speakerId;
+  }
+  return best;
+}
+
+/**
+ * Keep existing speakers (and their names) and add any new ids found in
+ * `words` as "Speaker N" with the next palette color.
+ */
+export function buildSpeakerTable(words: Word[], existing: Speaker[] = []): Speaker[] {
+  const speakers = [...existing];
+  const known = new Set(existing.map((s) => s.id));
+  for (const word of words) {
+    if (!word.speakerId || known.has(word.speakerId)) continue;
+    known.add(word.speakerId);
+    speakers.push({
+      id: word.speakerId,
+      name: `Speaker ${speakers.length + 1}`,
+      color: SPEAKER_COLORS[speakers.length % SPEAKER_COLORS.length],
+    });
+  }
+  return speakers;
+}
@@ -1,9 +1,80 @@
+import { invoke } from "@tauri-apps/api/core";
 import { fetch } from "@tauri-apps/plugin-http";
 import type { ElevenLabsTranscriptResponse, Word } from "../types";
 import { getCachedTranscription, setCachedTranscription } from "./cache";
 
 const API_URL = "https://api.elevenlabs.io/v1/speech-to-text";
 
+/** Which service the backend transcribes with; API keys only come from its environment */
+export type TranscriptionConfig =
+  | { provider: "elevenlabs"; model?: string }
+  | { provider: "openai"; baseUrl?: string; model?: string };
+
+/** A word as normalized by the backend, whichever provider produced it */
+export interface TranscriptWord {
+  text: string;
+  start: number;
+  end: number;
+  confidence: number;
+  speaker: string | null;
+}
+
+export interface Transcript {
+  provider: string;
+  model: string;
+  language: string | null;
+  words: TranscriptWord[];
+}
+
+/**
+ * Provider from `VITE_TRANSCRIPTION_PROVIDER` ("elevenlabs" or "openai"), with
+ * `VITE_TRANSCRIPTION_BASE_URL` pointing "openai" at a local whisper.cpp server
+ */
+export function transcriptionConfig(): TranscriptionConfig {
+  const model = import.meta.env.VITE_TRANSCRIPTION_MODEL || undefined;
+  if (import.meta.env.VITE_TRANSCRIPTION_PROVIDER === "openai") {
+    return {
+      provider: "openai",
+      baseUrl: import.meta.env.VITE_TRANSCRIPTION_BASE_URL || undefined,
+      model,
+    };
+  }
+  return { provider: "elevenlabs", model };
+}
+
+/**
+ * Transcribe a source in the backend: only its extracted audio is uploaded and
+ * the result is cached by CID, provider and model.
+ */
+export async function transcribeSource(
+  path: string,
+  cid: string,
+  config: TranscriptionConfig = transcriptionConfig()
+): Promise<Transcript> {
+  console.log(`[transcribe] transcribeSource: ${path} via ${config.provider}, CID=${cid.substring(0, 8)}`);
+  return invoke<Transcript>("transcribe_source", { path, cid, config });
+}
+
+/**
+ * Diarization labels ("speaker_0") are only unique within one recording, so
+ * they are scoped to the source; the same person across sources can be merged.
+ */
+function speakerIdFor(sourceId: string, label?: string | null): string | undefined {
+  return label ? `${sourceId}:${label}` : undefined;
+}
+
+export function mapTranscriptWords(transcript: Transcript, sourceId: string): Word[] {
+  return transcript.words.map((word, index) => ({
+    id: `word-${sourceId}-${index}`,
+    word: word.text,
+    confidence: word.confidence,
+    sourceId,
+    start: word.start,
+    end: word.end,
+    speakerId: speakerIdFor(sourceId, word.speaker),
+  }));
+}
+
 /**
  * Transcribe a file with optional CID-based caching.
  * If a CID is provided and a cached result exists, returns the cached result.
@@ -79,5 +150,6 @@ export function mapTranscriptToWords(
       sourceId,
       start: word.start,
       end: word.end,
+      speakerId: speakerIdFor(sourceId, word.speaker_id),
     }));
 }
@@ -2,6 +2,7 @@ import { useState } from "react";
 import { Dialog } from "@base-ui-components/react/dialog";
 import { Menu } from "@base-ui-components/react/menu";
 import type { Source } from "../../types";
+import { thumbnailSrc } from "../../utils/video";
 
 interface SourceCardProps {
   source: Source;
@@ -16,7 +17,7 @@ export function SourceCard({ source, onRemove }: SourceCardProps) {
       <div className="group relative aspect-[4/3] overflow-hidden bg-[#111]">
         {source.thumbnail ? (
           <img
-            src={source.thumbnail}
+            src={thumbnailSrc(source.thumbnail)}
             alt={source.name}
             className="h-full w-full object-cover transition-opacity duration-150 group-hover:opacity-80"
           />
@@ -14,6 +14,7 @@ import { ArrowClockwise, ChatCircle, Wrench, CaretDown } from "@phosphor-icons/r
 import { Menu } from "@base-ui/react/menu";
 import { useHistoryStore } from "../../stores/useHistoryStore";
 import { executeAgenticEdit } from "../../api/agenticEdit";
+import { errorMessage } from "../../utils/errors";
 
 // Action performed by the assistant
 interface ActionItem {
@@ -139,7 +140,7 @@ export function ConversationPanel() {
       });
     } catch (err) {
       // Error already handled by onError callback, but just in case
-      const errorMsg = err instanceof Error ? err.message : String(err);
+      const errorMsg = errorMessage(err);
       setMessages((prev) =>
         prev.map((msg) =>
           msg.id === streamingId
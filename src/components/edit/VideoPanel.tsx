@@ -5,7 +5,7 @@ import { PlaybackControls } from "./PlaybackControls";
 import { usePlaybackStore, FPS } from "../../stores/usePlaybackStore";
 import { useTimelineSegments } from "../../hooks/useTimelineSegments";
 import { useSourcesStore } from "../../stores/useSourcesStore";
-import { getVideoUrl } from "../../lib/assetUrl";
+import { getSourceUrl, getVideoUrl } from "../../lib/assetUrl";
 
 export function VideoPanel() {
   const playerRef = useRef<PlayerRef>(null);
@@ -70,16 +70,19 @@ export function VideoPanel() {
   const isPortrait = compositionHeight > compositionWidth;
 
 
-  // Build video URLs synchronously using asset protocol
+  // Build media URLs synchronously; hashed sources stream over lexi:// with Range support
   const videoUrls = useMemo(() => {
+    const sourceByPath = new Map(sources.map((s) => [s.path, s]));
     const urls: Record<string, string> = {};
     for (const seg of segments) {
-      if (!urls[seg.sourcePath]) {
-        urls[seg.sourcePath] = getVideoUrl(seg.sourcePath);
+      for (const path of [seg.sourcePath, seg.audioSourcePath]) {
+        if (path && !urls[path]) {
+          urls[path] = getSourceUrl(sourceByPath.get(path) ?? { path });
+        }
       }
     }
     return urls;
-  }, [segments]);
+  }, [segments, sources]);
 
   // Only subscribe to what we need - NOT currentFrame (causes 30fps re-renders)
   const isPlaying = usePlaybackStore((s) => s.isPlaying);
@@ -3,8 +3,10 @@ import { invoke } from "@tauri-apps/api/core";
 import { save } from "@tauri-apps/plugin-dialog";
 import { ArrowRight } from "@phosphor-icons/react";
 import { useTimelineSegments } from "../../hooks/useTimelineSegments";
+import { toExportSegments } from "../../hooks/useExport";
 import { useExportProgress } from "../../hooks/useExportProgress";
 import { ExportProgress } from "./ExportProgress";
+import { errorMessage } from "../../utils/errors";
 
 export type ExportPreset = "fast" | "standard" | "high";
 export type ExportResolution = "original" | "4k" | "1080p" | "720p";
@@ -109,18 +111,23 @@ export function LocalExportTab({
 
       setOutputPath(path);
 
-      const exportSegments = segments.map((seg) => ({
-        sourcePath: seg.sourcePath,
-        startTime: seg.sourceStart,
-        endTime: seg.sourceEnd,
-      }));
+      const exportSegments = toExportSegments(segments);
+
+      // Without a target resolution the canvas is the first segment's source size;
+      // an unknown (e.g. persisted) value falls back to "Original"
+      const target = RESOLUTIONS.find((r) => r.id === resolution) ?? RESOLUTIONS[0];
+      const options =
+        canChangeResolution && target.width && target.height
+          ? { width: target.width, height: target.height }
+          : undefined;
 
       await invoke("export_video", {
         segments: exportSegments,
         outputPath: path,
+        options,
       });
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     } finally {
       setIsExporting(false);
     }
@@ -2,11 +2,32 @@ import { useState, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { save } from "@tauri-apps/plugin-dialog";
 import { useTimelineSegments } from "./useTimelineSegments";
+import { errorMessage } from "../utils/errors";
+import type { Segment } from "../types";
 
 export interface ExportSegment {
   sourcePath: string;
   startTime: number;
   endTime: number;
+  // Separate audio (B-roll or multicam angle); its range sets the segment duration
+  audioPath?: string;
+  audioStart?: number;
+  audioEnd?: number;
+}
+
+/**
+ * Timeline segments in the shape `export_video` expects. Segment paths are the
+ * original `Source.path`, never a playback proxy, so exports keep full quality.
+ */
+export function toExportSegments(segments: Segment[]): ExportSegment[] {
+  return segments.map((seg) => ({
+    sourcePath: seg.sourcePath,
+    startTime: seg.sourceStart,
+    endTime: seg.sourceEnd,
+    audioPath: seg.audioSourcePath,
+    audioStart: seg.audioStart,
+    audioEnd: seg.audioEnd,
+  }));
 }
 
 export interface ExportProgress {
@@ -46,11 +67,7 @@ export function useExport() {
       }
 
       // Build export segments
-      const exportSegments: ExportSegment[] = segments.map((seg) => ({
-        sourcePath: seg.sourcePath,
-        startTime: seg.sourceStart,
-        endTime: seg.sourceEnd,
-      }));
+      const exportSegments = toExportSegments(segments);
 
       setProgress({ current: 0, total: segments.length, phase: "rendering" });
 
@@ -62,7 +79,7 @@ export function useExport() {
 
       setProgress({ current: segments.length, total: segments.length, phase: "complete" });
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     } finally {
       setIsExporting(false);
     }
@@ -3,7 +3,9 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import { tempDir } from "@tauri-apps/api/path";
 import { useTimelineSegments } from "./useTimelineSegments";
+import { toExportSegments } from "./useExport";
 import { createPost, getApiKey, type SupportedPlatform, type PlatformTarget, type Account } from "../api/late";
+import { errorMessage } from "../utils/errors";
 
 interface MediaUploadResult {
   url: string;
@@ -99,11 +101,7 @@ export function usePublish() {
           }
         });
 
-        const exportSegments = segments.map((seg) => ({
-          sourcePath: seg.sourcePath,
-          startTime: seg.sourceStart,
-          endTime: seg.sourceEnd,
-        }));
+        const exportSegments = toExportSegments(segments);
 
         await invoke("export_video", {
           segments: exportSegments,
@@ -182,12 +180,12 @@ export function usePublish() {
 
         return publishResult;
       } catch (e) {
-        const errorMessage = e instanceof Error ? e.message : String(e);
-        setError(errorMessage);
+        const message = errorMessage(e);
+        setError(message);
         setProgress({
           phase: "error",
           percent: 0,
-          message: errorMessage,
+          message,
         });
         return null;
       } finally {
@@ -2,6 +2,7 @@ import { useMemo } from "react";
 import { useProjectStore } from "../stores/useProjectStore";
 import { useSourcesStore } from "../stores/useSourcesStore";
 import { secondsToFrames } from "../stores/usePlaybackStore";
+import { angleCovers, chooseAutoAngles, resolveEntryAngle } from "../utils/angles";
 import type { Segment } from "../types";
 
 /**
@@ -11,18 +12,30 @@ import type { Segment } from "../types";
  * - Unedited content from same source = ONE segment
  * - Only break when: word deleted, sentence reordered, or source changes
  *
+ * Entries in a multicam angle group show the chosen angle's picture over the
+ * group's primary audio, shifted by the sync offsets.
+ *
  * This minimizes Remotion <Sequence> elements and avoids seeking jitter.
  */
 export function useTimelineSegments(): Segment[] {
   const timeline = useProjectStore((s) => s.timeline);
   const sentences = useProjectStore((s) => s.sentences);
   const words = useProjectStore((s) => s.words);
+  const syncGroups = useProjectStore((s) => s.syncGroups);
+  const angleGroups = useProjectStore((s) => s.angleGroups);
   const sources = useSourcesStore((s) => s.sources);
 
   return useMemo(() => {
     const sourceMap = new Map(sources.map((s) => [s.id, s]));
     const sentenceMap = new Map(sentences.map((s) => [s.sentenceId, s]));
     const wordMap = new Map(words.map((w) => [w.id, w]));
+    const autoAngles = chooseAutoAngles(
+      timeline.entries,
+      sentenceMap,
+      angleGroups,
+      syncGroups,
+      (s) => s.speakerId
+    );
 
     // First pass: Build time ranges from timeline entries
     // Use sentence-level timing when possible, only go word-level when words are excluded
@@ -40,7 +53,7 @@ export function useTimelineSegments(): Segment[] {
       audioEnd?: number;
       text: string;
       hasWordDeletions: boolean; // True if this range came from a sentence with deleted words
-      hasVideoOverride: boolean; // True if this range has a video override (don't merge)
+      hasVideoOverride: boolean; // True if this range has a video override or angle (don't merge)
     }> = [];
 
     for (const entry of timeline.entries) {
@@ -50,14 +63,25 @@ export function useTimelineSegments(): Segment[] {
       const sentence = sentenceMap.get(entry.sentenceId);
       if (!sentence) continue;
 
-      // Determine video source (may be overridden with B-roll)
+      // Determine video source (may be overridden with B-roll, or a multicam angle)
       const hasOverride = !!entry.videoOverride;
-      const videoSourceId = entry.videoOverride?.sourceId ?? entry.sourceId;
+      let angle = hasOverride
+        ? null
+        : resolveEntryAngle(entry, autoAngles.get(entry.sentenceId), angleGroups, syncGroups);
+      // Where the angle has no picture, play the entry's own source instead
+      const angleDuration = angle ? sourceMap.get(angle.videoSourceId)?.duration : undefined;
+      if (angle && !angleCovers(angle, sentence.startTime, sentence.endTime, angleDuration)) {
+        angle = null;
+      }
+      const hasSeparateAudio = hasOverride || !!angle;
+      const videoShift = angle?.videoShift ?? 0;
+      const videoSourceId = entry.videoOverride?.sourceId ?? angle?.videoSourceId ?? entry.sourceId;
       const videoSource = sourceMap.get(videoSourceId);
       if (!videoSource) continue;
 
-      // Determine audio source (always original sentence source)
-      const audioSourceId = entry.sourceId;
+      // Determine audio source (original sentence source, or the angle group's primary audio)
+      const audioShift = angle?.audioShift ?? 0;
+      const audioSourceId = angle?.audioSourceId ?? entry.sourceId;
       const audioSource = sourceMap.get(audioSourceId);
 
       const excludedWordIds = new Set(entry.excludedWordIds);
@@ -66,8 +90,8 @@ export function useTimelineSegments(): Segment[] {
       // No words excluded OR no word data → use sentence timing directly (ONE range)
       if (!hasExcludedWords || sentence.wordIds.length === 0) {
         // Video timing from override (or sentence if no override)
-        const videoStart = entry.videoOverride?.start ?? sentence.startTime;
-        const videoEnd = entry.videoOverride?.end ?? sentence.endTime;
+        const videoStart = entry.videoOverride?.start ?? sentence.startTime + videoShift;
+        const videoEnd = entry.videoOverride?.end ?? sentence.endTime + videoShift;
 
         const range: typeof timeRanges[0] = {
           sentenceId: entry.sentenceId,
@@ -77,15 +101,15 @@ export function useTimelineSegments(): Segment[] {
           end: videoEnd,
           text: entry.text,
           hasWordDeletions: false,
-          hasVideoOverride: hasOverride,
+          hasVideoOverride: hasSeparateAudio,
         };
 
         // Add separate audio source if video is overridden
-        if (hasOverride && audioSource) {
+        if (hasSeparateAudio && audioSource) {
           range.audioSourceId = audioSourceId;
           range.audioSourcePath = audioSource.path;
-          range.audioStart = sentence.startTime;
-          range.audioEnd = sentence.endTime;
+          range.audioStart = sentence.startTime + audioShift;
+          range.audioEnd = sentence.endTime + audioShift;
         }
 
         timeRanges.push(range);
@@ -110,19 +134,19 @@ export function useTimelineSegments(): Segment[] {
               sourceId: videoSourceId,
               sourcePath: videoSource.path,
               // For video override with word deletions, use proportional video timing
-              start: hasOverride ? entry.videoOverride!.start : rangeStart,
-              end: hasOverride ? entry.videoOverride!.end : rangeEnd,
+              start: hasOverride ? entry.videoOverride!.start : rangeStart + videoShift,
+              end: hasOverride ? entry.videoOverride!.end : rangeEnd + videoShift,
               text: rangeText.join(" "),
               hasWordDeletions: true,
-              hasVideoOverride: hasOverride,
+              hasVideoOverride: hasSeparateAudio,
             };
 
             // Add separate audio source if video is overridden
-            if (hasOverride && audioSource) {
+            if (hasSeparateAudio && audioSource) {
               range.audioSourceId = audioSourceId;
               range.audioSourcePath = audioSource.path;
-              range.audioStart = rangeStart;
-              range.audioEnd = rangeEnd;
+              range.audioStart = rangeStart + audioShift;
+              range.audioEnd = rangeEnd + audioShift;
             }
 
             timeRanges.push(range);
@@ -146,18 +170,18 @@ export function useTimelineSegments(): Segment[] {
           sentenceId: entry.sentenceId,
           sourceId: videoSourceId,
           sourcePath: videoSource.path,
-          start: hasOverride ? entry.videoOverride!.start : rangeStart,
-          end: hasOverride ? entry.videoOverride!.end : rangeEnd,
+          start: hasOverride ? entry.videoOverride!.start : rangeStart + videoShift,
+          end: hasOverride ? entry.videoOverride!.end : rangeEnd + videoShift,
           text: rangeText.join(" "),
           hasWordDeletions: true,
-          hasVideoOverride: hasOverride,
+          hasVideoOverride: hasSeparateAudio,
         };
 
-        if (hasOverride && audioSource) {
+        if (hasSeparateAudio && audioSource) {
           range.audioSourceId = audioSourceId;
           range.audioSourcePath = audioSource.path;
-          range.audioStart = rangeStart;
-          range.audioEnd = rangeEnd;
+          range.audioStart = rangeStart + audioShift;
+          range.audioEnd = rangeEnd + audioShift;
         }
 
         timeRanges.push(range);
@@ -215,8 +239,8 @@ export function useTimelineSegments(): Segment[] {
               segments[segments.length - 1].durationFrames
             : 0;
 
-        // For video override, use AUDIO duration (sentence audio drives timing)
-        const audioDuration = range.audioEnd && range.audioStart
+        // For video override or angle, use AUDIO duration (sentence audio drives timing)
+        const audioDuration = range.audioEnd !== undefined && range.audioStart !== undefined
           ? range.audioEnd - range.audioStart
           : range.end - range.start;
         const durationFrames = secondsToFrames(audioDuration);
@@ -250,7 +274,7 @@ export function useTimelineSegments(): Segment[] {
     }
 
     return segments;
-  }, [timeline, sentences, words, sources]);
+  }, [timeline, sentences, words, sources, syncGroups, angleGroups]);
 }
 
 /**
@@ -1,8 +1,33 @@
 import { convertFileSrc } from "@tauri-apps/api/core";
 
+/** Derived files served next to a source by the `lexi://` protocol */
+export type MediaArtifactKind = "thumbnails" | "filmstrips" | "audio" | "proxies" | "frames";
+
 /**
  * Get a video URL using Tauri's asset protocol.
  */
 export function getVideoUrl(filePath: string): string {
   return convertFileSrc(filePath);
 }
+
+/**
+ * URL of a source (or a file derived from it) on the `lexi://` protocol, which
+ * supports Range requests so players and fetch() stream instead of loading it all.
+ * The scheme's origin differs per platform, so it is taken from convertFileSrc.
+ */
+export function getMediaUrl(cid: string, artifact?: { kind: MediaArtifactKind; name: string }): string {
+  const base = convertFileSrc("", "lexi");
+  const path = artifact ? `${cid}/${artifact.kind}/${artifact.name}` : cid;
+  return `${base}media/${path}`;
+}
+
+/**
+ * URL to play a source from: its proxy when one was generated, else the
+ * original streamed by CID, else the asset protocol. Export never uses this.
+ */
+export function getSourceUrl(source: { path: string; cid?: string; proxy?: string }): string {
+  if (!source.cid) return getVideoUrl(source.path);
+  return source.proxy
+    ? getMediaUrl(source.cid, { kind: "proxies", name: source.proxy })
+    : getMediaUrl(source.cid);
+}
@@ -3,9 +3,11 @@ import { invoke } from "@tauri-apps/api/core";
 import { ArrowRight, X, VideoCamera } from "@phosphor-icons/react";
 import { UploadCard } from "../components";
 import { useSourcesStore } from "../stores";
-import { generateCid } from "../utils/cid";
-import { generateThumbnail } from "../utils/video";
+import { useProjectStore } from "../stores/useProjectStore";
+import { generateCid, verifyCid } from "../utils/cid";
+import { generateThumbnail, thumbnailSrc } from "../utils/video";
 import { transcribeSourceBackground, describeSourceBackground } from "../api/backgroundProcessing";
+import { generateProxy } from "../api/proxy";
 import type { Source } from "../types";
 
 interface AddClipsPageProps {
@@ -20,8 +22,14 @@ function formatDuration(seconds?: number): string {
 }
 
 export function AddClipsPage({ onNext }: AddClipsPageProps) {
-  const { sources, addSources, updateSourceCid, removeSource } =
-    useSourcesStore();
+  const {
+    sources,
+    addSources,
+    updateSourceCid,
+    updateSourceThumbnail,
+    updateSourceProxy,
+    removeSource,
+  } = useSourcesStore();
 
   const handleSelectFiles = async () => {
     const selected = await open({
@@ -40,8 +48,7 @@ export function AddClipsPage({ onNext }: AddClipsPageProps) {
 
     const newSources = await Promise.all(
       paths.map(async (path) => {
-        const [thumbnail, duration, dimensions] = await Promise.all([
-          generateThumbnail(path),
+        const [duration, dimensions] = await Promise.all([
           invoke<number>("get_duration", { videoPath: path }).catch(() => undefined),
           invoke<{ width: number; height: number }>("get_dimensions", { videoPath: path }).catch(() => undefined),
         ]);
@@ -49,7 +56,7 @@ export function AddClipsPage({ onNext }: AddClipsPageProps) {
         return {
           id: crypto.randomUUID(),
           name,
-          thumbnail,
+          thumbnail: "",
           path,
           duration,
           width: dimensions?.width,
@@ -60,14 +67,42 @@ export function AddClipsPage({ onNext }: AddClipsPageProps) {
 
     addSources(newSources);
 
-    // Generate CIDs in background after sources are added, then start processing
+    // Thumbnail and proxy are keyed by CID, so a source used in another project is ready at once
+    const generateArtifacts = (source: Source, cid: string) =>
+      Promise.all([
+        generateThumbnail(source.path, cid).then((thumbnails) => {
+          if (thumbnails) updateSourceThumbnail(source.id, thumbnails.medium);
+        }),
+        generateProxy(source.path, cid)
+          .then((proxy) => {
+            if (proxy) updateSourceProxy(source.id, proxy.name);
+          })
+          .catch((err) => console.warn(`[proxy] Failed for ${source.path}:`, err)),
+      ]);
+
+    // Sampled CIDs take milliseconds even for huge files, so processing starts at once.
+    // The full hash runs after it and upgrades the CID, moving the cache along.
     newSources.forEach((source) => {
-      generateCid(source.path).then((cid) => {
+      generateCid(source.path, "sampled").then(async (cid) => {
         updateSourceCid(source.id, cid);
 
-        // Start background processing immediately (fire and forget)
-        transcribeSourceBackground(source.path, cid);
-        describeSourceBackground(source.path, source.duration, cid);
+        // Start background processing immediately
+        await Promise.allSettled([
+          generateArtifacts(source, cid),
+          transcribeSourceBackground(source.path, cid),
+          describeSourceBackground(source.path, source.duration, cid),
+        ]);
+
+        const verification = await verifyCid(source.path, cid).catch((err) => {
+          console.warn(`[cid] Full hash failed for ${source.path}:`, err);
+          return null;
+        });
+        if (!verification || verification.status === "verified") return;
+
+        // Artifact paths embed the CID; after an upgrade these are cache hits
+        updateSourceCid(source.id, verification.cid);
+        await generateArtifacts(source, verification.cid);
+        useProjectStore.getState().markDirty();
       });
     });
   };
@@ -104,7 +139,7 @@ export function AddClipsPage({ onNext }: AddClipsPageProps) {
                 {/* Thumbnail */}
                 {source.thumbnail ? (
                   <img
-                    src={source.thumbnail}
+                    src={thumbnailSrc(source.thumbnail)}
                     alt={source.name}
                     className="absolute inset-0 h-full w-full object-cover transition-transform duration-300 group-hover:scale-105"
                   />
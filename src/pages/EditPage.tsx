@@ -9,6 +9,7 @@ import { useProjectStore } from "../stores/useProjectStore";
 import { runPipeline } from "../api/processingPipeline";
 import { executeAgenticAssemblyCut } from "../api/agenticEdit";
 import { useSaveProject } from "../hooks/useSaveProject";
+import { errorMessage } from "../utils/errors";
 
 function ResizeHandle({ orientation = "horizontal" }: { orientation?: "horizontal" | "vertical" }) {
   const isHorizontal = orientation === "horizontal";
@@ -48,6 +49,7 @@ export function EditPage() {
   const setSentences = useProjectStore((s) => s.setSentences);
   const setTranscriptlessSourceIds = useProjectStore((s) => s.setTranscriptlessSourceIds);
   const setBrollClassifications = useProjectStore((s) => s.setBrollClassifications);
+  const setSpeakers = useProjectStore((s) => s.setSpeakers);
   const initializeTimeline = useProjectStore((s) => s.initializeTimeline);
 
   // Run processing pipeline when sources are available and not already processed
@@ -84,6 +86,7 @@ export function EditPage() {
       setSentences(result.sentences);
       setTranscriptlessSourceIds(result.transcriptlessSourceIds);
       setBrollClassifications(result.brollClassifications);
+      setSpeakers(result.speakers);
       // Initialize the timeline from fresh sentences
       initializeTimeline(result.sentences);
 
@@ -109,7 +112,7 @@ export function EditPage() {
       // Auto-save after first assembly cut completes
       await save();
     } catch (e) {
-      setError(e instanceof Error ? e.message : String(e));
+      setError(errorMessage(e));
     }
   }, [
     sources,
@@ -122,6 +125,7 @@ export function EditPage() {
     setSentences,
     setTranscriptlessSourceIds,
     setBrollClassifications,
+    setSpeakers,
     initializeTimeline,
     updateSourceDescriptions,
     save,
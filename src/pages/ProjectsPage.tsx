@@ -5,6 +5,7 @@ import "ldrs/react/Quantum.css";
 import { useProjectStore } from "../stores/useProjectStore";
 import { loadProjects, saveProjects } from "../api/projects";
 import type { ProjectMeta } from "../types";
+import { thumbnailSrc } from "../utils/video";
 
 export function ProjectsPage() {
   const [name, setName] = useState("");
@@ -148,7 +149,7 @@ export function ProjectsPage() {
                   {/* Full cell background */}
                   {project.thumbnail ? (
                     <img
-                      src={project.thumbnail}
+                      src={thumbnailSrc(project.thumbnail)}
                       alt={project.name}
                       className="absolute inset-0 h-full w-full object-cover transition-transform duration-300 group-hover:scale-105"
                     />
@@ -9,9 +9,15 @@ import type {
   Source,
   BrollClassification,
   VideoOverride,
+  SyncGroup,
+  AngleGroup,
+  Speaker,
 } from "../types";
 import { saveProjectData, loadProjectData, createTimelineFromSentences, loadProjects, saveProjects, type ProjectData } from "../api/projects";
+import { mergeSpeakers as mergeSpeakersCommand, renameSpeaker as renameSpeakerCommand } from "../api/speakers";
 import { useSourcesStore } from "./useSourcesStore";
+import { isSampledCid, registerMediaSource, verifyCid } from "../utils/cid";
+import { generateThumbnail } from "../utils/video";
 
 interface ProjectState {
   // Project identity
@@ -33,6 +39,13 @@ interface ProjectState {
   // Sources without transcripts (silent/no audio)
   transcriptlessSourceIds: string[];
 
+  // Multicam sources aligned by audio
+  syncGroups: SyncGroup[];
+  angleGroups: AngleGroup[];
+
+  // Diarized speakers, referenced by Word.speakerId and Sentence.speakerId
+  speakers: Speaker[];
+
   // B-roll classifications
   brollClassifications: Map<string, BrollClassification>;
 
@@ -72,10 +85,22 @@ interface ProjectState {
   restoreSentencesByIds: (sentenceIds: string[]) => void;
   reorderSentencesById: (sentenceIds: string[]) => void;
   setVideoOverride: (sentenceId: string, override: VideoOverride | null) => void;
+  setEntryAngle: (sentenceId: string, angleSourceId: string | null) => void;
 
   // Transcriptless tracking
   setTranscriptlessSourceIds: (sourceIds: string[]) => void;
 
+  // Multicam actions
+  setSyncGroup: (group: SyncGroup) => void;
+  removeSyncGroup: (groupId: string) => void;
+  setAngleGroup: (group: AngleGroup) => void;
+  removeAngleGroup: (groupId: string) => void;
+
+  // Speaker actions
+  setSpeakers: (speakers: Speaker[]) => void;
+  renameSpeaker: (speakerId: string, name: string, color?: string) => Promise<void>;
+  mergeSpeakers: (fromIds: string[], intoId: string) => Promise<void>;
+
   // B-roll classification actions
   setBrollClassifications: (classifications: BrollClassification[]) => void;
   clearBrollClassifications: () => void;
@@ -107,6 +132,9 @@ const initialState = {
   sentences: [],
   timeline: emptyTimeline,
   transcriptlessSourceIds: [] as string[],
+  syncGroups: [] as SyncGroup[],
+  angleGroups: [] as AngleGroup[],
+  speakers: [] as Speaker[],
   brollClassifications: new Map<string, BrollClassification>(),
   orderedGroupIds: [],
   excludedGroupIds: [],
@@ -146,6 +174,9 @@ export const useProjectStore = create<ProjectState>((set) => ({
           segmentGroups: data.segmentGroups,
           timeline: data.timeline ?? emptyTimeline,
           transcriptlessSourceIds: data.transcriptlessSourceIds,
+          syncGroups: data.syncGroups ?? [],
+          angleGroups: data.angleGroups ?? [],
+          speakers: data.speakers ?? [],
           brollClassifications: brollMap,
           orderedGroupIds: data.segmentGroups.map((g) => g.groupId),
           excludedGroupIds: [],
@@ -155,8 +186,38 @@ export const useProjectStore = create<ProjectState>((set) => ({
           phase: (data.timeline?.entries.length ?? 0) > 0 || data.segmentGroups.length > 0 ? "ready" : "idle",
         });
 
+        // Hashes from earlier sessions may not be known to the media protocol yet;
+        // register them before any player asks for lexi://media/<cid>
+        await Promise.all(
+          data.sources
+            .filter((source) => source.cid)
+            .map((source) =>
+              registerMediaSource(source.path, source.cid!).catch((err) =>
+                console.warn(`[project] Failed to register ${source.path} for streaming:`, err)
+              )
+            )
+        );
+
         // Restore sources to the sources store
         useSourcesStore.getState().setSources(data.sources);
+
+        // A project saved before a full hash finished still holds sampled CIDs
+        for (const source of data.sources) {
+          if (!source.cid) continue;
+          const sampledCid = source.cid;
+          isSampledCid(sampledCid)
+            .then(async (sampled) => {
+              if (!sampled) return;
+              const { cid } = await verifyCid(source.path, sampledCid);
+              const sources = useSourcesStore.getState();
+              sources.updateSourceCid(source.id, cid);
+              // Thumbnail paths embed the CID; after an upgrade this is a cache hit
+              const thumbnails = await generateThumbnail(source.path, cid);
+              if (thumbnails) sources.updateSourceThumbnail(source.id, thumbnails.medium);
+              useProjectStore.getState().markDirty();
+            })
+            .catch((err) => console.warn(`[project] Full hash failed for ${source.path}:`, err));
+        }
       } else {
         // No saved data - new project
         set({
@@ -199,6 +260,9 @@ export const useProjectStore = create<ProjectState>((set) => ({
       segmentGroups: state.segmentGroups,
       timeline: state.timeline,
       transcriptlessSourceIds: state.transcriptlessSourceIds,
+      syncGroups: state.syncGroups,
+      angleGroups: state.angleGroups,
+      speakers: state.speakers,
       brollClassifications: Array.from(state.brollClassifications.values()),
       savedAt: Date.now(),
     };
@@ -240,6 +304,9 @@ export const useProjectStore = create<ProjectState>((set) => ({
         segmentGroups: data.segmentGroups,
         timeline: data.timeline ?? emptyTimeline,
         transcriptlessSourceIds: data.transcriptlessSourceIds,
+        syncGroups: data.syncGroups ?? [],
+        angleGroups: data.angleGroups ?? [],
+        speakers: data.speakers ?? [],
         brollClassifications: brollMap,
         orderedGroupIds: data.segmentGroups.map((g) => g.groupId),
         excludedGroupIds: [],
@@ -429,9 +496,67 @@ export const useProjectStore = create<ProjectState>((set) => ({
       isDirty: true,
     })),
 
+  setEntryAngle: (sentenceId: string, angleSourceId: string | null) =>
+    set((state) => ({
+      timeline: {
+        ...state.timeline,
+        entries: state.timeline.entries.map((entry) =>
+          entry.sentenceId === sentenceId
+            ? { ...entry, angleSourceId: angleSourceId ?? undefined }
+            : entry
+        ),
+      },
+      isDirty: true,
+    })),
+
   // Transcriptless tracking
   setTranscriptlessSourceIds: (sourceIds) => set({ transcriptlessSourceIds: sourceIds, isDirty: true }),
 
+  // A source belongs to at most one sync group, so re-syncing replaces overlapping groups
+  setSyncGroup: (group) =>
+    set((state) => {
+      const sourceIds = new Set(group.offsets.map((o) => o.sourceId));
+      const others = state.syncGroups.filter(
+        (g) => g.id !== group.id && !g.offsets.some((o) => sourceIds.has(o.sourceId))
+      );
+      return { syncGroups: [...others, group], isDirty: true };
+    }),
+
+  // Angle groups are built on a sync group and go with it
+  removeSyncGroup: (groupId) =>
+    set((state) => ({
+      syncGroups: state.syncGroups.filter((g) => g.id !== groupId),
+      angleGroups: state.angleGroups.filter((g) => g.syncGroupId !== groupId),
+      isDirty: true,
+    })),
+
+  setAngleGroup: (group) =>
+    set((state) => ({
+      angleGroups: [...state.angleGroups.filter((g) => g.id !== group.id), group],
+      isDirty: true,
+    })),
+
+  removeAngleGroup: (groupId) =>
+    set((state) => ({
+      angleGroups: state.angleGroups.filter((g) => g.id !== groupId),
+      isDirty: true,
+    })),
+
+  // Speaker actions
+  setSpeakers: (speakers) => set({ speakers, isDirty: true }),
+
+  renameSpeaker: async (speakerId, name, color) => {
+    const { speakers } = useProjectStore.getState();
+    const renamed = await renameSpeakerCommand(speakers, speakerId, name, color);
+    set({ speakers: renamed, isDirty: true });
+  },
+
+  mergeSpeakers: async (fromIds, intoId) => {
+    const { speakers, words, sentences } = useProjectStore.getState();
+    const merged = await mergeSpeakersCommand(speakers, words, sentences, fromIds, intoId);
+    set({ ...merged, isDirty: true });
+  },
+
   // B-roll classification actions
   setBrollClassifications: (classifications) =>
     set((state) => {
@@ -6,6 +6,8 @@ interface SourcesState {
   setSources: (sources: Source[]) => void;
   addSources: (sources: Source[]) => void;
   updateSourceCid: (id: string, cid: string) => void;
+  updateSourceThumbnail: (id: string, thumbnail: string) => void;
+  updateSourceProxy: (id: string, proxy: string) => void;
   updateSourceDescriptions: (id: string, descriptions: SourceDescription[]) => void;
   updateSourceDimensions: (path: string, width: number, height: number) => void;
   removeSource: (id: string) => void;
@@ -21,6 +23,14 @@ export const useSourcesStore = create<SourcesState>((set) => ({
     set((state) => ({
       sources: state.sources.map((s) => (s.id === id ? { ...s, cid } : s)),
     })),
+  updateSourceThumbnail: (id, thumbnail) =>
+    set((state) => ({
+      sources: state.sources.map((s) => (s.id === id ? { ...s, thumbnail } : s)),
+    })),
+  updateSourceProxy: (id, proxy) =>
+    set((state) => ({
+      sources: state.sources.map((s) => (s.id === id ? { ...s, proxy } : s)),
+    })),
   updateSourceDescriptions: (id, descriptions) =>
     set((state) => ({
       sources: state.sources.map((s) =>
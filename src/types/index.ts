@@ -26,6 +26,7 @@ export interface Source {
   width?: number;
   height?: number;
   descriptions?: SourceDescription[];
+  proxy?: string;  // Playback proxy name under lexi://media/<cid>/proxies; export always uses path
 }
 
 // --- Project Data Model ---
@@ -38,6 +39,7 @@ export interface Word {
   end: number;
   confidence: number;
   sourceId: string;
+  speakerId?: string;  // Diarized speaker, an id into ProjectData.speakers
 }
 
 export interface Project {
@@ -87,6 +89,15 @@ export interface Sentence {
   startTime: number;
   endTime: number;
   originalGroupId?: string;  // For optional visual grouping
+  speakerId?: string;        // Speaker of most of the sentence's words
+}
+
+// --- Speakers ---
+
+export interface Speaker {
+  id: string;     // Diarization label from transcription, e.g. "speaker_0"
+  name: string;
+  color: string;  // CSS color for labels and timeline tint
 }
 
 // --- Timeline (first-class edit state) ---
@@ -104,6 +115,7 @@ export interface TimelineEntry {
   excluded: boolean;
   excludedWordIds: string[];
   videoOverride?: VideoOverride;
+  angleSourceId?: string;    // Multicam angle to show; the group's primary audio is kept
 }
 
 export interface Timeline {
@@ -111,6 +123,34 @@ export interface Timeline {
   entries: TimelineEntry[];
 }
 
+// --- Multicam ---
+
+export interface SourceOffset {
+  sourceId: string;
+  offset: number;      // Seconds to add to this source's time to get reference time
+  confidence: number;  // 0-1; low means the audio did not clearly line up
+}
+
+export interface SyncGroup {
+  id: string;
+  referenceSourceId: string;
+  offsets: SourceOffset[];  // Includes the reference at offset 0
+}
+
+// How angles are picked for entries without an explicit angleSourceId
+export type AutoSwitch =
+  | { mode: "speaker"; angles: Record<string, string> }  // Speaker id → angle source id
+  | { mode: "cadence"; seconds: number };                // Next angle at the first sentence boundary after this long
+
+export interface AngleGroup {
+  id: string;
+  name: string;
+  syncGroupId: string;
+  audioSourceId: string;     // Audio kept whichever angle is shown
+  angleSourceIds: string[];  // Sources with usable picture, in switching order
+  autoSwitch?: AutoSwitch;
+}
+
 // --- Gemini Video Understanding API Types ---
 
 export interface GeminiFileUploadResponse {
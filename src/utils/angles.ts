@@ -0,0 +1,113 @@
+import type { AngleGroup, Sentence, SyncGroup, TimelineEntry } from "../types";
+
+/** Picture and audio sources for an entry, with the shifts from its own clock */
+export interface AngleChoice {
+  videoSourceId: string;
+  videoShift: number;
+  audioSourceId: string;
+  audioShift: number;
+}
+
+function groupForSource(sourceId: string, angleGroups: AngleGroup[], syncGroups: SyncGroup[]) {
+  for (const group of angleGroups) {
+    const sync = syncGroups.find((g) => g.id === group.syncGroupId);
+    if (sync?.offsets.some((o) => o.sourceId === sourceId)) {
+      return { group, sync };
+    }
+  }
+  return null;
+}
+
+/** Seconds to add to a time in `from` to get the same moment in `to` */
+function shiftBetween(sync: SyncGroup, from: string, to: string): number {
+  const offset = (id: string) => sync.offsets.find((o) => o.sourceId === id)?.offset ?? 0;
+  return offset(from) - offset(to);
+}
+
+/**
+ * Angles picked by each group's auto-switch mode, by sentence id. Cadence mode
+ * moves to the next angle at the first sentence boundary after `seconds` of
+ * output; speaker mode needs `speakerOf` to know who is talking.
+ */
+export function chooseAutoAngles(
+  entries: TimelineEntry[],
+  sentenceMap: Map<string, Sentence>,
+  angleGroups: AngleGroup[],
+  syncGroups: SyncGroup[],
+  speakerOf?: (sentence: Sentence) => string | undefined
+): Map<string, string> {
+  const choices = new Map<string, string>();
+  const cadence = new Map<string, { index: number; elapsed: number }>();
+
+  for (const entry of entries) {
+    if (entry.excluded || entry.angleSourceId || entry.videoOverride) continue;
+    const sentence = sentenceMap.get(entry.sentenceId);
+    const found = groupForSource(entry.sourceId, angleGroups, syncGroups);
+    if (!sentence || !found?.group.autoSwitch || found.group.angleSourceIds.length === 0) continue;
+
+    const { group } = found;
+    const auto = group.autoSwitch!;
+    if (auto.mode === "speaker") {
+      const speaker = speakerOf?.(sentence);
+      const angle = speaker ? auto.angles[speaker] : undefined;
+      if (angle) choices.set(entry.sentenceId, angle);
+      continue;
+    }
+
+    const state = cadence.get(group.id) ?? { index: 0, elapsed: 0 };
+    if (state.elapsed >= auto.seconds) {
+      state.index = (state.index + 1) % group.angleSourceIds.length;
+      state.elapsed = 0;
+    }
+    choices.set(entry.sentenceId, group.angleSourceIds[state.index]);
+    state.elapsed += sentence.endTime - sentence.startTime;
+    cadence.set(group.id, state);
+  }
+
+  return choices;
+}
+
+/**
+ * Resolve which angle's picture an entry shows and which source's audio it
+ * keeps. Returns null when the entry is not in an angle group or would play
+ * its own source unchanged.
+ */
+export function resolveEntryAngle(
+  entry: TimelineEntry,
+  autoAngle: string | undefined,
+  angleGroups: AngleGroup[],
+  syncGroups: SyncGroup[]
+): AngleChoice | null {
+  const found = groupForSource(entry.sourceId, angleGroups, syncGroups);
+  if (!found) return null;
+  const { group, sync } = found;
+
+  // The entry's own source is the default angle unless it has no picture (e.g. a recorder)
+  const ownAngle = group.angleSourceIds.includes(entry.sourceId) ? entry.sourceId : group.angleSourceIds[0];
+  const videoSourceId = entry.angleSourceId ?? autoAngle ?? ownAngle;
+  if (!videoSourceId) return null;
+
+  const audioSourceId = group.audioSourceId;
+  if (videoSourceId === entry.sourceId && audioSourceId === entry.sourceId) return null;
+
+  return {
+    videoSourceId,
+    videoShift: shiftBetween(sync, entry.sourceId, videoSourceId),
+    audioSourceId,
+    audioShift: shiftBetween(sync, entry.sourceId, audioSourceId),
+  };
+}
+
+/**
+ * Whether the angle has picture for `start`-`end` on the entry's own clock; an
+ * angle that started recording late or stopped early has nothing to show there
+ */
+export function angleCovers(
+  angle: AngleChoice,
+  start: number,
+  end: number,
+  angleDuration?: number
+): boolean {
+  if (start + angle.videoShift < 0) return false;
+  return angleDuration === undefined || end + angle.videoShift <= angleDuration;
+}
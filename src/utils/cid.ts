@@ -1,6 +1,64 @@
 import { invoke } from "@tauri-apps/api/core";
 
-export const generateCid = async (filePath: string): Promise<string> => {
-  const cid = await invoke<string>("generate_cid", { path: filePath });
+/**
+ * "full" hashes the whole file; "sampled" hashes its size and a few blocks and
+ * should be followed by verifyCid.
+ */
+export type CidMode = "full" | "sampled";
+
+export interface CidVerification {
+  /** Full CID of the file as it is now */
+  cid: string;
+  previousCid: string;
+  /** "upgraded": a sampled CID was replaced and its cache moved; "changed": the file differs */
+  status: "verified" | "upgraded" | "changed";
+}
+
+export const generateCid = async (filePath: string, mode: CidMode = "full"): Promise<string> => {
+  const cid = await invoke<string>("generate_cid", { path: filePath, mode });
   return cid;
 };
+
+/** Work still writing cache entries and files under a CID, by CID */
+const pendingWork = new Map<string, Set<Promise<unknown>>>();
+
+/** Record work that stores results under `cid`, so a CID upgrade can wait for it */
+export function trackCidWork(cid: string, work: Promise<unknown>): void {
+  const pending = pendingWork.get(cid) ?? new Set<Promise<unknown>>();
+  pending.add(work);
+  pendingWork.set(cid, pending);
+  const done = () => {
+    pending.delete(work);
+    if (pending.size === 0 && pendingWork.get(cid) === pending) pendingWork.delete(cid);
+  };
+  work.then(done, done);
+}
+
+/** Resolves once no tracked work for `cid` is running, including work started meanwhile */
+async function settleCidWork(cid: string): Promise<void> {
+  for (let pending = pendingWork.get(cid); pending?.size; pending = pendingWork.get(cid)) {
+    await Promise.allSettled([...pending]);
+  }
+}
+
+export const isSampledCid = (cid: string): Promise<boolean> =>
+  invoke<boolean>("cid_is_sampled", { cid });
+
+/**
+ * Hash the whole file in the background, upgrading a sampled CID to the full one.
+ * Work still running under the sampled CID has its results moved once it is done.
+ */
+export const verifyCid = async (filePath: string, cid: string): Promise<CidVerification> => {
+  const verification = await invoke<CidVerification>("verify_cid", { path: filePath, cid });
+  if (verification.status === "upgraded") {
+    settleCidWork(cid)
+      .then(() => invoke<number>("migrate_cid", { from: cid, to: verification.cid }))
+      .catch((err) => console.warn(`[cid] Failed to move late results for ${filePath}:`, err));
+  }
+  return verification;
+};
+
+/** Serve an already-hashed source (e.g. from a saved project) at lexi://media/<cid> */
+export const registerMediaSource = async (filePath: string, cid: string): Promise<void> => {
+  await invoke("register_media_source", { path: filePath, cid });
+};
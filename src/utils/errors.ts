@@ -0,0 +1,39 @@
+/**
+ * Structured error returned by every Tauri command.
+ * `code` is stable and can be matched on to offer a recovery action.
+ */
+export interface LexiError {
+  code:
+    | "FFMPEG_MISSING"
+    | "FFMPEG_FAILED"
+    | "FILE_NOT_FOUND"
+    | "PERMISSION_DENIED"
+    | "DISK_FULL"
+    | "IO_ERROR"
+    | "DATABASE_ERROR"
+    | "SERIALIZATION_ERROR"
+    | "HTTP_ERROR"
+    | "AUTH_REJECTED"
+    | "INVALID_INPUT"
+    | "CANCELLED"
+    | "INTERNAL";
+  message: string;
+  details?: {
+    stderr?: string;
+    status?: number;
+    path?: string;
+  };
+}
+
+export function isLexiError(e: unknown): e is LexiError {
+  return typeof e === "object" && e !== null && "code" in e && "message" in e;
+}
+
+/**
+ * Human-readable message for anything thrown by invoke() or regular JS code
+ */
+export function errorMessage(e: unknown): string {
+  if (isLexiError(e)) return e.message;
+  if (e instanceof Error) return e.message;
+  return String(e);
+}
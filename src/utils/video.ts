@@ -1,13 +1,62 @@
-import { invoke } from "@tauri-apps/api/core";
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
 
-export const generateThumbnail = async (filePath: string): Promise<string> => {
+/** Thumbnail files under app data, keyed by CID */
+export interface Thumbnails {
+  small: string;
+  medium: string;
+  large: string;
+}
+
+export const generateThumbnail = async (
+  filePath: string,
+  cid: string,
+): Promise<Thumbnails | null> => {
   try {
-    const thumbnail = await invoke<string>("generate_thumbnail", {
+    return await invoke<Thumbnails>("generate_thumbnail", {
       videoPath: filePath,
+      cid,
     });
-    return thumbnail;
   } catch (error) {
     console.error("Failed to generate thumbnail:", error);
-    return "";
+    return null;
   }
 };
+
+/** Image src for a stored thumbnail: a file path, or a data URI from older projects */
+export const thumbnailSrc = (thumbnail: string): string =>
+  thumbnail.startsWith("data:") ? thumbnail : convertFileSrc(thumbnail);
+
+export interface FilmstripOptions {
+  count?: number;
+  start?: number;
+  end?: number;
+  tileWidth?: number;
+  columns?: number;
+}
+
+export interface FilmstripTile {
+  start: number;
+  end: number;
+  x: number;
+  y: number;
+}
+
+export interface Filmstrip {
+  /** Sprite sheet on disk; load with getVideoUrl (asset protocol) */
+  imagePath: string;
+  tileWidth: number;
+  tileHeight: number;
+  columns: number;
+  rows: number;
+  tiles: FilmstripTile[];
+  /** WebVTT thumbnail track for the same tiles */
+  vtt: string;
+}
+
+/** Sprite sheet of evenly spaced thumbnails for a source or time range, cached by CID */
+export const generateFilmstrip = (
+  filePath: string,
+  cid: string,
+  options?: FilmstripOptions,
+): Promise<Filmstrip> =>
+  invoke<Filmstrip>("generate_filmstrip", { path: filePath, cid, options });
@@ -0,0 +1,69 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/** Peaks at one zoom level; `min[i]`/`max[i]` are 8-bit (-128..127) */
+export interface WaveformLevel {
+  samplesPerPeak: number;
+  min: Int8Array;
+  max: Int8Array;
+}
+
+export interface Waveform {
+  sampleRate: number;
+  /** Finest level first */
+  levels: WaveformLevel[];
+}
+
+/**
+ * Min/max peaks for a source's audio at several zoom levels, cached by CID.
+ * The backend returns the compact binary form; see `parseWaveform`.
+ */
+export async function computeWaveform(path: string, cid: string): Promise<Waveform> {
+  const buffer = await invoke<ArrayBuffer>("compute_waveform", { path, cid });
+  return parseWaveform(buffer);
+}
+
+/**
+ * Parse the `LXWF` binary layout: magic, u16 version, u32 sample rate, u16 level
+ * count, then per level u32 samples per peak, u32 peak count and interleaved i8
+ * min/max pairs. All little endian.
+ */
+export function parseWaveform(buffer: ArrayBuffer): Waveform {
+  const view = new DataView(buffer);
+  const magic = String.fromCharCode(...new Uint8Array(buffer, 0, 4));
+  if (magic !== "LXWF" || view.getUint16(4, true) !== 1) {
+    throw new Error("Unsupported waveform data");
+  }
+
+  const sampleRate = view.getUint32(6, true);
+  const levelCount = view.getUint16(10, true);
+  const levels: WaveformLevel[] = [];
+  let offset = 12;
+
+  for (let l = 0; l < levelCount; l++) {
+    const samplesPerPeak = view.getUint32(offset, true);
+    const count = view.getUint32(offset + 4, true);
+    offset += 8;
+
+    const pairs = new Int8Array(buffer, offset, count * 2);
+    const min = new Int8Array(count);
+    const max = new Int8Array(count);
+    for (let i = 0; i < count; i++) {
+      min[i] = pairs[i * 2];
+      max[i] = pairs[i * 2 + 1];
+    }
+    levels.push({ samplesPerPeak, min, max });
+    offset += count * 2;
+  }
+
+  return { sampleRate, levels };
+}
+
+/** Coarsest level that still has at least one peak per pixel at this zoom */
+export function pickLevel(waveform: Waveform, pixelsPerSecond: number): WaveformLevel {
+  const samplesPerPixel = waveform.sampleRate / pixelsPerSecond;
+  let best = waveform.levels[0];
+  for (const level of waveform.levels) {
+    if (level.samplesPerPeak <= samplesPerPixel) best = level;
+  }
+  return best;
+}